@@ -1,51 +1,210 @@
 //! Event emission helpers for the user profile contract.
 
-use soroban_sdk::{Address, Bytes, Env, Symbol};
+use soroban_sdk::{Address, Bytes, Env, IntoVal, Symbol, Val};
+
+use crate::storage::ProfileKey;
+
+/// Publish an event under `name`.
+///
+/// When an event prefix is configured, it is prepended as the leading
+/// topic so indexers watching many contracts can tell events apart.
+fn publish<D: IntoVal<Env, Val>>(env: &Env, name: &str, data: D) {
+    let topic = Symbol::new(env, name);
+    let prefix: Option<Symbol> = env.storage().instance().get(&ProfileKey::EventPrefix);
+
+    match prefix {
+        Some(prefix) => env.events().publish((prefix, topic), data),
+        None => env.events().publish((topic,), data),
+    }
+}
+
+/// Publish an event under `name` with a second topic, e.g. a setting.
+fn publish_tagged<D: IntoVal<Env, Val>>(env: &Env, name: &str, tag: &str, data: D) {
+    let topic = Symbol::new(env, name);
+    let tag = Symbol::new(env, tag);
+    let prefix: Option<Symbol> = env.storage().instance().get(&ProfileKey::EventPrefix);
+
+    match prefix {
+        Some(prefix) => env.events().publish((prefix, topic, tag), data),
+        None => env.events().publish((topic, tag), data),
+    }
+}
 
 /// Emit an event when a profile is registered.
 pub fn emit_profile_registered(env: &Env, address: &Address, username: &Bytes) {
-    let topics = (Symbol::new(env, "profile_registered"),);
-    env.events().publish(topics, (address.clone(), username.clone()));
+    publish(
+        env,
+        "profile_registered",
+        (address.clone(), username.clone()),
+    );
 }
 
-/// Emit an event when a profile is updated.
-pub fn emit_profile_updated(env: &Env, address: &Address, field: &Symbol) {
-    let topics = (Symbol::new(env, "profile_updated"),);
-    env.events().publish(topics, (address.clone(), field.clone()));
+/// Emit an event when a profile is updated, with its new generation.
+pub fn emit_profile_updated(env: &Env, address: &Address, field: &Symbol, generation: u64) {
+    publish(
+        env,
+        "profile_updated",
+        (address.clone(), field.clone(), generation),
+    );
 }
 
 /// Emit an event when a profile's display name is changed.
 pub fn emit_display_name_changed(env: &Env, address: &Address) {
-    let topics = (Symbol::new(env, "display_name_changed"),);
-    env.events().publish(topics, address.clone());
+    publish(env, "display_name_changed", address.clone());
 }
 
 /// Emit an event when a profile is deleted.
 pub fn emit_profile_deleted(env: &Env, address: &Address) {
-    let topics = (Symbol::new(env, "profile_deleted"),);
-    env.events().publish(topics, address.clone());
+    publish(env, "profile_deleted", address.clone());
 }
 
 /// Emit an event when a profile is banned by admin.
 pub fn emit_profile_banned(env: &Env, address: &Address) {
-    let topics = (Symbol::new(env, "profile_banned"),);
-    env.events().publish(topics, address.clone());
+    publish(env, "profile_banned", address.clone());
 }
 
 /// Emit an event when a username is transferred.
 pub fn emit_username_transferred(env: &Env, username: &Bytes, from: &Address, to: &Address) {
-    let topics = (Symbol::new(env, "username_transferred"),);
-    env.events().publish(topics, (username.clone(), from.clone(), to.clone()));
+    publish(
+        env,
+        "username_transferred",
+        (username.clone(), from.clone(), to.clone()),
+    );
 }
 
 /// Emit an event when a username is reserved by admin.
 pub fn emit_username_reserved(env: &Env, username: &Bytes) {
-    let topics = (Symbol::new(env, "username_reserved"),);
-    env.events().publish(topics, username.clone());
+    publish(env, "username_reserved", username.clone());
 }
 
 /// Emit an event when a username reservation is released by admin.
 pub fn emit_username_unreserved(env: &Env, username: &Bytes) {
-    let topics = (Symbol::new(env, "username_unreserved"),);
-    env.events().publish(topics, username.clone());
+    publish(env, "username_unreserved", username.clone());
+}
+
+/// Emit an event when a profile is reported.
+pub fn emit_profile_reported(env: &Env, target: &Address, reporter: &Address, reason: &Symbol) {
+    publish(
+        env,
+        "profile_reported",
+        (target.clone(), reporter.clone(), reason.clone()),
+    );
+}
+
+/// Emit an event when the open reports against a profile are resolved.
+pub fn emit_reports_resolved(env: &Env, target: &Address) {
+    publish(env, "reports_resolved", target.clone());
+}
+
+/// Emit an event when a profile is hidden pending review.
+pub fn emit_profile_hidden(env: &Env, address: &Address) {
+    publish(env, "profile_hidden", address.clone());
+}
+
+/// Emit an event when a hidden profile is restored.
+pub fn emit_profile_unhidden(env: &Env, address: &Address) {
+    publish(env, "profile_unhidden", address.clone());
+}
+
+/// Emit an event when an owner temporarily deactivates their profile.
+pub fn emit_profile_deactivated(env: &Env, address: &Address) {
+    publish(env, "profile_deactivated", address.clone());
+}
+
+/// Emit an event when an owner reactivates their profile.
+pub fn emit_profile_activated(env: &Env, address: &Address) {
+    publish(env, "profile_activated", address.clone());
+}
+
+/// Emit an event when an admin swaps the owners of two profiles.
+pub fn emit_owners_swapped(env: &Env, address_a: &Address, address_b: &Address) {
+    publish(
+        env,
+        "owners_swapped",
+        (address_a.clone(), address_b.clone()),
+    );
+}
+
+/// Emit an event when a profile field is frozen.
+pub fn emit_field_frozen(env: &Env, address: &Address, field: &Symbol) {
+    publish(env, "field_frozen", (address.clone(), field.clone()));
+}
+
+/// Emit an event attributing a registration to a source app.
+pub fn emit_registration_source(env: &Env, address: &Address, source: &Symbol) {
+    publish(
+        env,
+        "registration_source",
+        (address.clone(), source.clone()),
+    );
+}
+
+/// Emit an event when a profile's username changes.
+///
+/// `admin` is true when the change was made by the admin rather than
+/// the owner.
+pub fn emit_username_changed(env: &Env, address: &Address, old: &Bytes, new: &Bytes, admin: bool) {
+    publish(
+        env,
+        "username_changed",
+        (address.clone(), old.clone(), new.clone(), admin),
+    );
+}
+
+/// Emit an event when a profile is permanently removed by its owner.
+///
+/// `released` is true when the username was freed for others to claim.
+pub fn emit_profile_purged(env: &Env, address: &Address, username: &Bytes, released: bool) {
+    publish(
+        env,
+        "profile_purged",
+        (address.clone(), username.clone(), released),
+    );
+}
+
+/// Emit an event when a beneficiary claims a reserved username.
+pub fn emit_username_claimed(env: &Env, username: &Bytes, address: &Address) {
+    publish(env, "username_claimed", (username.clone(), address.clone()));
+}
+
+/// Emit an event when a profile adds an alias.
+pub fn emit_alias_added(env: &Env, address: &Address, alias: &Bytes) {
+    publish(env, "alias_added", (address.clone(), alias.clone()));
+}
+
+/// Emit an event when a profile records a verified external link.
+pub fn emit_link_verified(env: &Env, address: &Address, platform: &Symbol) {
+    publish(env, "link_verified", (address.clone(), platform.clone()));
+}
+
+/// Emit an event when the admin clears a field on a profile.
+pub fn emit_field_cleared_admin(env: &Env, address: &Address, field: &Symbol) {
+    publish(env, "field_cleared_admin", (address.clone(), field.clone()));
+}
+
+/// Emit an event when registrations are paused or resumed.
+pub fn emit_paused_changed(env: &Env, paused: bool) {
+    publish(env, "paused_changed", paused);
+}
+
+/// Emit an event when the admin changes a configuration setting.
+///
+/// Data is (old, new, caller, timestamp). A `None` old value means the
+/// setting had never been stored, i.e. this is a first-time set; a
+/// `None` new value means the setting was cleared back to its default.
+pub fn emit_admin_config_changed<V: IntoVal<Env, Val>>(
+    env: &Env,
+    setting: &str,
+    old: Option<V>,
+    new: Option<V>,
+    caller: &Address,
+) {
+    let old: Option<Val> = old.map(|value| value.into_val(env));
+    let new: Option<Val> = new.map(|value| value.into_val(env));
+    publish_tagged(
+        env,
+        "admin_config_changed",
+        setting,
+        (old, new, caller.clone(), env.ledger().timestamp()),
+    );
 }
@@ -7,7 +7,7 @@ use soroban_sdk::{contracttype, Address, Bytes, String};
 /// This enum allows storing different types of values in profile fields
 /// while maintaining type safety.
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FieldValue {
     /// String field (bio, avatar URL, etc.)
     StringField(String),
@@ -75,12 +75,16 @@ pub mod standard_fields {
     /// User biography or description.
     pub const BIO: &str = "bio";
 
-    /// Avatar image URL (IPFS, Gravatar, etc.).
+    /// Avatar image URL (IPFS, Gravatar, etc.), or a small PNG, JPEG, GIF
+    /// or WebP image stored as a bytes field (up to `MAX_AVATAR_BYTES`).
     pub const AVATAR: &str = "avatar";
 
     /// Personal website or homepage URL.
     pub const HOMEPAGE: &str = "homepage";
 
+    /// Decentralized website (`ipfs://`, `ipns://` or `ar://` URL).
+    pub const DWEB: &str = "dweb";
+
     /// Location (city, country, etc.).
     pub const LOCATION: &str = "location";
 
@@ -90,9 +94,33 @@ pub mod standard_fields {
     /// Twitter/X handle.
     pub const TWITTER: &str = "twitter";
 
-    /// Email address.
+    /// Email address. Linked on the profile page only if the owner opts
+    /// in with `set_show_email`.
     pub const EMAIL: &str = "email";
 
+    /// How to get in touch, shown in the profile page's Contact section
+    /// (up to `MAX_CONTACT_LENGTH` bytes).
+    pub const CONTACT: &str = "contact";
+
+    /// Preferred locale, e.g. `en` or `pt_BR`. Stored under a dedicated
+    /// key; use `set_locale`/`get_locale` rather than a generic field.
+    pub const LOCALE: &str = "locale";
+
     /// Whether user is available for hiring.
     pub const AVAILABLE_FOR_HIRING: &str = "hiring";
+
+    /// Standard names stored as generic fields. `LOCALE` is excluded
+    /// because it lives under its own key.
+    pub const ALL: &[&str] = &[
+        BIO,
+        AVATAR,
+        HOMEPAGE,
+        DWEB,
+        LOCATION,
+        GITHUB,
+        TWITTER,
+        EMAIL,
+        CONTACT,
+        AVAILABLE_FOR_HIRING,
+    ];
 }
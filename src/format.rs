@@ -0,0 +1,619 @@
+//! `no_std` formatting helpers for rendering profile data.
+//!
+//! These are used by the contract's own render functions and are public
+//! so that embedding contracts don't need to copy them.
+
+use soroban_sdk::{contracttype, Address, Bytes, Env};
+
+use crate::profile::LEGACY_TIMESTAMP_THRESHOLD;
+
+/// Longest strkey an address can render as (muxed account).
+const MAX_ADDRESS_LENGTH: usize = 69;
+
+/// Convert Address to Bytes for display.
+pub fn address_to_bytes(env: &Env, address: &Address) -> Bytes {
+    // Convert Address to String, then copy it out as Bytes
+    let addr_string = address.to_string();
+    let len = addr_string.len() as usize;
+    let mut buffer = [0u8; MAX_ADDRESS_LENGTH];
+    addr_string.copy_into_slice(&mut buffer[..len]);
+    Bytes::from_slice(env, &buffer[..len])
+}
+
+/// Default number of leading characters kept when truncating an address.
+pub const DEFAULT_TRUNCATE_PREFIX: u32 = 4;
+
+/// Default number of trailing characters kept when truncating an address.
+pub const DEFAULT_TRUNCATE_SUFFIX: u32 = 4;
+
+/// Truncate an address for display (returns Bytes).
+pub fn truncate_address_bytes(env: &Env, address: &Address) -> Bytes {
+    truncate_address_bytes_with(
+        env,
+        address,
+        DEFAULT_TRUNCATE_PREFIX,
+        DEFAULT_TRUNCATE_SUFFIX,
+    )
+}
+
+/// Truncate an address to `prefix` leading and `suffix` trailing
+/// characters around an ellipsis.
+///
+/// The full address is returned when truncating would not shorten it.
+pub fn truncate_address_bytes_with(
+    env: &Env,
+    address: &Address,
+    prefix: u32,
+    suffix: u32,
+) -> Bytes {
+    let full_bytes = address_to_bytes(env, address);
+    let len = full_bytes.len();
+
+    if len <= prefix.saturating_add(suffix).saturating_add(3) {
+        // Short enough, return as-is
+        return full_bytes;
+    }
+
+    // Truncate: GXXX...XXXX
+    let mut result = full_bytes.slice(0..prefix);
+    result.append(&Bytes::from_slice(env, b"..."));
+    result.append(&full_bytes.slice((len - suffix)..len));
+
+    result
+}
+
+/// Buffer size for formatting a u64: `u64::MAX` has 20 digits.
+const U64_BUFFER_LEN: usize = 20;
+
+/// Buffer size for formatting an i128: the 39 digits of its largest
+/// magnitude (`i128::MIN`) plus a sign.
+const I128_BUFFER_LEN: usize = 40;
+
+const _: () = assert!(U64_BUFFER_LEN >= decimal_digits(u64::MAX as u128));
+const _: () = assert!(I128_BUFFER_LEN > decimal_digits(i128::MIN.unsigned_abs()));
+
+/// Count the decimal digits needed to print `n`.
+const fn decimal_digits(mut n: u128) -> usize {
+    let mut digits = 1;
+    while n >= 10 {
+        n /= 10;
+        digits += 1;
+    }
+    digits
+}
+
+/// Convert u32 to Bytes.
+pub fn u32_to_bytes(env: &Env, n: u32) -> Bytes {
+    u64_to_bytes(env, n as u64)
+}
+
+/// Convert u64 to Bytes.
+pub fn u64_to_bytes(env: &Env, n: u64) -> Bytes {
+    u64_to_bytes_padded(env, n, 1)
+}
+
+/// Convert u64 to Bytes, zero-padded to at least `width` digits.
+///
+/// Numbers with more digits than `width` are written in full. `width`
+/// is capped at the 20 digits of `u64::MAX`.
+pub fn u64_to_bytes_padded(env: &Env, n: u64, width: u32) -> Bytes {
+    let mut buffer = [b'0'; U64_BUFFER_LEN];
+    let start = write_digits(&mut buffer, n as u128);
+    let start = start.min(U64_BUFFER_LEN.saturating_sub(width as usize));
+    Bytes::from_slice(env, &buffer[start..])
+}
+
+/// Convert i128 to Bytes.
+pub fn i128_to_bytes(env: &Env, n: i128) -> Bytes {
+    // unsigned_abs avoids overflowing on i128::MIN
+    let mut buffer = [0u8; I128_BUFFER_LEN];
+    let mut idx = write_digits(&mut buffer, n.unsigned_abs());
+
+    if n < 0 {
+        idx -= 1;
+        buffer[idx] = b'-';
+    }
+
+    Bytes::from_slice(env, &buffer[idx..])
+}
+
+/// Write the decimal digits of `n` right-aligned into `buffer` and return
+/// the index of the first digit. `0` is written as a single digit.
+///
+/// Callers size their buffers with the compile-time checks above, so the
+/// digits always fit.
+fn write_digits(buffer: &mut [u8], mut n: u128) -> usize {
+    let mut idx = buffer.len();
+    loop {
+        idx -= 1;
+        buffer[idx] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return idx;
+        }
+    }
+}
+
+/// Encode bytes as lowercase hexadecimal.
+pub fn bytes_to_hex(env: &Env, bytes: &Bytes) -> Bytes {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut result = Bytes::new(env);
+    for b in bytes.iter() {
+        result.push_back(DIGITS[(b >> 4) as usize]);
+        result.push_back(DIGITS[(b & 0x0f) as usize]);
+    }
+    result
+}
+
+/// Parse a decimal route parameter into a u32.
+///
+/// Returns None for empty input, non-digit characters, or overflow.
+pub fn parse_u32(bytes: &Bytes) -> Option<u32> {
+    bytes_to_u64(bytes).and_then(|n| u32::try_from(n).ok())
+}
+
+/// Parse a decimal route parameter into a u64.
+///
+/// Returns None for empty input, non-digit characters, or overflow.
+pub fn bytes_to_u64(bytes: &Bytes) -> Option<u64> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let mut n: u64 = 0;
+    for b in bytes.iter() {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        n = n.checked_mul(10)?.checked_add((b - b'0') as u64)?;
+    }
+
+    Some(n)
+}
+
+/// Format a Unix timestamp as a readable date string.
+/// Returns format: "YYYY-MM-DD HH:MM:SS UTC"
+pub fn format_timestamp(env: &Env, timestamp: u64) -> Bytes {
+    // Handle legacy ledger sequence numbers (small values)
+    // Unix timestamps for 2024+ are ~1700000000+
+    if timestamp < LEGACY_TIMESTAMP_THRESHOLD {
+        // This is likely a ledger sequence, not a timestamp
+        let mut result = Bytes::from_slice(env, b"Ledger ");
+        result.append(&u64_to_bytes(env, timestamp));
+        return result;
+    }
+
+    let mut result = format_date(env, timestamp);
+    let seconds_of_day = timestamp % 86_400;
+    for (separator, value) in [
+        (b' ', seconds_of_day / 3600),
+        (b':', seconds_of_day / 60 % 60),
+        (b':', seconds_of_day % 60),
+    ] {
+        result.push_back(separator);
+        result.append(&u64_to_bytes_padded(env, value, 2));
+    }
+    result.append(&Bytes::from_slice(env, b" UTC"));
+    result
+}
+
+/// Format the date part of a Unix timestamp as "YYYY-MM-DD". Years past
+/// 9999 are written with as many digits as they need.
+fn format_date(env: &Env, timestamp: u64) -> Bytes {
+    // Unix timestamps are never before 1970, so the year is positive
+    let (year, month, day) = days_to_date((timestamp / 86_400) as i64);
+    let mut result = u64_to_bytes_padded(env, year as u64, 4);
+    result.push_back(b'-');
+    result.append(&u64_to_bytes_padded(env, month as u64, 2));
+    result.push_back(b'-');
+    result.append(&u64_to_bytes_padded(env, day as u64, 2));
+    result
+}
+
+/// How `format_timestamp_style` renders a timestamp.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DateStyle {
+    /// "2024-02-29 00:00:00 UTC"
+    Full,
+    /// "2024-02-29"
+    DateOnly,
+    /// "3 days ago", relative to the current ledger time
+    Relative,
+}
+
+/// Which parts of a profile card to render.
+///
+/// The default matches the standard full card.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CardOptions {
+    /// Render the compact `@username` link instead of the full card.
+    pub compact: bool,
+    /// Show the avatar image.
+    pub show_avatar: bool,
+    /// Show the bio below the username.
+    pub show_bio: bool,
+    /// Show the "Joined" date.
+    pub show_joined: bool,
+    /// Show a link to the decentralized website in the `dweb` field.
+    pub show_dweb: bool,
+    /// Render a plain-text line without HTML, for hosts that strip it.
+    /// Only `compact` and `show_bio` apply in this mode.
+    pub text: bool,
+}
+
+impl Default for CardOptions {
+    fn default() -> Self {
+        CardOptions {
+            compact: false,
+            show_avatar: true,
+            show_bio: false,
+            show_joined: true,
+            show_dweb: false,
+            text: false,
+        }
+    }
+}
+
+/// Format a Unix timestamp in the given style.
+///
+/// Legacy ledger-sequence values render as "Ledger N" in every style.
+pub fn format_timestamp_style(env: &Env, timestamp: u64, style: DateStyle) -> Bytes {
+    if timestamp < LEGACY_TIMESTAMP_THRESHOLD {
+        return format_timestamp(env, timestamp);
+    }
+
+    match style {
+        DateStyle::Full => format_timestamp(env, timestamp),
+        DateStyle::DateOnly => format_date(env, timestamp),
+        DateStyle::Relative => {
+            let age = env.ledger().timestamp().saturating_sub(timestamp);
+            let mut result = format_duration(env, age);
+            result.append(&Bytes::from_slice(env, b" ago"));
+            result
+        }
+    }
+}
+
+/// Format a timestamp as a compact age relative to `now`.
+///
+/// Produces "just now", "5m ago", "3h ago" or "12d ago". Timestamps at or
+/// after `now` (clock skew) clamp to "just now", and legacy ledger-sequence
+/// values fall back to "Ledger N".
+pub fn format_relative(env: &Env, timestamp: u64, now: u64) -> Bytes {
+    if timestamp < LEGACY_TIMESTAMP_THRESHOLD {
+        return format_timestamp(env, timestamp);
+    }
+
+    let age = now.saturating_sub(timestamp);
+    let (value, unit) = if age < 60 {
+        return Bytes::from_slice(env, b"just now");
+    } else if age < 3_600 {
+        (age / 60, b'm')
+    } else if age < 86_400 {
+        (age / 3_600, b'h')
+    } else {
+        (age / 86_400, b'd')
+    };
+
+    let mut result = u64_to_bytes(env, value);
+    result.push_back(unit);
+    result.append(&Bytes::from_slice(env, b" ago"));
+    result
+}
+
+/// Format a duration in seconds as a coarse human-readable string.
+///
+/// Produces "59 seconds", "4 hours", "3 days", "5 months", "2 years", etc.
+/// Months are counted as 30 days and years as 365 days.
+pub fn format_duration(env: &Env, seconds: u64) -> Bytes {
+    let (value, unit): (u64, &[u8]) = if seconds < 60 {
+        (seconds, b"second")
+    } else if seconds < 3_600 {
+        (seconds / 60, b"minute")
+    } else if seconds < 86_400 {
+        (seconds / 3_600, b"hour")
+    } else if seconds < 30 * 86_400 {
+        (seconds / 86_400, b"day")
+    } else if seconds < 365 * 86_400 {
+        (seconds / (30 * 86_400), b"month")
+    } else {
+        (seconds / (365 * 86_400), b"year")
+    };
+
+    let mut result = u64_to_bytes(env, value);
+    result.push_back(b' ');
+    result.append(&Bytes::from_slice(env, unit));
+    if value != 1 {
+        result.push_back(b's');
+    }
+
+    result
+}
+
+/// Convert days since Unix epoch to (year, month, day).
+pub fn days_to_date(days: i64) -> (i64, u8, u8) {
+    // Algorithm based on Howard Hinnant's date algorithms
+    // http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u32; // day of era
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // year of era
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // day of year
+    let mp = (5 * doy + 2) / 153; // month index
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if m <= 2 { y + 1 } else { y };
+
+    (year, m as u8, d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger as _};
+
+    fn b(env: &Env, s: &[u8]) -> Bytes {
+        Bytes::from_slice(env, s)
+    }
+
+    #[test]
+    fn test_unsigned_to_bytes() {
+        let env = Env::default();
+
+        assert_eq!(u32_to_bytes(&env, 0), b(&env, b"0"));
+        assert_eq!(u32_to_bytes(&env, u32::MAX), b(&env, b"4294967295"));
+        assert_eq!(u64_to_bytes(&env, 0), b(&env, b"0"));
+        assert_eq!(u64_to_bytes(&env, 7), b(&env, b"7"));
+        assert_eq!(u64_to_bytes(&env, 10), b(&env, b"10"));
+        assert_eq!(
+            u64_to_bytes(&env, u64::MAX),
+            b(&env, b"18446744073709551615")
+        );
+    }
+
+    #[test]
+    fn test_u64_to_bytes_padded() {
+        let env = Env::default();
+
+        assert_eq!(u64_to_bytes_padded(&env, 0, 2), b(&env, b"00"));
+        assert_eq!(u64_to_bytes_padded(&env, 7, 2), b(&env, b"07"));
+        assert_eq!(u64_to_bytes_padded(&env, 2024, 4), b(&env, b"2024"));
+        // Wider numbers are not cut to the width
+        assert_eq!(u64_to_bytes_padded(&env, 12_345, 4), b(&env, b"12345"));
+        assert_eq!(u64_to_bytes_padded(&env, 0, 0), b(&env, b"0"));
+        assert_eq!(
+            u64_to_bytes_padded(&env, 5, 99),
+            b(&env, b"00000000000000000005")
+        );
+        assert_eq!(
+            u64_to_bytes_padded(&env, u64::MAX, 20),
+            b(&env, b"18446744073709551615")
+        );
+    }
+
+    #[test]
+    fn test_i128_to_bytes() {
+        let env = Env::default();
+
+        assert_eq!(i128_to_bytes(&env, 0), b(&env, b"0"));
+        assert_eq!(i128_to_bytes(&env, -1), b(&env, b"-1"));
+        assert_eq!(i128_to_bytes(&env, -42), b(&env, b"-42"));
+        assert_eq!(
+            i128_to_bytes(&env, i128::MAX),
+            b(&env, b"170141183460469231731687303715884105727")
+        );
+        assert_eq!(
+            i128_to_bytes(&env, i128::MIN),
+            b(&env, b"-170141183460469231731687303715884105728")
+        );
+    }
+
+    #[test]
+    fn test_bytes_to_hex() {
+        let env = Env::default();
+
+        assert_eq!(bytes_to_hex(&env, &b(&env, b"")), b(&env, b""));
+        assert_eq!(
+            bytes_to_hex(&env, &b(&env, &[0x00, 0x0f, 0xa5, 0xff])),
+            b(&env, b"000fa5ff")
+        );
+    }
+
+    #[test]
+    fn test_buffer_sizes() {
+        assert_eq!(decimal_digits(0), 1);
+        assert_eq!(decimal_digits(u64::MAX as u128), 20);
+        assert_eq!(decimal_digits(i128::MIN.unsigned_abs()), 39);
+        assert_eq!(decimal_digits(u128::MAX), 39);
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        let env = Env::default();
+
+        assert_eq!(bytes_to_u64(&b(&env, b"0")), Some(0));
+        assert_eq!(
+            bytes_to_u64(&b(&env, b"18446744073709551615")),
+            Some(u64::MAX)
+        );
+        assert_eq!(bytes_to_u64(&b(&env, b"18446744073709551616")), None);
+        assert_eq!(bytes_to_u64(&b(&env, b"")), None);
+        assert_eq!(bytes_to_u64(&b(&env, b"12a")), None);
+
+        assert_eq!(parse_u32(&b(&env, b"4294967295")), Some(u32::MAX));
+        assert_eq!(parse_u32(&b(&env, b"4294967296")), None);
+    }
+
+    #[test]
+    fn test_days_to_date() {
+        assert_eq!(days_to_date(0), (1970, 1, 1));
+        // Leap days
+        assert_eq!(days_to_date(11_016), (2000, 2, 29));
+        assert_eq!(days_to_date(19_782), (2024, 2, 29));
+        assert_eq!(days_to_date(19_783), (2024, 3, 1));
+        // 2100 is not a leap year
+        assert_eq!(days_to_date(47_540), (2100, 2, 28));
+        assert_eq!(days_to_date(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let env = Env::default();
+
+        assert_eq!(format_timestamp(&env, 12_345), b(&env, b"Ledger 12345"));
+        assert_eq!(
+            format_timestamp(&env, 1_709_164_800),
+            b(&env, b"2024-02-29 00:00:00 UTC")
+        );
+        assert_eq!(
+            format_timestamp(&env, 1_709_164_800 + 13 * 3600 + 5 * 60 + 9),
+            b(&env, b"2024-02-29 13:05:09 UTC")
+        );
+
+        // Years past 9999 get a fifth digit instead of wrapping
+        assert_eq!(
+            format_timestamp(&env, 253_402_300_800),
+            b(&env, b"10000-01-01 00:00:00 UTC")
+        );
+        assert_eq!(
+            format_timestamp(&env, u64::MAX),
+            b(&env, b"584554051223-11-09 07:00:15 UTC")
+        );
+        assert_eq!(
+            format_timestamp_style(&env, u64::MAX, DateStyle::DateOnly),
+            b(&env, b"584554051223-11-09")
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_style() {
+        let env = Env::default();
+        env.ledger()
+            .with_mut(|li| li.timestamp = 1_709_164_800 + 3 * 86_400);
+
+        let ts = 1_709_164_800;
+        assert_eq!(
+            format_timestamp_style(&env, ts, DateStyle::Full),
+            b(&env, b"2024-02-29 00:00:00 UTC")
+        );
+        assert_eq!(
+            format_timestamp_style(&env, ts, DateStyle::DateOnly),
+            b(&env, b"2024-02-29")
+        );
+        assert_eq!(
+            format_timestamp_style(&env, ts, DateStyle::Relative),
+            b(&env, b"3 days ago")
+        );
+
+        // Timestamps ahead of the ledger clock clamp to zero
+        assert_eq!(
+            format_timestamp_style(&env, ts + 4 * 86_400, DateStyle::Relative),
+            b(&env, b"0 seconds ago")
+        );
+
+        // Legacy ledger sequences fall back in every style
+        for style in [DateStyle::Full, DateStyle::DateOnly, DateStyle::Relative] {
+            assert_eq!(
+                format_timestamp_style(&env, 12_345, style),
+                b(&env, b"Ledger 12345")
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let env = Env::default();
+        let now = 1_709_164_800;
+
+        assert_eq!(format_relative(&env, now - 59, now), b(&env, b"just now"));
+        assert_eq!(format_relative(&env, now - 5 * 60, now), b(&env, b"5m ago"));
+        assert_eq!(
+            format_relative(&env, now - 3 * 3_600 - 10, now),
+            b(&env, b"3h ago")
+        );
+        assert_eq!(
+            format_relative(&env, now - 400 * 86_400, now),
+            b(&env, b"400d ago")
+        );
+
+        // Clock skew clamps to "just now"
+        assert_eq!(format_relative(&env, now + 600, now), b(&env, b"just now"));
+        assert_eq!(format_relative(&env, 12_345, now), b(&env, b"Ledger 12345"));
+    }
+
+    #[test]
+    fn test_truncate_address() {
+        let env = Env::default();
+        let address = Address::generate(&env);
+        let full = address_to_bytes(&env, &address);
+        let short = truncate_address_bytes(&env, &address);
+
+        assert_eq!(full.len(), 56);
+        assert_eq!(short.len(), 11);
+        assert_eq!(short.slice(0..4), full.slice(0..4));
+        assert_eq!(short.slice(7..11), full.slice(52..56));
+
+        let wide = truncate_address_bytes_with(&env, &address, 6, 6);
+        assert_eq!(wide.len(), 15);
+        assert_eq!(wide.slice(0..6), full.slice(0..6));
+        assert_eq!(wide.slice(6..9), b(&env, b"..."));
+        assert_eq!(wide.slice(9..15), full.slice(50..56));
+
+        // Lengths that cover the whole address leave it untouched
+        assert_eq!(truncate_address_bytes_with(&env, &address, 30, 30), full);
+        assert_eq!(
+            truncate_address_bytes_with(&env, &address, u32::MAX, 1),
+            full
+        );
+    }
+
+    #[test]
+    fn test_format_duration_boundaries() {
+        let env = Env::default();
+
+        assert_eq!(
+            format_duration(&env, 0),
+            Bytes::from_slice(&env, b"0 seconds")
+        );
+        assert_eq!(
+            format_duration(&env, 1),
+            Bytes::from_slice(&env, b"1 second")
+        );
+        assert_eq!(
+            format_duration(&env, 59),
+            Bytes::from_slice(&env, b"59 seconds")
+        );
+        assert_eq!(
+            format_duration(&env, 60),
+            Bytes::from_slice(&env, b"1 minute")
+        );
+        assert_eq!(
+            format_duration(&env, 86_399),
+            Bytes::from_slice(&env, b"23 hours")
+        );
+        assert_eq!(
+            format_duration(&env, 3 * 86_400),
+            Bytes::from_slice(&env, b"3 days")
+        );
+        assert_eq!(
+            format_duration(&env, 90 * 86_400),
+            Bytes::from_slice(&env, b"3 months")
+        );
+        assert_eq!(
+            format_duration(&env, 364 * 86_400),
+            Bytes::from_slice(&env, b"12 months")
+        );
+        assert_eq!(
+            format_duration(&env, 366 * 86_400),
+            Bytes::from_slice(&env, b"1 year")
+        );
+        assert_eq!(
+            format_duration(&env, 800 * 86_400),
+            Bytes::from_slice(&env, b"2 years")
+        );
+    }
+}
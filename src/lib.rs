@@ -29,6 +29,8 @@
 
 mod events;
 mod fields;
+pub mod format;
+mod moderation;
 mod profile;
 mod storage;
 mod validation;
@@ -37,17 +39,36 @@ mod validation;
 mod render;
 
 pub use fields::{standard_fields, FieldValue};
-pub use profile::Profile;
+pub use format::{CardOptions, DateStyle};
+pub use moderation::ModLogEntry;
+pub use profile::{
+    FullProfile, InitConfig, Profile, ProfileExport, ProfileStats, ProfileView, ViewerContext,
+    PROFILE_SCHEMA_VERSION, UNVERSIONED_PROFILE_SCHEMA,
+};
 pub use storage::ProfileKey;
-pub use validation::{validate_username, MAX_USERNAME_LENGTH, MIN_USERNAME_LENGTH};
+pub use validation::{
+    canonicalize_username, confusable_username, dweb_scheme, normalize_display_name,
+    validate_dweb_url, validate_link_url, validate_username, validate_username_with_bounds,
+    DEFAULT_MAX_FIELDS, DEFAULT_MAX_FIELD_LENGTH, MAX_ADMIN_NOTE_LENGTH, MAX_AVATAR_BYTES,
+    MAX_CONTACT_LENGTH, MAX_DWEB_URL_LENGTH, MAX_LINK_LABEL_LENGTH, MAX_LINK_URL_LENGTH,
+    MAX_NORMALIZED_NAME_LENGTH, MAX_USERNAME_LENGTH, MIN_USERNAME_LENGTH, USERNAME_LENGTH_CEILING,
+};
 
 use soroban_sdk::{
-    contract, contracterror, contractimpl, panic_with_error, Address, Bytes, BytesN, Env, Map,
-    String, Symbol,
+    contract, contracterror, contractimpl, panic_with_error, vec, Address, Bytes, BytesN, Env,
+    IntoVal, Map, String, Symbol, SymbolStr, TryFromVal, Val,
 };
 
+use soroban_sdk::xdr::ToXdr;
+
 use crate::events::*;
-use crate::storage::{PROFILE_TTL_EXTEND, PROFILE_TTL_THRESHOLD};
+use crate::profile::LEGACY_TIMESTAMP_THRESHOLD;
+use crate::storage::{
+    DIRECTORY_PAGE_SIZE, MAX_ALIASES, MAX_BATCH_SIZE, MAX_DISPLAY_NAME_INDEX,
+    MAX_ELIGIBILITY_BATCH, MAX_EXPORT_PAGE, MAX_FIELD_HISTORY, MAX_FORMER_USERNAMES, MAX_LINKS,
+    MAX_NAMESPACES, MAX_OPEN_REPORTS, MAX_REGISTER_FIELDS, MAX_STATS_DAYS, MAX_WINDOW_SCAN,
+    PROFILE_TTL_EXTEND, PROFILE_TTL_THRESHOLD,
+};
 
 /// Error codes for the user profile contract.
 #[contracterror]
@@ -74,8 +95,73 @@ pub enum ProfileError {
     ProfileDeleted = 9,
     /// Invalid field name.
     InvalidField = 10,
+    /// Value exceeds the maximum allowed length.
+    ValueTooLong = 11,
+    /// Reporter already has an open report against this profile.
+    AlreadyReported = 12,
+    /// Profile has reached the maximum number of open reports.
+    ReportLimitReached = 13,
+    /// Field is frozen and can no longer be changed.
+    FieldFrozen = 14,
+    /// Profile is younger than the required minimum age.
+    ProfileTooNew = 15,
+    /// Batch request exceeds the maximum size.
+    BatchTooLarge = 16,
+    /// Field value failed validation.
+    InvalidFieldValue = 17,
+    /// Profile is too new to be transferred.
+    TransferTooEarly = 18,
+    /// Configuration value is out of range.
+    InvalidConfig = 19,
+    /// Profile already has the maximum number of fields.
+    TooManyFields = 20,
+    /// Profile has been banned by the admin.
+    ProfileBanned = 21,
+    /// Registrations are temporarily paused.
+    RegistrationPaused = 22,
+    /// Profile already has the maximum number of aliases.
+    TooManyAliases = 23,
+    /// Too many registrations in the current throttle window.
+    ThrottleExceeded = 24,
+    /// Caller does not meet the condition attached to a reserved username.
+    ConditionNotMet = 25,
+    /// Profile already has the maximum number of links.
+    TooManyLinks = 26,
+    /// Display name is not acceptable, e.g. identical to the username.
+    InvalidDisplayName = 27,
+    /// The value was changed too recently to change again.
+    ChangeTooSoon = 28,
+    /// Username looks like an already registered username.
+    UsernameConfusable = 29,
+    /// Username is in a namespace delegated to a registrar contract.
+    NamespaceDelegated = 30,
+    /// Registration is invite-only and the caller has no invite.
+    NotInvited = 31,
+    /// Profile is temporarily deactivated by its owner.
+    ProfileDeactivated = 32,
 }
 
+/// Claim condition that only requires the caller to be the beneficiary.
+pub const CLAIM_CONDITION_NONE: &str = "none";
+
+/// `reservation_status`: the name is free.
+pub const RESERVATION_FREE: u32 = 0;
+
+/// `reservation_status`: the name is soft-reserved but can be registered.
+pub const RESERVATION_SOFT: u32 = 1;
+
+/// `reservation_status`: the name is reserved and cannot be registered.
+pub const RESERVATION_HARD: u32 = 2;
+
+/// `reservation_status`: the name belongs to a profile.
+pub const RESERVATION_TAKEN: u32 = 3;
+
+/// Embed policy under which any contract may embed full profile cards.
+pub const EMBED_OPEN: &str = "open";
+
+/// Embed policy under which only approved contracts get full profile cards.
+pub const EMBED_ALLOWLIST: &str = "allowlist";
+
 #[contract]
 pub struct UserProfileContract;
 
@@ -93,7 +179,50 @@ impl UserProfileContract {
 
         admin.require_auth();
         env.storage().instance().set(&ProfileKey::Admin, &admin);
-        env.storage().instance().set(&ProfileKey::ProfileCount, &0u64);
+        env.storage()
+            .instance()
+            .set(&ProfileKey::ProfileCount, &0u64);
+    }
+
+    /// Initialize the contract and apply its initial configuration in the
+    /// same transaction, so it is never live unconfigured.
+    ///
+    /// Fails as `init` does, and with `InvalidUsername` if a reserved
+    /// name is invalid, in which case nothing is applied. Very long
+    /// reservation lists can exceed transaction limits; reserve the rest
+    /// with `reserve_username`.
+    pub fn init_with_config(env: Env, admin: Address, config: InitConfig) {
+        Self::init(env.clone(), admin.clone());
+
+        if let Some(fee) = config.registration_fee {
+            Self::set_config(
+                &env,
+                &ProfileKey::RegistrationFee,
+                "registration_fee",
+                Some(fee),
+                &admin,
+            );
+        }
+
+        if let Some(recovery) = config.recovery_address {
+            env.storage()
+                .instance()
+                .set(&ProfileKey::RecoveryAddress, &recovery);
+        }
+
+        if !config.registration_open {
+            env.storage().instance().set(&ProfileKey::Paused, &true);
+            emit_paused_changed(&env, true);
+        }
+
+        for username in config.reserved_usernames.iter() {
+            let username = canonicalize_username(&username);
+            if !Self::username_is_valid(&env, &username) {
+                panic_with_error!(&env, ProfileError::InvalidUsername);
+            }
+            Self::set_reserved(&env, &username);
+            emit_username_reserved(&env, &username);
+        }
     }
 
     /// Get the admin address.
@@ -125,91 +254,190 @@ impl UserProfileContract {
         caller.require_auth();
 
         // Convert username String to Bytes for storage and validation
-        let username = soroban_render_sdk::bytes::string_to_bytes(&env, &username);
+        let username =
+            canonicalize_username(&soroban_render_sdk::bytes::string_to_bytes(&env, &username));
+        Self::require_distinct_name(&env, &username, &display_name);
 
-        // Check contract is initialized
-        if !env.storage().instance().has(&ProfileKey::Admin) {
-            panic_with_error!(&env, ProfileError::NotInitialized);
+        Self::register_internal(&env, username, display_name, &caller);
+
+        true
+    }
+
+    /// Claim a reserved username assigned to the caller.
+    ///
+    /// The admin assigns the name with `reserve_username_with_condition`.
+    /// The caller must be the beneficiary and meet the attached condition.
+    /// The claim then registers a new profile under the name, with the
+    /// username as its initial display name. Conditions the contract does
+    /// not recognise fail closed with `ConditionNotMet`; currently only
+    /// `CLAIM_CONDITION_NONE` is recognised.
+    pub fn claim_reserved(env: Env, username: Bytes, caller: Address) -> bool {
+        let username = canonicalize_username(&username);
+        caller.require_auth();
+
+        let (beneficiary, condition): (Address, Symbol) = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ReservedFor(username.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::NotAuthorized));
+
+        if beneficiary != caller {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
         }
 
-        // Validate username format
-        if !validation::validate_username(&username) {
+        if condition != Symbol::new(&env, CLAIM_CONDITION_NONE) {
+            panic_with_error!(&env, ProfileError::ConditionNotMet);
+        }
+
+        if username.len() > USERNAME_LENGTH_CEILING {
             panic_with_error!(&env, ProfileError::InvalidUsername);
         }
+        let mut buffer = [0u8; USERNAME_LENGTH_CEILING as usize];
+        let len = username.len() as usize;
+        username.copy_into_slice(&mut buffer[..len]);
+        let display_name = String::from_bytes(&env, &buffer[..len]);
 
-        // Check username is not taken
-        if env
-            .storage()
-            .persistent()
-            .has(&ProfileKey::Username(username.clone()))
-        {
-            panic_with_error!(&env, ProfileError::UsernameTaken);
+        Self::lift_reservation(&env, &username);
+        Self::register_internal(&env, username.clone(), display_name, &caller);
+
+        emit_username_claimed(&env, &username, &caller);
+
+        true
+    }
+
+    /// Register a new profile and set its fields in one call.
+    ///
+    /// Each field goes through the same checks as the individual setters.
+    /// If any field is rejected the whole call fails and nothing is
+    /// registered. At most `MAX_REGISTER_FIELDS` fields may be provided.
+    pub fn register_full(
+        env: Env,
+        username: String,
+        display_name: String,
+        fields: Map<Symbol, FieldValue>,
+        caller: Address,
+    ) -> bool {
+        if fields.len() > MAX_REGISTER_FIELDS {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
         }
 
-        // Check username is not reserved
-        if env
-            .storage()
-            .persistent()
-            .has(&ProfileKey::ReservedUsername(username.clone()))
-        {
-            panic_with_error!(&env, ProfileError::UsernameReserved);
+        Self::register(env.clone(), username, display_name, caller.clone());
+
+        let mut changed = soroban_sdk::Vec::new(&env);
+        for (field, value) in fields.iter() {
+            if Self::apply_field(&env, &caller, field.clone(), value) {
+                changed.push_back(field);
+            }
         }
 
-        // Check caller doesn't already have a profile
-        if env
-            .storage()
-            .persistent()
-            .has(&ProfileKey::Profile(caller.clone()))
-        {
-            panic_with_error!(&env, ProfileError::ProfileExists);
+        // The whole batch is one change
+        if !changed.is_empty() {
+            let generation = Self::bump_generation(&env, &caller);
+            for field in changed.iter() {
+                emit_profile_updated(&env, &caller, &field, generation);
+            }
         }
 
-        // Create profile
-        let timestamp = env.ledger().timestamp();
-        let profile = Profile::new(username.clone(), display_name, caller.clone(), timestamp);
+        true
+    }
 
-        // Store username -> address mapping
-        env.storage()
+    /// Register a new profile and attribute it to the app it came through.
+    ///
+    /// Behaves like `register`, and records `source` for the new profile.
+    /// Sources not on the admin-approved list are recorded as `other`,
+    /// but still counted under their own id by `app_registration_count`.
+    pub fn register_from(
+        env: Env,
+        username: String,
+        display_name: String,
+        caller: Address,
+        source: Symbol,
+    ) -> bool {
+        Self::register(env.clone(), username, display_name, caller.clone());
+        Self::increment_count(&env, &ProfileKey::AppRegistrationCount(source.clone()));
+
+        let source = if env
+            .storage()
             .persistent()
-            .set(&ProfileKey::Username(username.clone()), &caller);
+            .has(&ProfileKey::ApprovedSource(source.clone()))
+        {
+            source
+        } else {
+            Symbol::new(&env, "other")
+        };
 
-        // Store profile
         env.storage()
             .persistent()
-            .set(&ProfileKey::Profile(caller.clone()), &profile);
-
-        // Extend TTL
+            .set(&ProfileKey::Source(caller.clone()), &source);
         env.storage().persistent().extend_ttl(
-            &ProfileKey::Username(username.clone()),
-            PROFILE_TTL_THRESHOLD,
-            PROFILE_TTL_EXTEND,
-        );
-        env.storage().persistent().extend_ttl(
-            &ProfileKey::Profile(caller.clone()),
+            &ProfileKey::Source(caller.clone()),
             PROFILE_TTL_THRESHOLD,
             PROFILE_TTL_EXTEND,
         );
 
-        // Increment profile count
-        let count: u64 = env
+        Self::increment_count(&env, &ProfileKey::SourceCount(source.clone()));
+
+        emit_registration_source(&env, &caller, &source);
+
+        true
+    }
+
+    /// Register a profile for `owner` under a delegated namespace.
+    ///
+    /// Only the registrar the username's prefix is delegated to may call
+    /// this. Both the registrar and `owner` authorize, so nobody is given
+    /// a profile they did not ask for. Format, uniqueness and other
+    /// registration checks still apply. Reservations assigned to the
+    /// registrar with `reserve_username_with_condition` are taken over;
+    /// other reservations still block the name.
+    pub fn register_via_registrar(
+        env: Env,
+        username: String,
+        display_name: String,
+        owner: Address,
+        registrar: Address,
+    ) -> bool {
+        registrar.require_auth();
+
+        let username =
+            canonicalize_username(&soroban_render_sdk::bytes::string_to_bytes(&env, &username));
+        if Self::namespace_registrar(env.clone(), username.clone()) != Some(registrar.clone()) {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        if owner != registrar {
+            owner.require_auth();
+        }
+
+        let reserved_for: Option<(Address, Symbol)> = env
             .storage()
-            .instance()
-            .get(&ProfileKey::ProfileCount)
-            .unwrap_or(0);
-        env.storage()
-            .instance()
-            .set(&ProfileKey::ProfileCount, &(count + 1));
+            .persistent()
+            .get(&ProfileKey::ReservedFor(username.clone()));
+        if matches!(reserved_for, Some((beneficiary, _)) if beneficiary == registrar) {
+            Self::lift_reservation(&env, &username);
+        }
 
-        // Emit event
-        emit_profile_registered(&env, &caller, &username);
+        Self::require_distinct_name(&env, &username, &display_name);
+        Self::create_profile(&env, username, display_name, &owner);
 
         true
     }
 
+    /// Check whether a username matches the current validation rules.
+    ///
+    /// Uses the admin-configured length bounds, if any, and ignores
+    /// whether the name is taken or reserved. A name that was valid at
+    /// registration may not be valid under stricter bounds.
+    pub fn is_valid_username(env: Env, username: Bytes) -> bool {
+        let username = canonicalize_username(&username);
+        Self::username_is_valid(&env, &username)
+    }
+
     /// Check if a username is available for registration.
     pub fn is_username_available(env: Env, username: Bytes) -> bool {
+        let username = canonicalize_username(&username);
         // Check format
-        if !validation::validate_username(&username) {
+        if !Self::username_is_valid(&env, &username) {
             return false;
         }
 
@@ -223,11 +451,7 @@ impl UserProfileContract {
         }
 
         // Check not reserved
-        if env
-            .storage()
-            .persistent()
-            .has(&ProfileKey::ReservedUsername(username.clone()))
-        {
+        if Self::is_reserved(&env, &username) {
             return false;
         }
 
@@ -237,367 +461,4414 @@ impl UserProfileContract {
     // ========== Profile Queries ==========
 
     /// Get a profile by username.
+    ///
+    /// Soft lookup: returns None if the name is unknown or its profile is
+    /// deleted or deactivated. Use `expect_by_username` when a missing
+    /// profile is an error. Former usernames resolve to the renamed profile.
     pub fn get_by_username(env: Env, username: Bytes) -> Option<Profile> {
-        let address: Option<Address> = env
-            .storage()
-            .persistent()
-            .get(&ProfileKey::Username(username));
+        let username = canonicalize_username(&username);
+        let address = Self::resolve_username(&env, &username);
 
         match address {
-            Some(addr) => {
-                let profile: Option<Profile> =
-                    env.storage().persistent().get(&ProfileKey::Profile(addr));
-                profile.filter(|p| p.is_active())
-            }
+            Some(addr) => Self::get_by_address(env, addr),
             None => None,
         }
     }
 
     /// Get a profile by address.
+    ///
+    /// Soft lookup: returns None if there is no active profile or its
+    /// owner deactivated it. Use `expect_by_address` when a missing
+    /// profile is an error.
     pub fn get_by_address(env: Env, address: Address) -> Option<Profile> {
         let profile: Option<Profile> = env
             .storage()
             .persistent()
-            .get(&ProfileKey::Profile(address));
-        profile.filter(|p| p.is_active())
-    }
-
-    /// Get a profile field value.
-    pub fn get_field(env: Env, address: Address, field: Symbol) -> Option<FieldValue> {
-        env.storage()
-            .persistent()
-            .get(&ProfileKey::Field(address, field))
+            .get(&ProfileKey::Profile(address.clone()));
+        profile
+            .filter(|p| p.is_active())
+            .filter(|_| !Self::is_deactivated(env, address))
     }
 
-    /// Get all fields for an address.
+    /// Get an active profile by address, or fail with a typed error.
     ///
-    /// Note: This returns only fields that have been explicitly set.
-    /// For efficiency, field names must be provided.
-    pub fn get_fields(env: Env, address: Address, field_names: soroban_sdk::Vec<Symbol>) -> Map<Symbol, FieldValue> {
-        let mut result = Map::new(&env);
-
-        for field in field_names.iter() {
-            if let Some(value) = env
-                .storage()
-                .persistent()
-                .get::<_, FieldValue>(&ProfileKey::Field(address.clone(), field.clone()))
-            {
-                result.set(field, value);
-            }
-        }
-
-        result
-    }
-
-    /// Get total profile count.
-    pub fn profile_count(env: Env) -> u64 {
-        env.storage()
-            .instance()
-            .get(&ProfileKey::ProfileCount)
-            .unwrap_or(0)
-    }
-
-    // ========== Profile Updates ==========
-
-    /// Update the display name.
-    pub fn set_display_name(env: Env, display_name: String, caller: Address) {
-        caller.require_auth();
-
-        let mut profile: Profile = env
+    /// Intended for cross-contract callers that require a profile. Fails
+    /// with `ProfileNotFound` if the address never registered,
+    /// `ProfileBanned` if it was banned, `ProfileDeleted` if the owner
+    /// deleted it and `ProfileDeactivated` if the owner deactivated it.
+    pub fn expect_by_address(env: Env, address: Address) -> Profile {
+        let profile: Profile = env
             .storage()
             .persistent()
-            .get(&ProfileKey::Profile(caller.clone()))
+            .get(&ProfileKey::Profile(address.clone()))
             .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
 
         if profile.deleted {
+            if Self::is_banned(env.clone(), address) {
+                panic_with_error!(&env, ProfileError::ProfileBanned);
+            }
             panic_with_error!(&env, ProfileError::ProfileDeleted);
         }
 
-        if profile.owner != caller {
-            panic_with_error!(&env, ProfileError::NotAuthorized);
+        if Self::is_deactivated(env.clone(), address) {
+            panic_with_error!(&env, ProfileError::ProfileDeactivated);
         }
 
-        profile.display_name = display_name;
-        profile.updated_at = env.ledger().timestamp();
-
-        env.storage()
-            .persistent()
-            .set(&ProfileKey::Profile(caller.clone()), &profile);
+        profile
+    }
 
-        env.storage().persistent().extend_ttl(
-            &ProfileKey::Profile(caller.clone()),
-            PROFILE_TTL_THRESHOLD,
-            PROFILE_TTL_EXTEND,
-        );
+    /// Get an active profile by username, or fail with a typed error.
+    ///
+    /// Errors as `expect_by_address`; `ProfileNotFound` also covers names
+    /// that are not registered.
+    pub fn expect_by_username(env: Env, username: Bytes) -> Profile {
+        let username = canonicalize_username(&username);
+        let address = Self::resolve_username(&env, &username)
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
 
-        emit_display_name_changed(&env, &caller);
+        Self::expect_by_address(env, address)
     }
 
-    /// Set a string field.
-    pub fn set_string_field(env: Env, field: Symbol, value: String, caller: Address) {
-        Self::set_field_internal(&env, &caller, field, FieldValue::StringField(value));
+    /// Get the current username for a name, following former usernames
+    /// and aliases.
+    ///
+    /// Lets apps update stored references after a rename. Returns None
+    /// if the name does not resolve to an active profile.
+    pub fn canonical_username(env: Env, username: Bytes) -> Option<Bytes> {
+        Self::get_by_username(env, username).map(|p| p.username)
     }
 
-    /// Set an integer field.
-    pub fn set_int_field(env: Env, field: Symbol, value: i128, caller: Address) {
-        Self::set_field_internal(&env, &caller, field, FieldValue::IntField(value));
+    /// Get the former usernames of a profile that still redirect to it,
+    /// oldest first.
+    pub fn former_usernames(env: Env, address: Address) -> soroban_sdk::Vec<Bytes> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::FormerUsernames(address))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env))
     }
 
-    /// Set a boolean field.
-    pub fn set_bool_field(env: Env, field: Symbol, value: bool, caller: Address) {
-        Self::set_field_internal(&env, &caller, field, FieldValue::BoolField(value));
-    }
+    /// Get the viewer's profile summary and admin status in one call.
+    ///
+    /// Anonymous viewers and viewers without an active profile get empty
+    /// profile details.
+    pub fn viewer_context(env: Env, viewer: Option<Address>) -> ViewerContext {
+        let viewer = match viewer {
+            Some(viewer) => viewer,
+            None => {
+                return ViewerContext {
+                    has_profile: false,
+                    username: None,
+                    display_name: None,
+                    avatar: None,
+                    is_admin: false,
+                }
+            }
+        };
 
-    /// Remove a field.
-    pub fn remove_field(env: Env, field: Symbol, caller: Address) {
-        caller.require_auth();
+        let admin: Option<Address> = env.storage().instance().get(&ProfileKey::Admin);
+        let is_admin = admin.as_ref() == Some(&viewer);
 
-        // Verify profile exists and is active
-        let profile: Profile = env
+        // Viewers still see their own deactivated profile
+        let profile: Option<Profile> = env
             .storage()
             .persistent()
-            .get(&ProfileKey::Profile(caller.clone()))
-            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
-
-        if profile.deleted {
-            panic_with_error!(&env, ProfileError::ProfileDeleted);
+            .get(&ProfileKey::Profile(viewer.clone()));
+        match profile.filter(|p| p.is_active()) {
+            Some(profile) => {
+                let avatar = Symbol::new(&env, standard_fields::AVATAR);
+                ViewerContext {
+                    has_profile: true,
+                    username: Some(profile.username),
+                    display_name: Some(profile.display_name),
+                    avatar: Self::get_string_field(env, viewer, avatar),
+                    is_admin,
+                }
+            }
+            None => ViewerContext {
+                has_profile: false,
+                username: None,
+                display_name: None,
+                avatar: None,
+                is_admin,
+            },
         }
+    }
 
-        if profile.owner != caller {
-            panic_with_error!(&env, ProfileError::NotAuthorized);
+    /// Export the registration slots `start..start + limit` for snapshotting.
+    ///
+    /// Intended to be called repeatedly off-chain with increasing `start`
+    /// until it reaches `profile_count`. Deleted and banned profiles are
+    /// included with their status; slots emptied by a hard delete are
+    /// skipped. `limit` may be at most `MAX_EXPORT_PAGE`; profiles with
+    /// many fields or verified links may need a smaller page to fit the
+    /// read footprint. `ProfileExport` lists the data a record leaves out.
+    pub fn export_page(env: Env, start: u64, limit: u32) -> soroban_sdk::Vec<ProfileExport> {
+        if limit > MAX_EXPORT_PAGE {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
         }
 
-        env.storage()
-            .persistent()
-            .remove(&ProfileKey::Field(caller.clone(), field));
-    }
+        let mut result = soroban_sdk::Vec::new(&env);
+        for index in start..start.saturating_add(limit as u64) {
+            let username: Option<Bytes> = env
+                .storage()
+                .persistent()
+                .get(&ProfileKey::ProfileIndex(index));
+            let owner: Option<Address> = username.and_then(|username| {
+                env.storage()
+                    .persistent()
+                    .get(&ProfileKey::Username(username))
+            });
+            let profile: Option<Profile> =
+                owner.and_then(|owner| env.storage().persistent().get(&ProfileKey::Profile(owner)));
+            let profile = match profile {
+                Some(profile) => profile,
+                None => continue,
+            };
 
-    // ========== Profile Management ==========
+            let address = profile.owner.clone();
+            result.push_back(ProfileExport {
+                index,
+                fields: Self::get_fields(
+                    env.clone(),
+                    address.clone(),
+                    Self::field_names(&env, &address),
+                ),
+                banned: Self::is_banned(env.clone(), address.clone()),
+                hidden: Self::is_hidden(env.clone(), address.clone()),
+                deactivated: Self::is_deactivated(env.clone(), address.clone()),
+                aliases: Self::aliases(env.clone(), address.clone()),
+                former_usernames: Self::former_usernames(env.clone(), address.clone()),
+                links: Self::links_of(env.clone(), address.clone()),
+                verified_links: Self::verified_links_of(&env, &address),
+                locale: Self::get_locale(env.clone(), address.clone()),
+                date_style: Self::date_style(env.clone(), address.clone()),
+                created_at_ledger: Self::created_at_ledger(env.clone(), address.clone()),
+                schema: Self::profile_schema(env.clone(), address.clone()),
+                generation: Self::generation(env.clone(), address),
+                profile,
+            });
+        }
+        result
+    }
 
-    /// Soft delete a profile.
+    /// Get a profile by its registration index (0-based).
     ///
-    /// The username remains reserved (cannot be reused by others).
-    pub fn delete_profile(env: Env, caller: Address) {
-        caller.require_auth();
-
-        let mut profile: Profile = env
+    /// Returns None if the slot is empty or the profile is deleted.
+    /// Useful for crawlers that partition the directory by index range.
+    pub fn get_by_index(env: Env, index: u64) -> Option<Profile> {
+        let username: Option<Bytes> = env
             .storage()
             .persistent()
-            .get(&ProfileKey::Profile(caller.clone()))
-            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+            .get(&ProfileKey::ProfileIndex(index));
 
-        if profile.deleted {
-            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        match username {
+            Some(username) => Self::get_by_username(env, username),
+            None => None,
         }
+    }
 
-        if profile.owner != caller {
-            panic_with_error!(&env, ProfileError::NotAuthorized);
-        }
+    /// Get the username of an active profile.
+    ///
+    /// Returns None for missing or deleted profiles.
+    pub fn username_of(env: Env, address: Address) -> Option<Bytes> {
+        Self::get_by_address(env, address).map(|p| p.username)
+    }
 
-        profile.deleted = true;
-        profile.updated_at = env.ledger().timestamp();
+    /// Resolve a batch of addresses to their usernames.
+    ///
+    /// Missing and deleted profiles are skipped. At most
+    /// `MAX_BATCH_SIZE` addresses may be requested at once.
+    pub fn usernames_of(env: Env, addresses: soroban_sdk::Vec<Address>) -> Map<Address, Bytes> {
+        if addresses.len() > MAX_BATCH_SIZE {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
 
-        env.storage()
-            .persistent()
-            .set(&ProfileKey::Profile(caller.clone()), &profile);
+        let mut result = Map::new(&env);
+        for address in addresses.iter() {
+            if let Some(profile) = Self::get_by_address(env.clone(), address.clone()) {
+                result.set(address, profile.username);
+            }
+        }
+        result
+    }
 
-        emit_profile_deleted(&env, &caller);
+    /// Resolve a batch of addresses to their avatar URLs.
+    ///
+    /// Missing and deleted profiles, and profiles without a string
+    /// `avatar` field, are skipped. On-chain image avatars are not
+    /// included. At most `MAX_BATCH_SIZE` addresses may be requested at once.
+    pub fn avatars_of(env: Env, addresses: soroban_sdk::Vec<Address>) -> Map<Address, String> {
+        if addresses.len() > MAX_BATCH_SIZE {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
+
+        let avatar = Symbol::new(&env, standard_fields::AVATAR);
+        let mut result = Map::new(&env);
+        for address in addresses.iter() {
+            if Self::get_by_address(env.clone(), address.clone()).is_none() {
+                continue;
+            }
+            let value: Option<FieldValue> = env
+                .storage()
+                .persistent()
+                .get(&ProfileKey::Field(address.clone(), avatar.clone()));
+            if let Some(FieldValue::StringField(url)) = value {
+                result.set(address, url);
+            }
+        }
+        result
     }
 
-    /// Transfer profile to a new owner.
+    /// Find active profiles whose display name looks like `name`.
     ///
-    /// Both old and new owners must authorize.
-    pub fn transfer(env: Env, new_owner: Address, caller: Address) {
+    /// Names are compared in the folded form produced by
+    /// `normalize_display_name`, so case, accents, spacing and common
+    /// look-alike characters are ignored. Returns at most `limit`
+    /// addresses; `limit` may be at most `MAX_BATCH_SIZE`.
+    pub fn similar_display_names(env: Env, name: String, limit: u32) -> soroban_sdk::Vec<Address> {
+        if limit > MAX_BATCH_SIZE {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
+
+        let mut result = soroban_sdk::Vec::new(&env);
+        for address in Self::display_name_matches(&env, &name).iter() {
+            if result.len() >= limit {
+                break;
+            }
+            if Self::get_by_address(env.clone(), address.clone()).is_some() {
+                result.push_back(address);
+            }
+        }
+        result
+    }
+
+    /// Add a profile to the display name index if it is missing.
+    ///
+    /// Profiles registered before the index existed are only indexed when
+    /// their display name changes; anyone may call this to index one
+    /// sooner. Indexing twice has no effect.
+    pub fn reindex_display_name(env: Env, address: Address) {
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        Self::index_display_name(&env, &address, &profile.display_name);
+    }
+
+    /// Count the other active profiles whose display name looks like
+    /// this profile's. Returns 0 for missing or deleted profiles.
+    pub fn display_name_collisions(env: Env, address: Address) -> u32 {
+        let profile = match Self::get_by_address(env.clone(), address.clone()) {
+            Some(profile) => profile,
+            None => return 0,
+        };
+
+        Self::display_name_matches(&env, &profile.display_name)
+            .iter()
+            .filter(|other| {
+                *other != address && Self::get_by_address(env.clone(), other.clone()).is_some()
+            })
+            .count() as u32
+    }
+
+    /// Check a batch of addresses for an active profile created before
+    /// the Unix timestamp `registered_before`.
+    ///
+    /// Returns one flag per address, in order. Legacy profiles, whose
+    /// creation time is a ledger sequence, are never eligible. At most
+    /// `MAX_ELIGIBILITY_BATCH` addresses may be checked at once.
+    pub fn eligibility_check(
+        env: Env,
+        addresses: soroban_sdk::Vec<Address>,
+        registered_before: u64,
+    ) -> soroban_sdk::Vec<bool> {
+        if addresses.len() > MAX_ELIGIBILITY_BATCH {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
+
+        let mut result = soroban_sdk::Vec::new(&env);
+        for address in addresses.iter() {
+            result.push_back(Self::is_eligible(&env, address, registered_before));
+        }
+        result
+    }
+
+    /// Count the addresses that pass `eligibility_check`.
+    pub fn count_eligible(
+        env: Env,
+        addresses: soroban_sdk::Vec<Address>,
+        registered_before: u64,
+    ) -> u32 {
+        if addresses.len() > MAX_ELIGIBILITY_BATCH {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
+
+        addresses
+            .iter()
+            .filter(|address| Self::is_eligible(&env, address.clone(), registered_before))
+            .count() as u32
+    }
+
+    /// Get how many seconds ago an active profile was created.
+    ///
+    /// Returns None for missing or deleted profiles, and for legacy
+    /// profiles whose creation time was recorded as a ledger sequence.
+    pub fn profile_age_seconds(env: Env, address: Address) -> Option<u64> {
+        Self::get_by_address(env.clone(), address)
+            .and_then(|p| p.age_seconds(env.ledger().timestamp()))
+    }
+
+    /// Get the ledger sequence at which a profile was registered.
+    ///
+    /// Complements the `created_at` timestamp for consumers that index by
+    /// ledger. Profiles registered before the sequence was recorded return
+    /// their legacy `created_at` when it is clearly a sequence, and None
+    /// otherwise. Deleted profiles are included.
+    pub fn created_at_ledger(env: Env, address: Address) -> Option<u32> {
+        let recorded: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::CreatedAtSeq(address.clone()));
+        if recorded.is_some() {
+            return recorded;
+        }
+
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address))?;
+        if profile.created_at < LEGACY_TIMESTAMP_THRESHOLD {
+            Some(profile.created_at as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Get activity counters for an active profile.
+    ///
+    /// Missing and deleted profiles get all zeros.
+    pub fn profile_stats(env: Env, address: Address) -> ProfileStats {
+        if Self::get_by_address(env.clone(), address.clone()).is_none() {
+            return ProfileStats::default();
+        }
+
+        ProfileStats {
+            fields: Self::field_names(&env, &address).len(),
+            links: Self::links_of(env.clone(), address.clone()).len(),
+            aliases: Self::aliases(env, address).len(),
+        }
+    }
+
+    /// Get the schema version of a profile's stored data.
+    ///
+    /// Profiles registered before versions were recorded report
+    /// `UNVERSIONED_PROFILE_SCHEMA`. Deleted profiles are included;
+    /// returns None if the address has no profile.
+    pub fn profile_schema(env: Env, address: Address) -> Option<u32> {
+        if !env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Profile(address.clone()))
+        {
+            return None;
+        }
+
+        Some(
+            env.storage()
+                .persistent()
+                .get(&ProfileKey::ProfileSchema(address))
+                .unwrap_or(UNVERSIONED_PROFILE_SCHEMA),
+        )
+    }
+
+    /// Check whether an active profile is at least `min_seconds` old.
+    ///
+    /// Returns false for missing or deleted profiles. Legacy profiles,
+    /// whose creation time was recorded as a ledger sequence, are
+    /// treated as old enough since they predate timestamped profiles.
+    pub fn meets_min_age(env: Env, address: Address, min_seconds: u64) -> bool {
+        match Self::get_by_address(env.clone(), address) {
+            Some(profile) => match profile.age_seconds(env.ledger().timestamp()) {
+                Some(age) => age >= min_seconds,
+                None => true,
+            },
+            None => false,
+        }
+    }
+
+    /// Panic unless an active profile is at least `min_seconds` old.
+    ///
+    /// Intended for cross-contract anti-sybil checks. Panics with
+    /// `ProfileNotFound` for missing or deleted profiles and with
+    /// `ProfileTooNew` otherwise. Legacy profiles always pass, as in
+    /// `meets_min_age`.
+    pub fn require_min_age(env: Env, address: Address, min_seconds: u64) {
+        if Self::get_by_address(env.clone(), address.clone()).is_none() {
+            panic_with_error!(&env, ProfileError::ProfileNotFound);
+        }
+
+        if !Self::meets_min_age(env.clone(), address, min_seconds) {
+            panic_with_error!(&env, ProfileError::ProfileTooNew);
+        }
+    }
+
+    /// Get a profile field value.
+    pub fn get_field(env: Env, address: Address, field: Symbol) -> Option<FieldValue> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Field(address, field))
+    }
+
+    /// Get a field's value if it is a `StringField`.
+    pub fn get_string_field(env: Env, address: Address, field: Symbol) -> Option<String> {
+        Self::get_field(env, address, field).and_then(|v| v.as_string().cloned())
+    }
+
+    /// Get a field's value if it is an `IntField`.
+    pub fn get_int_field(env: Env, address: Address, field: Symbol) -> Option<i128> {
+        Self::get_field(env, address, field).and_then(|v| v.as_int())
+    }
+
+    /// Get a field's value if it is a `BoolField`.
+    pub fn get_bool_field(env: Env, address: Address, field: Symbol) -> Option<bool> {
+        Self::get_field(env, address, field).and_then(|v| v.as_bool())
+    }
+
+    /// Get a profile owner's preferred locale, if set.
+    pub fn get_locale(env: Env, address: Address) -> Option<Symbol> {
+        env.storage().persistent().get(&ProfileKey::Locale(address))
+    }
+
+    /// Get all fields for an address.
+    ///
+    /// Note: This returns only fields that have been explicitly set.
+    /// For efficiency, field names must be provided.
+    pub fn get_fields(env: Env, address: Address, field_names: soroban_sdk::Vec<Symbol>) -> Map<Symbol, FieldValue> {
+        let mut result = Map::new(&env);
+
+        for field in field_names.iter() {
+            if let Some(value) = env
+                .storage()
+                .persistent()
+                .get::<_, FieldValue>(&ProfileKey::Field(address.clone(), field.clone()))
+            {
+                result.set(field, value);
+            }
+        }
+
+        result
+    }
+
+    /// Get a profile by username together with a subset of its fields.
+    ///
+    /// Returns None if the username is unknown or the profile is deleted.
+    /// Fields that are not set are absent from the returned map.
+    pub fn get_view_by_username(
+        env: Env,
+        username: Bytes,
+        field_names: soroban_sdk::Vec<Symbol>,
+    ) -> Option<ProfileView> {
+        Self::get_by_username(env.clone(), username).map(|profile| {
+            let fields = Self::get_fields(env, profile.owner.clone(), field_names);
+            ProfileView { profile, fields }
+        })
+    }
+
+    /// Get a profile together with all of its set standard fields.
+    ///
+    /// Returns None if there is no active profile at the address.
+    pub fn get_full_profile(env: Env, address: Address) -> Option<FullProfile> {
+        Self::get_by_address(env.clone(), address).map(|profile| {
+            let mut names = soroban_sdk::Vec::new(&env);
+            for name in standard_fields::ALL {
+                names.push_back(Symbol::new(&env, name));
+            }
+            let fields = Self::get_fields(env, profile.owner.clone(), names);
+            FullProfile { profile, fields }
+        })
+    }
+
+    /// Export the caller's profile and the requested fields.
+    ///
+    /// A self-service data export: requires the caller's authorization
+    /// and also works for soft-deleted profiles. Fields that are not set
+    /// are absent from the returned map.
+    pub fn export_my_profile(
+        env: Env,
+        field_names: soroban_sdk::Vec<Symbol>,
+        caller: Address,
+    ) -> (Profile, Map<Symbol, FieldValue>) {
+        caller.require_auth();
+
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        let fields = Self::get_fields(env, caller, field_names);
+        (profile, fields)
+    }
+
+    /// Get the source a profile was registered through, if any.
+    pub fn source_of(env: Env, address: Address) -> Option<Symbol> {
+        env.storage().persistent().get(&ProfileKey::Source(address))
+    }
+
+    /// Get the number of registrations attributed to a source.
+    pub fn registrations_by_source(env: Env, source: Symbol) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::SourceCount(source))
+            .unwrap_or(0)
+    }
+
+    /// Get the number of profiles registered through `register_from` with
+    /// `app_id`.
+    ///
+    /// Unlike `registrations_by_source`, ids that are not approved sources
+    /// are counted under their own id rather than as `other`.
+    pub fn app_registration_count(env: Env, app_id: Symbol) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::AppRegistrationCount(app_id))
+            .unwrap_or(0)
+    }
+
+    /// Get the number of profiles that are neither deleted nor banned.
+    ///
+    /// Counted from when this counter was introduced; profiles registered
+    /// before then are not included.
+    pub fn active_profile_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::ActiveCount)
+            .unwrap_or(0)
+    }
+
+    /// Get the number of permanently reserved usernames.
+    ///
+    /// Time-limited reservations from `reserve_until` are not counted.
+    pub fn reserved_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::ReservedCount)
+            .unwrap_or(0)
+    }
+
+    /// Get total profile count.
+    pub fn profile_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::ProfileCount)
+            .unwrap_or(0)
+    }
+
+    /// Count profiles registered with `start_ts <= created_at < end_ts`.
+    ///
+    /// Registration times increase with the index, so this binary-searches
+    /// the index rather than walking it. Only the most recent
+    /// `MAX_WINDOW_SCAN` registrations are searched. Deleted and purged
+    /// profiles are still counted as registrations.
+    pub fn count_registered_between(env: Env, start_ts: u64, end_ts: u64) -> u64 {
+        if end_ts <= start_ts {
+            return 0;
+        }
+
+        let count = Self::profile_count(env.clone());
+        let lo = count.saturating_sub(MAX_WINDOW_SCAN);
+        let first = Self::first_index_created_at_or_after(&env, lo, count, start_ts);
+        let end = Self::first_index_created_at_or_after(&env, first, count, end_ts);
+        end - first
+    }
+
+    /// Get the number of registrations on a UTC day, given as days since
+    /// the Unix epoch.
+    ///
+    /// Deleted profiles still count.
+    pub fn registrations_on(env: Env, day: u32) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::DailyRegistrations(day))
+            .unwrap_or(0)
+    }
+
+    /// Get daily registration counts for days `start_day <= day < end_day`.
+    ///
+    /// At most `MAX_STATS_DAYS` days may be requested at once.
+    pub fn registrations_between(env: Env, start_day: u32, end_day: u32) -> soroban_sdk::Vec<u64> {
+        if end_day.saturating_sub(start_day) > MAX_STATS_DAYS {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
+
+        let mut counts = soroban_sdk::Vec::new(&env);
+        for day in start_day..end_day {
+            counts.push_back(Self::registrations_on(env.clone(), day));
+        }
+        counts
+    }
+
+    // ========== Profile Updates ==========
+
+    /// Update the display name.
+    ///
+    /// Setting the current value again only extends the profile's TTL; no
+    /// write happens and no event is emitted. Fails with `ChangeTooSoon`
+    /// within `display_name_cooldown` seconds of the previous change.
+    pub fn set_display_name(env: Env, display_name: String, caller: Address) {
         caller.require_auth();
-        new_owner.require_auth();
 
-        // Get existing profile
         let mut profile: Profile = env
             .storage()
             .persistent()
             .get(&ProfileKey::Profile(caller.clone()))
             .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
 
-        if profile.deleted {
-            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        // Banned profiles are also marked deleted; report the ban instead
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(&env, ProfileError::ProfileBanned);
+        }
+
+        if profile.deleted {
+            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        }
+
+        if profile.owner != caller {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        if profile.display_name == display_name {
+            Self::extend_profile_ttl(&env, &caller);
+            return;
+        }
+
+        let now = env.ledger().timestamp();
+        let changed_key = ProfileKey::DisplayNameChangedAt(caller.clone());
+        let last_change: Option<u64> = env.storage().persistent().get(&changed_key);
+        if let Some(last_change) = last_change {
+            let cooldown = Self::display_name_cooldown(env.clone());
+            if now < last_change.saturating_add(cooldown) {
+                panic_with_error!(&env, ProfileError::ChangeTooSoon);
+            }
+        }
+        Self::write_display_name_changed_at(&env, &caller, Some(now));
+
+        Self::unindex_display_name(&env, &caller, &profile.display_name);
+        Self::index_display_name(&env, &caller, &display_name);
+
+        profile.display_name = display_name;
+        profile.updated_at = now;
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(caller.clone()), &profile);
+
+        Self::extend_profile_ttl(&env, &caller);
+
+        Self::bump_generation(&env, &caller);
+        emit_display_name_changed(&env, &caller);
+    }
+
+    /// Set a string field.
+    ///
+    /// Field setters are no-ops when the field already holds an identical
+    /// value: the TTL is extended but nothing is written and no
+    /// `profile_updated` event is emitted.
+    pub fn set_string_field(env: Env, field: Symbol, value: String, caller: Address) {
+        Self::set_field_internal(&env, &caller, field, FieldValue::StringField(value));
+    }
+
+    /// Set an integer field.
+    pub fn set_int_field(env: Env, field: Symbol, value: i128, caller: Address) {
+        Self::set_field_internal(&env, &caller, field, FieldValue::IntField(value));
+    }
+
+    /// Set a boolean field.
+    pub fn set_bool_field(env: Env, field: Symbol, value: bool, caller: Address) {
+        Self::set_field_internal(&env, &caller, field, FieldValue::BoolField(value));
+    }
+
+    /// Set a raw bytes field.
+    ///
+    /// Setting `avatar` this way stores a small on-chain image, rendered
+    /// as a `data:` URI. Such avatars are capped at `MAX_AVATAR_BYTES`.
+    pub fn set_bytes_field(env: Env, field: Symbol, value: Bytes, caller: Address) {
+        Self::set_field_internal(&env, &caller, field, FieldValue::BytesField(value));
+    }
+
+    /// Remove a field.
+    pub fn remove_field(env: Env, field: Symbol, caller: Address) {
+        caller.require_auth();
+
+        // Verify profile exists and is active
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(&env, ProfileError::ProfileBanned);
+        }
+
+        if profile.deleted {
+            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        }
+
+        if profile.owner != caller {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        Self::require_not_frozen(&env, &caller, &field);
+
+        if Self::clear_field(&env, &caller, &field) {
+            Self::bump_generation(&env, &caller);
+        }
+    }
+
+    /// Record a verified external account link, such as a GitHub handle.
+    ///
+    /// `proof` is the hash of an off-chain proof (e.g. a signed gist). The
+    /// contract does not check it; it is stored for auditors. Profile
+    /// pages show a checkmark next to the field named `platform` while its
+    /// value matches `handle`.
+    pub fn set_verified_link(
+        env: Env,
+        platform: Symbol,
+        handle: String,
+        proof: BytesN<32>,
+        caller: Address,
+    ) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        let (max_field_len, max_fields) = Self::field_limits(env.clone());
+        if handle.is_empty() || handle.len() > max_field_len {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+
+        // At most one verified link per possible field
+        let list = ProfileKey::VerifiedLinks(caller.clone());
+        if !Self::add_to_symbol_list(&env, &list, &platform, max_fields) {
+            panic_with_error!(&env, ProfileError::TooManyFields);
+        }
+
+        let key = ProfileKey::VerifiedLink(caller.clone(), platform.clone());
+        env.storage().persistent().set(&key, &(handle, proof));
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        Self::bump_generation(&env, &caller);
+
+        emit_link_verified(&env, &caller, &platform);
+    }
+
+    /// Get a profile's verified link for `platform` as (handle, proof hash).
+    pub fn verified_link(
+        env: Env,
+        address: Address,
+        platform: Symbol,
+    ) -> Option<(String, BytesN<32>)> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::VerifiedLink(address, platform))
+    }
+
+    /// Set the Gravatar email hash used when no avatar is set.
+    ///
+    /// `hash` is the MD5 (16 bytes) or SHA-256 (32 bytes) digest of the
+    /// trimmed, lowercased email, computed off-chain so the email is never
+    /// submitted. An empty `hash` removes it.
+    pub fn set_avatar_email_hash(env: Env, hash: Bytes, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        let key = ProfileKey::AvatarEmailHash(caller.clone());
+        if hash.is_empty() {
+            env.storage().persistent().remove(&key);
+            Self::bump_generation(&env, &caller);
+            return;
+        }
+
+        if hash.len() != 16 && hash.len() != 32 {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+
+        env.storage().persistent().set(&key, &hash);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        Self::bump_generation(&env, &caller);
+    }
+
+    /// Append a labeled link to the caller's profile.
+    ///
+    /// `url` must pass `validate_link_url`. A profile may hold up to
+    /// `MAX_LINKS` links.
+    pub fn add_link(env: Env, label: String, url: String, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        if label.is_empty() {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+        if label.len() > MAX_LINK_LABEL_LENGTH {
+            panic_with_error!(&env, ProfileError::ValueTooLong);
+        }
+        if !validate_link_url(&soroban_render_sdk::bytes::string_to_bytes(&env, &url)) {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+
+        let mut links = Self::links_of(env.clone(), caller.clone());
+        if links.len() >= MAX_LINKS {
+            panic_with_error!(&env, ProfileError::TooManyLinks);
+        }
+
+        links.push_back((label, url));
+        Self::write_links(&env, &caller, &links);
+        Self::bump_generation(&env, &caller);
+    }
+
+    /// Remove the link at `index` from the caller's profile.
+    pub fn remove_link(env: Env, index: u32, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        let mut links = Self::links_of(env.clone(), caller.clone());
+        if index >= links.len() {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+
+        links.remove(index);
+        Self::write_links(&env, &caller, &links);
+        Self::bump_generation(&env, &caller);
+    }
+
+    /// Reorder the caller's links.
+    ///
+    /// `order` lists the current index of each link in its new position
+    /// and must be a permutation of `0..len`.
+    pub fn reorder_links(env: Env, order: soroban_sdk::Vec<u32>, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        let links = Self::links_of(env.clone(), caller.clone());
+        if order.len() != links.len() {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+
+        let mut reordered = soroban_sdk::Vec::new(&env);
+        for (position, index) in order.iter().enumerate() {
+            // Each index must be in range and appear once
+            if index >= links.len() || order.first_index_of(index) != Some(position as u32) {
+                panic_with_error!(&env, ProfileError::InvalidFieldValue);
+            }
+            reordered.push_back(links.get_unchecked(index));
+        }
+
+        Self::write_links(&env, &caller, &reordered);
+        Self::bump_generation(&env, &caller);
+    }
+
+    /// Get a profile's links as (label, url) pairs, in display order.
+    pub fn links_of(env: Env, address: Address) -> soroban_sdk::Vec<(String, String)> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Links(address))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env))
+    }
+
+    /// Freeze a field so it can no longer be set or removed.
+    ///
+    /// Freezing is permanent and only the profile owner can freeze
+    /// their own fields.
+    pub fn freeze_field(env: Env, field: Symbol, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        env.storage().persistent().set(
+            &ProfileKey::FrozenField(caller.clone(), field.clone()),
+            &true,
+        );
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::FrozenField(caller.clone(), field.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        Self::bump_generation(&env, &caller);
+
+        emit_field_frozen(&env, &caller, &field);
+    }
+
+    /// Check whether a profile field has been frozen.
+    pub fn is_field_frozen(env: Env, address: Address, field: Symbol) -> bool {
+        env.storage()
+            .persistent()
+            .has(&ProfileKey::FrozenField(address, field))
+    }
+
+    /// Set the caller's preferred locale, e.g. `en` or `pt_BR`.
+    ///
+    /// Stored under a dedicated key so apps can read it cheaply.
+    pub fn set_locale(env: Env, locale: Symbol, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        let code = SymbolStr::try_from_val(&env, &locale.to_symbol_val())
+            .unwrap_or_else(|_| panic_with_error!(&env, ProfileError::InvalidFieldValue));
+        if !validation::validate_locale(code.as_ref()) {
+            panic_with_error!(&env, ProfileError::InvalidFieldValue);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Locale(caller.clone()), &locale);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Locale(caller.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+
+        let generation = Self::bump_generation(&env, &caller);
+        emit_profile_updated(
+            &env,
+            &caller,
+            &Symbol::new(&env, standard_fields::LOCALE),
+            generation,
+        );
+    }
+
+    /// Choose how dates are shown on the caller's profile page.
+    ///
+    /// Profile cards always show dates as `DateOnly`.
+    pub fn set_date_style(env: Env, style: DateStyle, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::DateStyle(caller.clone()), &style);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::DateStyle(caller.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        Self::bump_generation(&env, &caller);
+    }
+
+    /// Get the date style for a profile page, `Full` if not set.
+    pub fn date_style(env: Env, address: Address) -> DateStyle {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::DateStyle(address))
+            .unwrap_or(DateStyle::Full)
+    }
+
+    /// Choose whether the profile page links the caller's `email` field
+    /// in its Contact section. Off by default.
+    pub fn set_show_email(env: Env, show: bool, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::ShowEmail(caller.clone()), &show);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::ShowEmail(caller.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        Self::bump_generation(&env, &caller);
+    }
+
+    /// Check whether a profile page links the owner's email.
+    pub fn shows_email(env: Env, address: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::ShowEmail(address))
+            .unwrap_or(false)
+    }
+
+    /// Set how profile cards render inside a host app.
+    ///
+    /// The app authorizes for itself, so `caller` must be `app`. Used by
+    /// `render_profile_card_for_app`.
+    pub fn set_app_card_prefs(env: Env, app: Address, prefs: CardOptions, caller: Address) {
+        caller.require_auth();
+
+        if caller != app {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::AppCardPrefs(app.clone()), &prefs);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::AppCardPrefs(app),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+    }
+
+    /// Get the card options a host app registered, if any.
+    pub fn app_card_prefs(env: Env, app: Address) -> Option<CardOptions> {
+        // Read field by field so preferences stored before a flag was
+        // added still decode, with the flag at its default
+        let stored: Option<Map<Symbol, bool>> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::AppCardPrefs(app));
+        stored.map(|fields| {
+            let defaults = CardOptions::default();
+            let flag =
+                |name: &str, default: bool| fields.get(Symbol::new(&env, name)).unwrap_or(default);
+            CardOptions {
+                compact: flag("compact", defaults.compact),
+                show_avatar: flag("show_avatar", defaults.show_avatar),
+                show_bio: flag("show_bio", defaults.show_bio),
+                show_joined: flag("show_joined", defaults.show_joined),
+                show_dweb: flag("show_dweb", defaults.show_dweb),
+                text: flag("text", defaults.text),
+            }
+        })
+    }
+
+    // ========== Profile Management ==========
+
+    /// Add a secondary username that resolves to the caller's profile.
+    ///
+    /// The alias follows the same rules as a primary username and must be
+    /// free, including the look-alike check when confusables are blocked.
+    /// It gets a username mapping but no second profile, so
+    /// `get_by_username` returns the same profile for either name. A
+    /// profile may hold up to `MAX_ALIASES` aliases.
+    pub fn add_alias(env: Env, alias: Bytes, caller: Address) {
+        let alias = canonicalize_username(&alias);
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        if !Self::username_is_valid(&env, &alias) {
+            panic_with_error!(&env, ProfileError::InvalidUsername);
+        }
+
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Username(alias.clone()))
+        {
+            panic_with_error!(&env, ProfileError::UsernameTaken);
+        }
+
+        if Self::is_reserved(&env, &alias) {
+            panic_with_error!(&env, ProfileError::UsernameReserved);
+        }
+
+        if Self::namespace_registrar(env.clone(), alias.clone()).is_some() {
+            panic_with_error!(&env, ProfileError::NamespaceDelegated);
+        }
+
+        Self::require_not_confusable(&env, &alias, &caller);
+
+        let mut aliases = Self::aliases(env.clone(), caller.clone());
+        if aliases.len() >= MAX_ALIASES {
+            panic_with_error!(&env, ProfileError::TooManyAliases);
+        }
+        aliases.push_back(alias.clone());
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Username(alias.clone()), &caller);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Aliases(caller.clone()), &aliases);
+
+        for key in [
+            ProfileKey::Username(alias.clone()),
+            ProfileKey::Aliases(caller.clone()),
+        ] {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        }
+        Self::bump_generation(&env, &caller);
+
+        emit_alias_added(&env, &caller, &alias);
+    }
+
+    /// Get the aliases held by a profile, in the order they were added.
+    pub fn aliases(env: Env, address: Address) -> soroban_sdk::Vec<Bytes> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Aliases(address))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env))
+    }
+
+    /// Get every username an address controls: the primary username
+    /// followed by its aliases.
+    ///
+    /// Empty if there is no active profile at the address.
+    pub fn owned_usernames(env: Env, address: Address) -> soroban_sdk::Vec<Bytes> {
+        let mut names = soroban_sdk::Vec::new(&env);
+        if let Some(profile) = Self::get_by_address(env.clone(), address.clone()) {
+            names.push_back(profile.username);
+            names.append(&Self::aliases(env, address));
+        }
+        names
+    }
+
+    /// Get a profile's generation, a counter incremented once by every
+    /// change to it.
+    ///
+    /// Clients can cache a profile with its generation and refetch only
+    /// when the generation moves. A field batch, such as the fields of
+    /// `register_full`, counts as one change. Newly registered profiles
+    /// and addresses that never held a profile are at 0. The generation
+    /// never decreases: an address keeps counting after its profile is
+    /// transferred away or purged.
+    pub fn generation(env: Env, address: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Generation(address))
+            .unwrap_or(0)
+    }
+
+    /// Hash of a profile's current state, for comparing against a cached
+    /// copy.
+    ///
+    /// Covers the profile record and its generation, so any change that
+    /// bumps the generation changes the hash. The settings that change how
+    /// the profile renders (avatar email hash, date style, email display,
+    /// hidden and deactivated flags) are covered directly as well.
+    pub fn profile_hash(env: Env, address: Address) -> BytesN<32> {
+        let storage = env.storage().persistent();
+        let profile: Option<Profile> = storage.get(&ProfileKey::Profile(address.clone()));
+        let avatar_email_hash: Option<Bytes> =
+            storage.get(&ProfileKey::AvatarEmailHash(address.clone()));
+        let state = (
+            profile,
+            Self::generation(env.clone(), address.clone()),
+            avatar_email_hash,
+            Self::date_style(env.clone(), address.clone()),
+            Self::shows_email(env.clone(), address.clone()),
+            storage.has(&ProfileKey::Hidden(address.clone())),
+            storage.has(&ProfileKey::Deactivated(address)),
+        );
+        env.crypto().sha256(&state.to_xdr(&env)).into()
+    }
+
+    /// Temporarily hide the caller's profile.
+    ///
+    /// Lookups treat a deactivated profile as missing and renderers show
+    /// it anonymously, but all data is kept, the username stays taken and
+    /// the owner can edit it. `activate` restores it at any time.
+    pub fn deactivate(env: Env, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        let key = ProfileKey::Deactivated(caller.clone());
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        Self::bump_generation(&env, &caller);
+
+        emit_profile_deactivated(&env, &caller);
+    }
+
+    /// Restore the caller's deactivated profile.
+    ///
+    /// Banned and deleted profiles cannot be reactivated.
+    pub fn activate(env: Env, caller: Address) {
+        caller.require_auth();
+        Self::require_owned_profile(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Deactivated(caller.clone()));
+        Self::bump_generation(&env, &caller);
+
+        emit_profile_activated(&env, &caller);
+    }
+
+    /// Check whether a profile is deactivated by its owner.
+    pub fn is_deactivated(env: Env, address: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&ProfileKey::Deactivated(address))
+    }
+
+    /// Soft delete a profile.
+    ///
+    /// The username remains reserved (cannot be reused by others).
+    /// Aliases are released and can be registered again.
+    pub fn delete_profile(env: Env, caller: Address) {
+        caller.require_auth();
+
+        let mut profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if profile.deleted {
+            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        }
+
+        if profile.owner != caller {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        profile.deleted = true;
+        profile.updated_at = env.ledger().timestamp();
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(caller.clone()), &profile);
+        // Deletion supersedes a deactivation
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Deactivated(caller.clone()));
+        Self::adjust_count(&env, &ProfileKey::ActiveCount, -1);
+        Self::bump_generation(&env, &caller);
+
+        for alias in Self::aliases(env.clone(), caller.clone()).iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Username(alias));
+        }
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Aliases(caller.clone()));
+
+        emit_profile_deleted(&env, &caller);
+    }
+
+    /// Permanently remove the caller's profile and data.
+    ///
+    /// Unlike `delete_profile`, this removes the profile record, every
+    /// indexed field, the locale, source attribution, registration ledger
+    /// and aliases. The profile cannot be restored afterwards. With
+    /// `release_username` the username becomes available to others;
+    /// otherwise it stays reserved.
+    ///
+    /// Moderation records (ban, hidden flag, reports and admin notes) are
+    /// kept. A banned profile may be purged but cannot release its
+    /// username, and its address cannot register again. Fields written
+    /// before the field index existed are not tracked and are left in
+    /// place.
+    pub fn hard_delete_profile(env: Env, release_username: bool, caller: Address) {
+        caller.require_auth();
+
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if profile.owner != caller {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        if release_username && Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(&env, ProfileError::ProfileBanned);
+        }
+
+        let was_active = profile.is_active();
+        let created_at = profile.created_at;
+        Self::unindex_display_name(&env, &caller, &profile.display_name);
+        let username = profile.username;
+        Self::move_confusable_key(&env, &username, &caller, None);
+        Self::remove_from_directory(&env, &username);
+
+        // Fields with their freeze markers, history and verified links
+        for field in Self::field_names(&env, &caller).iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Field(caller.clone(), field.clone()));
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::FrozenField(caller.clone(), field.clone()));
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::FieldHistory(caller.clone(), field.clone()));
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::VerifiedLink(caller.clone(), field));
+        }
+
+        // Verified links and history can outlive the field they belong to
+        let links = ProfileKey::VerifiedLinks(caller.clone());
+        for platform in Self::symbol_list(&env, &links).iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::VerifiedLink(caller.clone(), platform));
+        }
+        let history = ProfileKey::HistoryFields(caller.clone());
+        for field in Self::symbol_list(&env, &history).iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::FieldHistory(caller.clone(), field));
+        }
+
+        for alias in Self::aliases(env.clone(), caller.clone()).iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Username(alias));
+        }
+
+        for former in Self::former_usernames(env.clone(), caller.clone()).iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::UsernameAlias(former));
+        }
+
+        for key in [
+            ProfileKey::Profile(caller.clone()),
+            ProfileKey::FieldIndex(caller.clone()),
+            ProfileKey::VerifiedLinks(caller.clone()),
+            ProfileKey::HistoryFields(caller.clone()),
+            ProfileKey::Locale(caller.clone()),
+            ProfileKey::DateStyle(caller.clone()),
+            ProfileKey::ShowEmail(caller.clone()),
+            ProfileKey::Deactivated(caller.clone()),
+            ProfileKey::DisplayNameChangedAt(caller.clone()),
+            ProfileKey::AvatarEmailHash(caller.clone()),
+            ProfileKey::Links(caller.clone()),
+            ProfileKey::Source(caller.clone()),
+            ProfileKey::CreatedAtSeq(caller.clone()),
+            ProfileKey::ProfileSchema(caller.clone()),
+            ProfileKey::TtlExtendedAt(caller.clone()),
+            ProfileKey::Aliases(caller.clone()),
+            ProfileKey::FormerUsernames(caller.clone()),
+            ProfileKey::Username(username.clone()),
+        ] {
+            env.storage().persistent().remove(&key);
+        }
+
+        // Empty the registration index slot so it cannot resolve to
+        // whoever claims the name next, leaving its creation time behind
+        let index: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::IndexSlot(username.clone()));
+        if let Some(index) = index {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::ProfileIndex(index));
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::IndexSlot(username.clone()));
+
+            let tombstone = ProfileKey::PurgedIndex(index);
+            env.storage().persistent().set(&tombstone, &created_at);
+            env.storage().persistent().extend_ttl(
+                &tombstone,
+                PROFILE_TTL_THRESHOLD,
+                PROFILE_TTL_EXTEND,
+            );
+        }
+
+        if !release_username {
+            Self::set_reserved(&env, &username);
+        }
+
+        if was_active {
+            Self::adjust_count(&env, &ProfileKey::ActiveCount, -1);
+        }
+        Self::bump_generation(&env, &caller);
+
+        emit_profile_purged(&env, &caller, &username, release_username);
+    }
+
+    /// Permanently remove the caller's profile but keep the username for
+    /// the caller alone.
+    ///
+    /// Purges the profile as `hard_delete_profile` does without releasing
+    /// the name, then assigns the reservation to the caller with
+    /// `CLAIM_CONDITION_NONE`. Only the caller can take the name back, with
+    /// `register` or `claim_reserved`. The assignment does not expire;
+    /// only the admin can lift it, with `unreserve_username`. Banned
+    /// profiles cannot keep their name this way.
+    pub fn delete_profile_keep_name(env: Env, caller: Address) {
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(&env, ProfileError::ProfileBanned);
+        }
+
+        Self::hard_delete_profile(env.clone(), false, caller.clone());
+
+        let condition = Symbol::new(&env, CLAIM_CONDITION_NONE);
+        let key = ProfileKey::ReservedFor(profile.username);
+        env.storage().persistent().set(&key, &(caller, condition));
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Transfer profile to a new owner.
+    ///
+    /// Both old and new owners must authorize.
+    pub fn transfer(env: Env, new_owner: Address, caller: Address) {
+        caller.require_auth();
+        new_owner.require_auth();
+
+        // Get existing profile
+        let mut profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        // Banned usernames must not be reclaimable through a transfer
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(&env, ProfileError::ProfileBanned);
+        }
+
+        if profile.deleted {
+            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        }
+
+        if profile.owner != caller {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        // Check new owner doesn't already have a profile
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Profile(new_owner.clone()))
+        {
+            panic_with_error!(&env, ProfileError::ProfileExists);
+        }
+
+        // Legacy profiles have no usable age and are always transferable
+        let min_age = Self::min_transfer_age(env.clone());
+        if let Some(age) = profile.age_seconds(env.ledger().timestamp()) {
+            if age < min_age {
+                panic_with_error!(&env, ProfileError::TransferTooEarly);
+            }
+        }
+
+        let username = profile.username.clone();
+
+        Self::unindex_display_name(&env, &caller, &profile.display_name);
+        Self::index_display_name(&env, &new_owner, &profile.display_name);
+
+        // Update profile ownership
+        profile.owner = new_owner.clone();
+        profile.updated_at = env.ledger().timestamp();
+
+        // Update username mapping
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Username(username.clone()), &new_owner);
+        Self::move_confusable_key(&env, &username, &caller, Some(&new_owner));
+
+        // Remove old profile entry
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Profile(caller.clone()));
+
+        // Set new profile entry
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(new_owner.clone()), &profile);
+
+        // Keep the directory pointing at the current owner
+        Self::update_directory_owner(&env, &username, &new_owner);
+
+        let created_at_ledger: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::CreatedAtSeq(caller.clone()));
+        Self::write_created_at_ledger(&env, &caller, None);
+        Self::write_created_at_ledger(&env, &new_owner, created_at_ledger);
+
+        let schema: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ProfileSchema(caller.clone()));
+        Self::write_profile_schema(&env, &caller, None);
+        Self::write_profile_schema(&env, &new_owner, schema);
+
+        // The new owner inherits the display name cooldown
+        let changed_at: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::DisplayNameChangedAt(caller.clone()));
+        Self::write_display_name_changed_at(&env, &caller, None);
+        Self::write_display_name_changed_at(&env, &new_owner, changed_at);
+
+        // Both addresses changed; neither generation may go backwards
+        let generation = Self::generation(env.clone(), caller.clone())
+            .max(Self::generation(env.clone(), new_owner.clone()))
+            .saturating_add(1);
+        Self::write_generation(&env, &caller, generation);
+        Self::write_generation(&env, &new_owner, generation);
+
+        // Aliases follow the profile
+        let aliases = Self::aliases(env.clone(), caller.clone());
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Aliases(caller.clone()));
+        Self::write_aliases(&env, &new_owner, &aliases);
+
+        let former = Self::former_usernames(env.clone(), caller.clone());
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::FormerUsernames(caller.clone()));
+        Self::write_former_usernames(&env, &new_owner, &former);
+
+        // A hidden profile stays hidden after a transfer
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Hidden(caller.clone()))
+        {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Hidden(caller.clone()));
+            env.storage()
+                .persistent()
+                .set(&ProfileKey::Hidden(new_owner.clone()), &true);
+        }
+
+        // Likewise a deactivated one stays deactivated
+        if Self::is_deactivated(env.clone(), caller.clone()) {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Deactivated(caller.clone()));
+            let key = ProfileKey::Deactivated(new_owner.clone());
+            env.storage().persistent().set(&key, &true);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        }
+
+        // Extend TTL
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Username(username.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::TtlExtendedAt(caller.clone()));
+        Self::extend_profile_ttl(&env, &new_owner);
+
+        emit_username_transferred(&env, &username, &caller, &new_owner);
+
+        Self::notify_transfer_hook(&env, &username, &caller, &new_owner);
+    }
+
+    // ========== Reports ==========
+
+    /// Report a profile for moderator review.
+    ///
+    /// The reporter must have an active profile and may hold only one open
+    /// report per target. Reports stay open until `resolve_reports`.
+    pub fn report_profile(env: Env, target: Address, reason: Symbol, reporter: Address) {
+        reporter.require_auth();
+
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(reporter.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if profile.deleted {
+            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        }
+
+        if reporter == target {
+            panic_with_error!(&env, ProfileError::NotAuthorized);
+        }
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Profile(target.clone()))
+        {
+            panic_with_error!(&env, ProfileError::ProfileNotFound);
+        }
+
+        let report_key = ProfileKey::Report(target.clone(), reporter.clone());
+        if env.storage().persistent().has(&report_key) {
+            panic_with_error!(&env, ProfileError::AlreadyReported);
+        }
+
+        let mut reporters: soroban_sdk::Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Reporters(target.clone()))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env));
+
+        if reporters.len() >= MAX_OPEN_REPORTS {
+            panic_with_error!(&env, ProfileError::ReportLimitReached);
+        }
+
+        reporters.push_back(reporter.clone());
+
+        env.storage()
+            .persistent()
+            .set(&report_key, &(reason.clone(), env.ledger().timestamp()));
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Reporters(target.clone()), &reporters);
+
+        env.storage().persistent().extend_ttl(
+            &report_key,
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Reporters(target.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+
+        emit_profile_reported(&env, &target, &reporter, &reason);
+
+        // Hide the profile once it crosses the configured threshold
+        let threshold: u32 = env
+            .storage()
+            .instance()
+            .get(&ProfileKey::AutoHideThreshold)
+            .unwrap_or(0);
+
+        if threshold > 0
+            && reporters.len() >= threshold
+            && !env
+                .storage()
+                .persistent()
+                .has(&ProfileKey::Hidden(target.clone()))
+        {
+            env.storage()
+                .persistent()
+                .set(&ProfileKey::Hidden(target.clone()), &true);
+            env.storage().persistent().extend_ttl(
+                &ProfileKey::Hidden(target.clone()),
+                PROFILE_TTL_THRESHOLD,
+                PROFILE_TTL_EXTEND,
+            );
+            Self::bump_generation(&env, &target);
+
+            emit_profile_hidden(&env, &target);
+        }
+    }
+
+    /// Clear all open reports against a profile (admin only).
+    pub fn resolve_reports(env: Env, target: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        let reporters: soroban_sdk::Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Reporters(target.clone()))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env));
+
+        for reporter in reporters.iter() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Report(target.clone(), reporter));
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Reporters(target.clone()));
+
+        emit_reports_resolved(&env, &target);
+
+        Self::unhide(&env, &target);
+    }
+
+    /// Restore a hidden profile without clearing its reports (admin only).
+    pub fn unhide_profile(env: Env, address: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::unhide(&env, &address);
+    }
+
+    /// Check whether a profile is hidden pending moderator review.
+    ///
+    /// Hidden profiles are still returned by `get_by_username` and
+    /// `get_by_address`; renderers show an "under review" notice instead.
+    pub fn is_hidden(env: Env, address: Address) -> bool {
+        env.storage().persistent().has(&ProfileKey::Hidden(address))
+    }
+
+    /// Set how many open reports hide a profile (admin only).
+    ///
+    /// A threshold of 0 disables auto-hiding.
+    pub fn set_auto_hide_threshold(env: Env, threshold: u32, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::AutoHideThreshold,
+            "auto_hide_threshold",
+            Some(threshold),
+            &caller,
+        );
+    }
+
+    /// Get the auto-hide report threshold (0 = disabled).
+    pub fn auto_hide_threshold(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::AutoHideThreshold)
+            .unwrap_or(0)
+    }
+
+    /// Get the number of open reports against a profile.
+    pub fn report_count(env: Env, target: Address) -> u32 {
+        let reporters: Option<soroban_sdk::Vec<Address>> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Reporters(target));
+
+        reporters.map(|r| r.len()).unwrap_or(0)
+    }
+
+    /// Get the open reports against a profile (admin only).
+    ///
+    /// Returns (reporter, reason, timestamp) for each open report.
+    pub fn reports_of(
+        env: Env,
+        target: Address,
+        caller: Address,
+    ) -> soroban_sdk::Vec<(Address, Symbol, u64)> {
+        Self::require_admin(&env, &caller);
+
+        let reporters: soroban_sdk::Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Reporters(target.clone()))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env));
+
+        let mut result = soroban_sdk::Vec::new(&env);
+        for reporter in reporters.iter() {
+            let report: Option<(Symbol, u64)> = env
+                .storage()
+                .persistent()
+                .get(&ProfileKey::Report(target.clone(), reporter.clone()));
+
+            if let Some((reason, timestamp)) = report {
+                result.push_back((reporter, reason, timestamp));
+            }
+        }
+
+        result
+    }
+
+    // ========== TTL Maintenance ==========
+
+    /// Extend the TTL of every entry belonging to a profile.
+    ///
+    /// Permissionless, so keepers can keep profiles live. Covers the
+    /// profile, its username mapping, directory and registration index
+    /// slots and display name index, each field with its freeze marker,
+    /// verified links and history, plus any aliases, source attribution,
+    /// flags or admin note. A profile near the field limit with many
+    /// verified links needs a large read footprint.
+    pub fn bump_profile(env: Env, address: Address) {
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        Self::extend_profile_ttl(&env, &address);
+        Self::bump_if_present(&env, &ProfileKey::CreatedAtSeq(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::ProfileSchema(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Username(profile.username.clone()));
+        Self::bump_if_present(
+            &env,
+            &ProfileKey::ConfusableKey(confusable_username(&env, &profile.username)),
+        );
+        Self::bump_if_present(&env, &ProfileKey::DirectorySlot(profile.username.clone()));
+
+        let slot = ProfileKey::IndexSlot(profile.username);
+        let index: Option<u64> = env.storage().persistent().get(&slot);
+        if let Some(index) = index {
+            Self::bump_if_present(&env, &slot);
+            Self::bump_if_present(&env, &ProfileKey::ProfileIndex(index));
+        }
+        let name_key = Self::display_name_key(&env, &profile.display_name);
+        Self::bump_if_present(&env, &ProfileKey::DisplayNameIndex(name_key));
+
+        for field in Self::field_names(&env, &address).iter() {
+            Self::bump_if_present(&env, &ProfileKey::Field(address.clone(), field.clone()));
+            Self::bump_if_present(&env, &ProfileKey::FrozenField(address.clone(), field));
+        }
+        Self::bump_if_present(&env, &ProfileKey::Source(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Locale(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::DateStyle(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::ShowEmail(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Generation(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::AvatarEmailHash(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Links(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::FieldIndex(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Hidden(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Deactivated(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::Banned(address.clone()));
+        Self::bump_if_present(&env, &ProfileKey::AdminNote(address.clone()));
+
+        for alias in Self::aliases(env.clone(), address.clone()).iter() {
+            Self::bump_if_present(&env, &ProfileKey::Username(alias));
+        }
+        Self::bump_if_present(&env, &ProfileKey::Aliases(address.clone()));
+
+        for former in Self::former_usernames(env.clone(), address.clone()).iter() {
+            Self::bump_if_present(&env, &ProfileKey::UsernameAlias(former));
+        }
+        Self::bump_if_present(&env, &ProfileKey::FormerUsernames(address.clone()));
+
+        let links = ProfileKey::VerifiedLinks(address.clone());
+        for platform in Self::symbol_list(&env, &links).iter() {
+            Self::bump_if_present(&env, &ProfileKey::VerifiedLink(address.clone(), platform));
+        }
+        Self::bump_if_present(&env, &links);
+        let history = ProfileKey::HistoryFields(address.clone());
+        for field in Self::symbol_list(&env, &history).iter() {
+            Self::bump_if_present(&env, &ProfileKey::FieldHistory(address.clone(), field));
+        }
+        Self::bump_if_present(&env, &history);
+    }
+
+    /// Estimate the remaining TTL of a profile entry, in ledgers.
+    ///
+    /// Based on when the contract last extended the entry, so TTL added
+    /// from outside the contract is not reflected and the estimate errs
+    /// low. Returns None if no extension has been recorded, as for
+    /// profiles last touched before tracking was added.
+    pub fn profile_ttl_remaining(env: Env, address: Address) -> Option<u32> {
+        let extended_at: u32 = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::TtlExtendedAt(address))?;
+
+        Some(
+            extended_at
+                .saturating_add(PROFILE_TTL_EXTEND)
+                .saturating_sub(env.ledger().sequence()),
+        )
+    }
+
+    /// Extend the TTL of a username reservation.
+    ///
+    /// Permissionless, so admins can run keepers for long-lived
+    /// reservations. Reservations that are never bumped are archived
+    /// after `PROFILE_TTL_EXTEND` ledgers, after which the name can be
+    /// registered; this is intended, since a reservation nobody
+    /// maintains should not block a name forever.
+    pub fn bump_reservation(env: Env, username: Bytes) {
+        let username = canonicalize_username(&username);
+        Self::bump_if_present(&env, &ProfileKey::ReservedUsername(username.clone()));
+        Self::bump_if_present(&env, &ProfileKey::ReservedFor(username.clone()));
+        Self::bump_if_present(&env, &ProfileKey::ReservedUntil(username));
+    }
+
+    /// Extend the TTL of the contract instance and its configuration.
+    pub fn bump_instance(env: Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    // ========== Admin Functions ==========
+
+    /// Reserve a username (admin only).
+    pub fn reserve_username(env: Env, username: Bytes, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        if !Self::username_is_valid(&env, &username) {
+            panic_with_error!(&env, ProfileError::InvalidUsername);
+        }
+
+        Self::set_reserved(&env, &username);
+
+        emit_username_reserved(&env, &username);
+    }
+
+    /// Reserve a username for a beneficiary to claim (admin only).
+    ///
+    /// The name is reserved as with `reserve_username`, and `beneficiary`
+    /// may take it with `claim_reserved` once `condition` holds.
+    pub fn reserve_username_with_condition(
+        env: Env,
+        username: Bytes,
+        beneficiary: Address,
+        condition: Symbol,
+        caller: Address,
+    ) {
+        let username = canonicalize_username(&username);
+        Self::reserve_username(env.clone(), username.clone(), caller);
+
+        env.storage().persistent().set(
+            &ProfileKey::ReservedFor(username.clone()),
+            &(beneficiary, condition),
+        );
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::ReservedFor(username),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+    }
+
+    /// Reserve a username until a Unix timestamp (admin only).
+    ///
+    /// The name is treated as reserved while the ledger timestamp is
+    /// before `expiry`, and becomes registrable automatically afterwards.
+    pub fn reserve_until(env: Env, username: Bytes, expiry: u64, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        if !Self::username_is_valid(&env, &username) {
+            panic_with_error!(&env, ProfileError::InvalidUsername);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::ReservedUntil(username.clone()), &expiry);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::ReservedUntil(username.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+
+        emit_username_reserved(&env, &username);
+    }
+
+    /// Discourage a username without blocking it (admin only).
+    ///
+    /// The name stays available; `reservation_status` reports it as
+    /// `RESERVATION_SOFT` so frontends can ask for confirmation.
+    pub fn soft_reserve(env: Env, username: Bytes, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        if !Self::username_is_valid(&env, &username) {
+            panic_with_error!(&env, ProfileError::InvalidUsername);
+        }
+
+        let key = ProfileKey::SoftReserved(username);
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Get the reservation status of a username.
+    ///
+    /// One of `RESERVATION_FREE`, `RESERVATION_SOFT`, `RESERVATION_HARD`
+    /// or `RESERVATION_TAKEN`. Does not check the username format; use
+    /// `is_username_available` for that.
+    pub fn reservation_status(env: Env, username: Bytes) -> u32 {
+        let username = canonicalize_username(&username);
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Username(username.clone()))
+        {
+            RESERVATION_TAKEN
+        } else if Self::is_reserved(&env, &username) {
+            RESERVATION_HARD
+        } else if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::SoftReserved(username))
+        {
+            RESERVATION_SOFT
+        } else {
+            RESERVATION_FREE
+        }
+    }
+
+    /// Release a reserved or soft-reserved username (admin only).
+    pub fn unreserve_username(env: Env, username: Bytes, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        Self::clear_reserved(&env, &username);
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::SoftReserved(username.clone()));
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::ReservedUntil(username.clone()));
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::ReservedFor(username.clone()));
+
+        emit_username_unreserved(&env, &username);
+    }
+
+    /// Pause or resume new registrations (admin only).
+    ///
+    /// Existing profiles can still be updated while paused.
+    pub fn set_paused(env: Env, paused: bool, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(&env, &ProfileKey::Paused, "paused", Some(paused), &caller);
+
+        emit_paused_changed(&env, paused);
+    }
+
+    /// Check whether new registrations are paused.
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Set how many seconds must pass between display name changes
+    /// (admin only). 0 disables the cooldown, which is the default.
+    pub fn set_display_name_cooldown(env: Env, seconds: u64, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::DisplayNameCooldown,
+            "display_name_cooldown",
+            Some(seconds),
+            &caller,
+        );
+    }
+
+    /// Get the display name change cooldown in seconds.
+    pub fn display_name_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::DisplayNameCooldown)
+            .unwrap_or(0)
+    }
+
+    /// Delegate usernames starting with `prefix` to a registrar contract
+    /// (admin only).
+    ///
+    /// Such names can then only be registered with
+    /// `register_via_registrar`. Delegating an existing prefix again
+    /// replaces its registrar. Prefixes may not overlap, and at most
+    /// `MAX_NAMESPACES` may be delegated.
+    pub fn delegate_namespace(env: Env, prefix: Bytes, registrar: Address, caller: Address) {
+        let prefix = canonicalize_username(&prefix);
+        Self::require_admin(&env, &caller);
+
+        if prefix.is_empty() || prefix.len() > USERNAME_LENGTH_CEILING {
+            panic_with_error!(&env, ProfileError::InvalidConfig);
+        }
+
+        let mut namespaces = Self::namespaces(env.clone());
+        let mut existing = None;
+        for (i, (delegated, _)) in namespaces.iter().enumerate() {
+            if delegated == prefix {
+                existing = Some(i as u32);
+            } else if Self::starts_with(&delegated, &prefix)
+                || Self::starts_with(&prefix, &delegated)
+            {
+                panic_with_error!(&env, ProfileError::InvalidConfig);
+            }
+        }
+
+        match existing {
+            Some(i) => namespaces.set(i, (prefix, registrar)),
+            None if namespaces.len() >= MAX_NAMESPACES => {
+                panic_with_error!(&env, ProfileError::InvalidConfig)
+            }
+            None => namespaces.push_back((prefix, registrar)),
+        }
+        Self::set_config(
+            &env,
+            &ProfileKey::Namespaces,
+            "namespaces",
+            Some(namespaces),
+            &caller,
+        );
+    }
+
+    /// Return a delegated prefix to normal registration (admin only).
+    pub fn undelegate_namespace(env: Env, prefix: Bytes, caller: Address) {
+        let prefix = canonicalize_username(&prefix);
+        Self::require_admin(&env, &caller);
+
+        let mut namespaces = Self::namespaces(env.clone());
+        if let Some(i) = namespaces
+            .iter()
+            .position(|(delegated, _)| delegated == prefix)
+        {
+            namespaces.remove(i as u32);
+            let namespaces = (!namespaces.is_empty()).then_some(namespaces);
+            Self::set_config(
+                &env,
+                &ProfileKey::Namespaces,
+                "namespaces",
+                namespaces,
+                &caller,
+            );
+        }
+    }
+
+    /// Get the delegated prefixes and their registrars.
+    pub fn namespaces(env: Env) -> soroban_sdk::Vec<(Bytes, Address)> {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::Namespaces)
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env))
+    }
+
+    /// Get the registrar a username is delegated to, if any.
+    pub fn namespace_registrar(env: Env, username: Bytes) -> Option<Address> {
+        let username = canonicalize_username(&username);
+        Self::namespaces(env)
+            .iter()
+            .find(|(prefix, _)| Self::starts_with(&username, prefix))
+            .map(|(_, registrar)| registrar)
+    }
+
+    /// Reject registrations whose username looks like a registered one,
+    /// e.g. `a1ice001` when `alice001` exists (admin only). Off by
+    /// default. See `confusable_username` for the characters merged.
+    ///
+    /// Folded forms are recorded for every registration, so enabling this
+    /// also protects names registered while it was off.
+    pub fn set_block_confusables(env: Env, enabled: bool, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::BlockConfusables,
+            "block_confusables",
+            Some(enabled),
+            &caller,
+        );
+    }
+
+    /// Check whether look-alike usernames are rejected.
+    pub fn blocks_confusables(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::BlockConfusables)
+            .unwrap_or(false)
+    }
+
+    /// Only let invited addresses register (admin only). Off by default.
+    ///
+    /// Registrations through a namespace registrar are not gated; the
+    /// registrar decides who registers in its namespace.
+    pub fn set_invite_only(env: Env, enabled: bool, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::InviteOnly,
+            "invite_only",
+            Some(enabled),
+            &caller,
+        );
+    }
+
+    /// Check whether registration is invite-only.
+    pub fn invite_only(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::InviteOnly)
+            .unwrap_or(false)
+    }
+
+    /// Invite an address to register (admin only).
+    ///
+    /// The invite is consumed by the address's first registration.
+    pub fn invite(env: Env, address: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        let key = ProfileKey::InvitedAddress(address);
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Withdraw an unused invite (admin only).
+    pub fn revoke_invite(env: Env, address: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::InvitedAddress(address));
+    }
+
+    /// Check whether an address holds an unused invite.
+    pub fn is_invited(env: Env, address: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&ProfileKey::InvitedAddress(address))
+    }
+
+    /// Require new registrations to use a display name that differs from
+    /// the username (admin only). Off by default. Names claimed with
+    /// `claim_reserved` are exempt.
+    pub fn set_require_distinct_name(env: Env, required: bool, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::RequireDistinctDisplayName,
+            "require_distinct_name",
+            Some(required),
+            &caller,
+        );
+    }
+
+    /// Check whether registrations must use a display name that differs
+    /// from the username.
+    pub fn requires_distinct_name(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::RequireDistinctDisplayName)
+            .unwrap_or(false)
+    }
+
+    /// Set the registration fee (admin only).
+    pub fn set_registration_fee(env: Env, fee_stroops: i128, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::RegistrationFee,
+            "registration_fee",
+            Some(fee_stroops),
+            &caller,
+        );
+    }
+
+    /// Get the current registration fee.
+    pub fn registration_fee(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::RegistrationFee)
+            .unwrap_or(0)
+    }
+
+    /// Configure the global registration throttle (admin only).
+    ///
+    /// At most `max_per_window` registrations are accepted per
+    /// `window_seconds`. Once the limit is reached, further registrations
+    /// in the window are rejected with `ThrottleExceeded`. The
+    /// `surge_multiplier` is advisory: the contract does not collect fees,
+    /// so a non-zero multiplier only makes `current_registration_fee`
+    /// quote the fee times the multiplier while the window is full.
+    /// A `max_per_window` of 0 disables the throttle (the default).
+    pub fn set_registration_throttle(
+        env: Env,
+        max_per_window: u32,
+        window_seconds: u64,
+        surge_multiplier: u32,
+        caller: Address,
+    ) {
+        Self::require_admin(&env, &caller);
+
+        let key = ProfileKey::RegistrationThrottle;
+        if max_per_window == 0 {
+            Self::set_config::<(u32, u64, u32)>(&env, &key, "registration_throttle", None, &caller);
+            env.storage().instance().remove(&ProfileKey::ThrottleWindow);
+            return;
+        }
+
+        if window_seconds == 0 {
+            panic_with_error!(&env, ProfileError::InvalidConfig);
+        }
+
+        let throttle = (max_per_window, window_seconds, surge_multiplier);
+        Self::set_config(&env, &key, "registration_throttle", Some(throttle), &caller);
+    }
+
+    /// Get the (max_per_window, window_seconds, surge_multiplier)
+    /// throttle settings, if the throttle is enabled.
+    pub fn registration_throttle(env: Env) -> Option<(u32, u64, u32)> {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::RegistrationThrottle)
+    }
+
+    /// Get the number of registrations in the current throttle window.
+    ///
+    /// Returns 0 when the throttle is disabled or the window has ended.
+    pub fn registrations_in_window(env: Env) -> u32 {
+        Self::current_throttle_window(&env)
+            .map(|(_, count)| count)
+            .unwrap_or(0)
+    }
+
+    /// Get the registration fee currently quoted to new registrants,
+    /// including any throttle surge.
+    pub fn current_registration_fee(env: Env) -> i128 {
+        let fee = Self::registration_fee(env.clone());
+
+        match Self::registration_throttle(env.clone()) {
+            Some((max, _, surge)) if surge > 0 && Self::registrations_in_window(env) >= max => {
+                fee.saturating_mul(surge as i128)
+            }
+            _ => fee,
+        }
+    }
+
+    /// Set a field applied automatically to every new profile (admin only).
+    pub fn set_auto_field(env: Env, field: Symbol, value: FieldValue, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::AutoField,
+            "auto_field",
+            Some((field, value)),
+            &caller,
+        );
+    }
+
+    /// Stop applying a default field to new profiles (admin only).
+    pub fn clear_auto_field(env: Env, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config::<(Symbol, FieldValue)>(
+            &env,
+            &ProfileKey::AutoField,
+            "auto_field",
+            None,
+            &caller,
+        );
+    }
+
+    /// Get the field applied automatically to new profiles, if any.
+    pub fn auto_field(env: Env) -> Option<(Symbol, FieldValue)> {
+        env.storage().instance().get(&ProfileKey::AutoField)
+    }
+
+    /// Set the event topic prefix (admin only).
+    ///
+    /// When set, the prefix is emitted as the leading topic on every event,
+    /// e.g. `(prefix, "profile_updated")`. Pass `None` to remove it.
+    pub fn set_event_prefix(env: Env, prefix: Option<Symbol>, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::EventPrefix,
+            "event_prefix",
+            prefix,
+            &caller,
+        );
+    }
+
+    /// Get the configured event topic prefix, if any.
+    pub fn event_prefix(env: Env) -> Option<Symbol> {
+        env.storage().instance().get(&ProfileKey::EventPrefix)
+    }
+
+    /// Set how many leading and trailing characters rendered addresses
+    /// keep when truncated (admin only). Defaults to 4 and 4.
+    pub fn set_address_truncation(env: Env, prefix: u32, suffix: u32, caller: Address) {
+        Self::require_admin(&env, &caller);
+        let truncation = Some((prefix, suffix));
+        Self::set_config(
+            &env,
+            &ProfileKey::AddrTruncate,
+            "address_truncation",
+            truncation,
+            &caller,
+        );
+    }
+
+    /// Get the (prefix, suffix) lengths used to truncate rendered addresses.
+    pub fn address_truncation(env: Env) -> (u32, u32) {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::AddrTruncate)
+            .unwrap_or((
+                format::DEFAULT_TRUNCATE_PREFIX,
+                format::DEFAULT_TRUNCATE_SUFFIX,
+            ))
+    }
+
+    /// Approve a registration source for attribution (admin only).
+    pub fn add_source(env: Env, source: Symbol, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::ApprovedSource(source.clone()), &true);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::ApprovedSource(source),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+    }
+
+    /// Remove a registration source from the approved list (admin only).
+    ///
+    /// Existing attributions and counts are kept.
+    pub fn remove_source(env: Env, source: Symbol, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::ApprovedSource(source));
+    }
+
+    /// Set the embed policy for render components (admin only).
+    ///
+    /// `open` (the default) lets any contract embed full profile cards.
+    /// Under `allowlist`, embedders not approved with `allow_embedder`
+    /// get the anonymous rendering instead. Any other mode is rejected.
+    pub fn set_embed_policy(env: Env, mode: Symbol, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        let policy = if mode == Symbol::new(&env, EMBED_OPEN) {
+            None
+        } else if mode == Symbol::new(&env, EMBED_ALLOWLIST) {
+            Some(mode)
+        } else {
+            panic_with_error!(&env, ProfileError::InvalidConfig);
+        };
+        Self::set_config(
+            &env,
+            &ProfileKey::EmbedPolicy,
+            "embed_policy",
+            policy,
+            &caller,
+        );
+    }
+
+    /// Get the embed policy: `open` or `allowlist`.
+    pub fn embed_policy(env: Env) -> Symbol {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::EmbedPolicy)
+            .unwrap_or_else(|| Symbol::new(&env, EMBED_OPEN))
+    }
+
+    /// Approve a contract to embed full profile cards (admin only).
+    pub fn allow_embedder(env: Env, embedder: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::AllowedEmbedder(embedder.clone()), &true);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::AllowedEmbedder(embedder),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+    }
+
+    /// Withdraw a contract's approval to embed full profile cards
+    /// (admin only).
+    pub fn deny_embedder(env: Env, embedder: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::AllowedEmbedder(embedder));
+    }
+
+    /// Check whether an embedder may render full profile data.
+    ///
+    /// Always true in `open` mode. In `allowlist` mode, an unknown (`None`)
+    /// embedder is never allowed.
+    pub fn is_embedder_allowed(env: Env, embedder: Option<Address>) -> bool {
+        if !env.storage().instance().has(&ProfileKey::EmbedPolicy) {
+            return true;
+        }
+
+        match embedder {
+            Some(embedder) => env
+                .storage()
+                .persistent()
+                .has(&ProfileKey::AllowedEmbedder(embedder)),
+            None => false,
+        }
+    }
+
+    /// Set the username length bounds (admin only).
+    ///
+    /// Applies to new registrations and reservations; existing profiles
+    /// are unaffected. `min_len` must be at least `MIN_USERNAME_LENGTH`
+    /// and `max_len` between `min_len` and `USERNAME_LENGTH_CEILING`.
+    pub fn set_username_bounds(env: Env, min_len: u32, max_len: u32, caller: Address) {
+        Self::require_admin(&env, &caller);
+        Self::write_username_bounds(&env, min_len, max_len, &caller);
+    }
+
+    /// Get the current (min, max) username length bounds.
+    pub fn username_bounds(env: Env) -> (u32, u32) {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::UsernameBounds)
+            .unwrap_or((MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH))
+    }
+
+    /// Set an external username validator contract (admin only).
+    ///
+    /// The validator must expose `validate(username: Bytes) -> bool`.
+    /// With `replace_builtin` it decides alone; otherwise a name must pass
+    /// both it and the built-in rules. If the validator call fails, the
+    /// built-in rules are used so a broken validator can't block
+    /// registration. Pass `None` to remove the validator.
+    pub fn set_username_validator(
+        env: Env,
+        validator: Option<Address>,
+        replace_builtin: bool,
+        caller: Address,
+    ) {
+        Self::require_admin(&env, &caller);
+
+        let validator = validator.map(|validator| (validator, replace_builtin));
+        Self::set_config(
+            &env,
+            &ProfileKey::UsernameValidator,
+            "username_validator",
+            validator,
+            &caller,
+        );
+    }
+
+    /// Get the external username validator and whether it replaces the
+    /// built-in rules, if one is set.
+    pub fn username_validator(env: Env) -> Option<(Address, bool)> {
+        env.storage().instance().get(&ProfileKey::UsernameValidator)
+    }
+
+    /// Turn whitelist-only registration on or off (admin only).
+    ///
+    /// While on, a username is valid only if it was added with
+    /// `whitelist_username`; the pattern rules and any external validator
+    /// are bypassed. Existing profiles are unaffected.
+    pub fn set_whitelist_mode(env: Env, enabled: bool, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::WhitelistMode,
+            "whitelist_mode",
+            Some(enabled),
+            &caller,
+        );
+    }
+
+    /// Check whether whitelist-only registration is on.
+    pub fn is_whitelist_mode(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::WhitelistMode)
+            .unwrap_or(false)
+    }
+
+    /// Pre-approve an exact username for whitelist mode (admin only).
+    ///
+    /// The name must be 1 to `USERNAME_LENGTH_CEILING` bytes long.
+    pub fn whitelist_username(env: Env, username: Bytes, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        if username.is_empty() || username.len() > USERNAME_LENGTH_CEILING {
+            panic_with_error!(&env, ProfileError::InvalidUsername);
+        }
+
+        let key = ProfileKey::WhitelistedUsername(username);
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Withdraw a username's whitelist approval (admin only).
+    pub fn unwhitelist_username(env: Env, username: Bytes, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::WhitelistedUsername(username));
+    }
+
+    /// Check whether a username is on the whitelist.
+    pub fn is_whitelisted(env: Env, username: Bytes) -> bool {
+        let username = canonicalize_username(&username);
+        env.storage()
+            .persistent()
+            .has(&ProfileKey::WhitelistedUsername(username))
+    }
+
+    /// Set a contract to notify after each profile transfer (admin only).
+    ///
+    /// The hook must expose `on_transfer(username: Bytes, from: Address,
+    /// to: Address)`. Calls are best-effort: a failing hook does not revert
+    /// the transfer. Pass `None` to remove the hook.
+    pub fn set_transfer_hook(env: Env, hook: Option<Address>, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::TransferHook,
+            "transfer_hook",
+            hook,
+            &caller,
+        );
+    }
+
+    /// Get the transfer hook contract, if one is set.
+    pub fn transfer_hook(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ProfileKey::TransferHook)
+    }
+
+    /// Set the maximum field value length and field count per profile
+    /// (admin only).
+    ///
+    /// Applies to future writes; fields already stored are kept.
+    pub fn set_field_limits(env: Env, max_field_len: u32, max_fields: u32, caller: Address) {
+        Self::require_admin(&env, &caller);
+        Self::write_field_limits(&env, max_field_len, max_fields, &caller);
+    }
+
+    /// Get the current (max_field_len, max_fields) limits.
+    pub fn field_limits(env: Env) -> (u32, u32) {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::FieldLimits)
+            .unwrap_or((DEFAULT_MAX_FIELD_LENGTH, DEFAULT_MAX_FIELDS))
+    }
+
+    /// Set username bounds, field limits and the registration fee in one
+    /// call (admin only).
+    pub fn configure(
+        env: Env,
+        min_username: u32,
+        max_username: u32,
+        max_field_len: u32,
+        max_fields: u32,
+        fee_stroops: i128,
+        caller: Address,
+    ) {
+        Self::require_admin(&env, &caller);
+        Self::write_username_bounds(&env, min_username, max_username, &caller);
+        Self::write_field_limits(&env, max_field_len, max_fields, &caller);
+        Self::set_config(
+            &env,
+            &ProfileKey::RegistrationFee,
+            "registration_fee",
+            Some(fee_stroops),
+            &caller,
+        );
+    }
+
+    /// Get all configured limits in one call, for building forms.
+    ///
+    /// Returns (min_username, max_username, max_field_len, max_fields, fee).
+    pub fn limits(env: Env) -> (u32, u32, u32, u32, i128) {
+        let (min_username, max_username) = Self::username_bounds(env.clone());
+        let (max_field_len, max_fields) = Self::field_limits(env.clone());
+        let fee = Self::registration_fee(env);
+        (min_username, max_username, max_field_len, max_fields, fee)
+    }
+
+    /// Set the minimum profile age, in seconds, before a transfer is
+    /// allowed (admin only). Deters flipping freshly registered names.
+    ///
+    /// A value of 0 disables the check.
+    pub fn set_min_transfer_age(env: Env, seconds: u64, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        Self::set_config(
+            &env,
+            &ProfileKey::MinTransferAge,
+            "min_transfer_age",
+            Some(seconds),
+            &caller,
+        );
+    }
+
+    /// Get the minimum profile age, in seconds, before a transfer (0 = disabled).
+    pub fn min_transfer_age(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ProfileKey::MinTransferAge)
+            .unwrap_or(0)
+    }
+
+    /// Ban a profile (admin only).
+    ///
+    /// This soft-deletes the profile.
+    pub fn ban_profile(env: Env, address: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        let mut profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if profile.is_active() {
+            Self::adjust_count(&env, &ProfileKey::ActiveCount, -1);
+        }
+
+        profile.deleted = true;
+        profile.updated_at = env.ledger().timestamp();
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(address.clone()), &profile);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Banned(address.clone()), &true);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Banned(address.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        Self::bump_generation(&env, &address);
+
+        emit_profile_banned(&env, &address);
+    }
+
+    /// Check whether a profile was banned by the admin.
+    pub fn is_banned(env: Env, address: Address) -> bool {
+        env.storage().persistent().has(&ProfileKey::Banned(address))
+    }
+
+    /// Swap which address owns which profile (admin only).
+    ///
+    /// Intended for recovering from a mis-transfer. Both addresses must
+    /// have a profile. Only the profiles, their username mappings, their
+    /// display name cooldowns and their hidden, deactivated and banned
+    /// flags move; fields and other address-keyed data stay with each
+    /// address.
+    pub fn admin_swap_owners(env: Env, addr_a: Address, addr_b: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        let mut profile_a: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(addr_a.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+        let mut profile_b: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(addr_b.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        Self::unindex_display_name(&env, &addr_a, &profile_a.display_name);
+        Self::unindex_display_name(&env, &addr_b, &profile_b.display_name);
+        Self::index_display_name(&env, &addr_b, &profile_a.display_name);
+        Self::index_display_name(&env, &addr_a, &profile_b.display_name);
+
+        let timestamp = env.ledger().timestamp();
+        profile_a.owner = addr_b.clone();
+        profile_a.updated_at = timestamp;
+        profile_b.owner = addr_a.clone();
+        profile_b.updated_at = timestamp;
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(addr_b.clone()), &profile_a);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(addr_a.clone()), &profile_b);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Username(profile_a.username.clone()), &addr_b);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Username(profile_b.username.clone()), &addr_a);
+
+        Self::update_directory_owner(&env, &profile_a.username, &addr_b);
+        Self::update_directory_owner(&env, &profile_b.username, &addr_a);
+        Self::move_confusable_key(&env, &profile_a.username, &addr_a, Some(&addr_b));
+        Self::move_confusable_key(&env, &profile_b.username, &addr_b, Some(&addr_a));
+
+        let seq_a: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::CreatedAtSeq(addr_a.clone()));
+        let seq_b: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::CreatedAtSeq(addr_b.clone()));
+        Self::write_created_at_ledger(&env, &addr_b, seq_a);
+        Self::write_created_at_ledger(&env, &addr_a, seq_b);
+
+        let schema_a: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ProfileSchema(addr_a.clone()));
+        let schema_b: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ProfileSchema(addr_b.clone()));
+        Self::write_profile_schema(&env, &addr_b, schema_a);
+        Self::write_profile_schema(&env, &addr_a, schema_b);
+
+        let changed_a: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::DisplayNameChangedAt(addr_a.clone()));
+        let changed_b: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::DisplayNameChangedAt(addr_b.clone()));
+        Self::write_display_name_changed_at(&env, &addr_b, changed_a);
+        Self::write_display_name_changed_at(&env, &addr_a, changed_b);
+
+        // Both addresses now show a different profile
+        let generation = Self::generation(env.clone(), addr_a.clone())
+            .max(Self::generation(env.clone(), addr_b.clone()))
+            .saturating_add(1);
+        Self::write_generation(&env, &addr_a, generation);
+        Self::write_generation(&env, &addr_b, generation);
+
+        let aliases_a = Self::aliases(env.clone(), addr_a.clone());
+        let aliases_b = Self::aliases(env.clone(), addr_b.clone());
+        Self::write_aliases(&env, &addr_b, &aliases_a);
+        Self::write_aliases(&env, &addr_a, &aliases_b);
+
+        let former_a = Self::former_usernames(env.clone(), addr_a.clone());
+        let former_b = Self::former_usernames(env.clone(), addr_b.clone());
+        Self::write_former_usernames(&env, &addr_b, &former_a);
+        Self::write_former_usernames(&env, &addr_a, &former_b);
+
+        // Sanction and visibility flags follow the profiles
+        for (flag_a, flag_b) in [
+            (
+                ProfileKey::Hidden(addr_a.clone()),
+                ProfileKey::Hidden(addr_b.clone()),
+            ),
+            (
+                ProfileKey::Deactivated(addr_a.clone()),
+                ProfileKey::Deactivated(addr_b.clone()),
+            ),
+            (
+                ProfileKey::Banned(addr_a.clone()),
+                ProfileKey::Banned(addr_b.clone()),
+            ),
+        ] {
+            let set_a = env.storage().persistent().has(&flag_a);
+            let set_b = env.storage().persistent().has(&flag_b);
+            Self::write_flag(&env, &flag_b, set_a);
+            Self::write_flag(&env, &flag_a, set_b);
+        }
+
+        for key in [
+            ProfileKey::Username(profile_a.username),
+            ProfileKey::Username(profile_b.username),
+        ] {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        }
+        Self::extend_profile_ttl(&env, &addr_a);
+        Self::extend_profile_ttl(&env, &addr_b);
+
+        emit_owners_swapped(&env, &addr_a, &addr_b);
+    }
+
+    /// Change a profile's username (admin only).
+    ///
+    /// For fixing names that violate a later-added rule without deleting
+    /// the profile. The owner does not authorize this, so every rename
+    /// is recorded in the moderation log. The old name is reserved so it
+    /// cannot be immediately re-registered, and redirects to the new name
+    /// while it is one of the last `MAX_FORMER_USERNAMES` names.
+    pub fn admin_rename(env: Env, address: Address, new_username: Bytes, caller: Address) {
+        let new_username = canonicalize_username(&new_username);
+        Self::require_admin(&env, &caller);
+
+        let mut profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+
+        if profile.deleted {
+            panic_with_error!(&env, ProfileError::ProfileDeleted);
+        }
+
+        if !Self::username_is_valid(&env, &new_username) {
+            panic_with_error!(&env, ProfileError::InvalidUsername);
+        }
+
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Username(new_username.clone()))
+        {
+            panic_with_error!(&env, ProfileError::UsernameTaken);
+        }
+
+        if Self::is_reserved(&env, &new_username) {
+            panic_with_error!(&env, ProfileError::UsernameReserved);
+        }
+
+        Self::require_not_confusable(&env, &new_username, &address);
+
+        let old_username = profile.username.clone();
+
+        // The look-alike protection follows the name
+        Self::move_confusable_key(&env, &old_username, &address, None);
+        let confusable = ProfileKey::ConfusableKey(confusable_username(&env, &new_username));
+        if !env.storage().persistent().has(&confusable) {
+            env.storage().persistent().set(&confusable, &address);
+            env.storage().persistent().extend_ttl(
+                &confusable,
+                PROFILE_TTL_THRESHOLD,
+                PROFILE_TTL_EXTEND,
+            );
+        }
+
+        // Move the username mapping and update the profile
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Username(old_username.clone()));
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Username(new_username.clone()), &address);
+
+        profile.username = new_username.clone();
+        profile.updated_at = env.ledger().timestamp();
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(address.clone()), &profile);
+
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Username(new_username.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        Self::extend_profile_ttl(&env, &address);
+
+        Self::rename_in_indexes(&env, &old_username, &new_username, &address);
+
+        // Keep the old name from being sniped
+        Self::set_reserved(&env, &old_username);
+
+        // Keep links to the old name working
+        Self::add_former_username(&env, &address, &old_username, &new_username);
+
+        moderation::record(&env, "rename", &address, &caller, old_username.clone());
+        Self::bump_generation(&env, &address);
+
+        emit_username_changed(&env, &address, &old_username, &new_username, true);
+    }
+
+    /// Remove a field from a batch of profiles (admin only).
+    ///
+    /// For emergencies such as a field being used to spread malware
+    /// links. Frozen fields are cleared too, but stay frozen. At most
+    /// `MAX_BATCH_SIZE` addresses may be given at once; addresses without
+    /// the field are skipped. Each clear is recorded in the moderation log
+    /// with the field name.
+    pub fn admin_clear_field_for(
+        env: Env,
+        addresses: soroban_sdk::Vec<Address>,
+        field: Symbol,
+        caller: Address,
+    ) {
+        Self::require_admin(&env, &caller);
+
+        if addresses.len() > MAX_BATCH_SIZE {
+            panic_with_error!(&env, ProfileError::BatchTooLarge);
+        }
+
+        for address in addresses.iter() {
+            if !Self::clear_field(&env, &address, &field) {
+                continue;
+            }
+
+            moderation::record(
+                &env,
+                "clear_field",
+                &address,
+                &caller,
+                Self::symbol_bytes(&env, &field),
+            );
+            Self::bump_generation(&env, &address);
+
+            emit_field_cleared_admin(&env, &address, &field);
+        }
+    }
+
+    /// Keep a history of previous values for a field (admin only).
+    ///
+    /// Each time an audited field is overwritten or removed, its old value
+    /// is kept with the time it was replaced, up to `MAX_FIELD_HISTORY`
+    /// entries.
+    pub fn add_audited_field(env: Env, field: Symbol, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::AuditedField(field.clone()), &true);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::AuditedField(field),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+    }
+
+    /// Stop recording history for a field (admin only).
+    ///
+    /// History already recorded is kept.
+    pub fn remove_audited_field(env: Env, field: Symbol, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::AuditedField(field));
+    }
+
+    /// Check whether a field keeps a history of previous values.
+    pub fn is_audited_field(env: Env, field: Symbol) -> bool {
+        env.storage()
+            .persistent()
+            .has(&ProfileKey::AuditedField(field))
+    }
+
+    /// Get the previous values of an audited field, oldest first, each
+    /// with the timestamp at which it was replaced.
+    pub fn field_history(
+        env: Env,
+        address: Address,
+        field: Symbol,
+    ) -> soroban_sdk::Vec<(FieldValue, u64)> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::FieldHistory(address, field))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(&env))
+    }
+
+    /// Check whether a username mapping is orphaned (admin only).
+    ///
+    /// A mapping is orphaned when it points at an address that has no
+    /// profile at all. Deleted profiles still have one and are not orphans.
+    pub fn check_orphan(env: Env, username: Bytes, caller: Address) -> bool {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+        Self::orphan_owner(&env, &username).is_some()
+    }
+
+    /// Remove an orphaned username mapping, freeing the name (admin only).
+    ///
+    /// Does nothing if the mapping is missing or not orphaned. The repair
+    /// is recorded in the moderation log.
+    pub fn repair_orphan(env: Env, username: Bytes, caller: Address) {
+        let username = canonicalize_username(&username);
+        Self::require_admin(&env, &caller);
+
+        if let Some(owner) = Self::orphan_owner(&env, &username) {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Username(username.clone()));
+            moderation::record(&env, "orphan", &owner, &caller, username);
+        }
+    }
+
+    /// Get the number of entries in the moderation log.
+    pub fn mod_log_count(env: Env) -> u64 {
+        moderation::count(&env)
+    }
+
+    /// Get a moderation log entry by index (0-based).
+    pub fn mod_log_entry(env: Env, index: u64) -> Option<ModLogEntry> {
+        moderation::get(&env, index)
+    }
+
+    /// Attach an internal moderation note to a profile (admin only).
+    ///
+    /// Notes are capped at `MAX_ADMIN_NOTE_LENGTH` bytes. The change is
+    /// recorded in the moderation log without the note's text, since the
+    /// log is public.
+    pub fn set_admin_note(env: Env, address: Address, note: String, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Profile(address.clone()))
+        {
+            panic_with_error!(&env, ProfileError::ProfileNotFound);
+        }
+
+        if note.len() > MAX_ADMIN_NOTE_LENGTH {
+            panic_with_error!(&env, ProfileError::ValueTooLong);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::AdminNote(address.clone()), &note);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::AdminNote(address.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+
+        moderation::record(
+            &env,
+            "note",
+            &address,
+            &caller,
+            Bytes::from_slice(&env, b"set"),
+        );
+    }
+
+    /// Remove the moderation note from a profile (admin only).
+    pub fn clear_admin_note(env: Env, address: Address, caller: Address) {
+        Self::require_admin(&env, &caller);
+
+        let key = ProfileKey::AdminNote(address.clone());
+        if !env.storage().persistent().has(&key) {
+            return;
+        }
+        env.storage().persistent().remove(&key);
+
+        moderation::record(
+            &env,
+            "note",
+            &address,
+            &caller,
+            Bytes::from_slice(&env, b"clear"),
+        );
+    }
+
+    /// Read the moderation note for a profile (admin only).
+    ///
+    /// This keeps notes out of the public API. The underlying ledger
+    /// entry is still readable by anyone inspecting raw contract data.
+    pub fn admin_note(env: Env, address: Address, caller: Address) -> Option<String> {
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::AdminNote(address))
+    }
+
+    /// Upgrade the contract WASM (admin only).
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&ProfileKey::Admin)
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::NotInitialized));
+
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // ========== Internal Helpers ==========
+
+    /// Add `delta` to an instance counter, saturating at zero.
+    fn adjust_count(env: &Env, key: &ProfileKey, delta: i64) {
+        let count: u64 = env.storage().instance().get(key).unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(key, &count.saturating_add_signed(delta));
+    }
+
+    /// Add one to a persistent counter.
+    fn increment_count(env: &Env, key: &ProfileKey) {
+        let count: u64 = env.storage().persistent().get(key).unwrap_or(0);
+        env.storage().persistent().set(key, &(count + 1));
+        env.storage()
+            .persistent()
+            .extend_ttl(key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Permanently reserve a username, counting it if newly reserved.
+    fn set_reserved(env: &Env, username: &Bytes) {
+        let key = ProfileKey::ReservedUsername(username.clone());
+        if !env.storage().persistent().has(&key) {
+            Self::adjust_count(env, &ProfileKey::ReservedCount, 1);
+        }
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Remove a permanent reservation, uncounting it if present.
+    fn clear_reserved(env: &Env, username: &Bytes) {
+        let key = ProfileKey::ReservedUsername(username.clone());
+        if env.storage().persistent().has(&key) {
+            Self::adjust_count(env, &ProfileKey::ReservedCount, -1);
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Reject a display name identical to the username when distinct
+    /// names are required. Applies to names the registrant picks, not to
+    /// `claim_reserved`, which uses the username as the display name.
+    fn require_distinct_name(env: &Env, username: &Bytes, display_name: &String) {
+        if Self::requires_distinct_name(env.clone())
+            && soroban_render_sdk::bytes::string_to_bytes(env, display_name) == *username
+        {
+            panic_with_error!(env, ProfileError::InvalidDisplayName);
+        }
+    }
+
+    /// Remove a reservation along with its expiry and beneficiary.
+    fn lift_reservation(env: &Env, username: &Bytes) {
+        Self::clear_reserved(env, username);
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::ReservedUntil(username.clone()));
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::ReservedFor(username.clone()));
+    }
+
+    /// Register a profile for a caller that has already authorized.
+    ///
+    /// Names in a delegated namespace must go through their registrar,
+    /// and invite-only registration consumes the caller's invite. A name
+    /// reserved for the caller with `CLAIM_CONDITION_NONE` is claimed, as
+    /// `claim_reserved` would.
+    fn register_internal(env: &Env, username: Bytes, display_name: String, caller: &Address) {
+        if Self::namespace_registrar(env.clone(), username.clone()).is_some() {
+            panic_with_error!(env, ProfileError::NamespaceDelegated);
+        }
+
+        let reserved_for: Option<(Address, Symbol)> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ReservedFor(username.clone()));
+        let claimed = matches!(
+            reserved_for,
+            Some((beneficiary, condition))
+                if beneficiary == *caller && condition == Symbol::new(env, CLAIM_CONDITION_NONE)
+        );
+        if claimed {
+            Self::lift_reservation(env, &username);
+        }
+
+        let invite_only = Self::invite_only(env.clone());
+        if invite_only && !Self::is_invited(env.clone(), caller.clone()) {
+            panic_with_error!(env, ProfileError::NotInvited);
+        }
+
+        Self::create_profile(env, username.clone(), display_name, caller);
+
+        if invite_only {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::InvitedAddress(caller.clone()));
+        }
+
+        if claimed {
+            emit_username_claimed(env, &username, caller);
+        }
+    }
+
+    /// Create a profile once namespace rules have been applied.
+    fn create_profile(env: &Env, username: Bytes, display_name: String, caller: &Address) {
+        // Check contract is initialized
+        if !env.storage().instance().has(&ProfileKey::Admin) {
+            panic_with_error!(env, ProfileError::NotInitialized);
+        }
+
+        if Self::is_paused(env.clone()) {
+            panic_with_error!(env, ProfileError::RegistrationPaused);
+        }
+
+        Self::count_throttled_registration(env);
+        Self::count_daily_registration(env);
+
+        // Validate username format
+        if !Self::username_is_valid(env, &username) {
+            panic_with_error!(env, ProfileError::InvalidUsername);
+        }
+
+        // Check username is not taken
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Username(username.clone()))
+        {
+            panic_with_error!(env, ProfileError::UsernameTaken);
+        }
+
+        // Check username is not reserved
+        if Self::is_reserved(env, &username) {
+            panic_with_error!(env, ProfileError::UsernameReserved);
+        }
+
+        // Check the username does not look like a registered one
+        let confusable = ProfileKey::ConfusableKey(confusable_username(env, &username));
+        let lookalike_owner: Option<Address> = env.storage().persistent().get(&confusable);
+        if Self::blocks_confusables(env.clone()) && lookalike_owner.is_some() {
+            panic_with_error!(env, ProfileError::UsernameConfusable);
+        }
+
+        // Check caller doesn't already have a profile
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Profile(caller.clone()))
+        {
+            panic_with_error!(env, ProfileError::ProfileExists);
+        }
+
+        // A banned address cannot purge its profile and start over
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(env, ProfileError::ProfileBanned);
+        }
+
+        // Create profile
+        let timestamp = env.ledger().timestamp();
+        let profile = Profile::new(username.clone(), display_name, caller.clone(), timestamp);
+
+        // Store username -> address mapping
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Username(username.clone()), caller);
+
+        // Store profile
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(caller.clone()), &profile);
+        Self::write_created_at_ledger(env, caller, Some(env.ledger().sequence()));
+        Self::write_profile_schema(env, caller, Some(PROFILE_SCHEMA_VERSION));
+        Self::index_display_name(env, caller, &profile.display_name);
+
+        // The first registrant of a folded form keeps it
+        if lookalike_owner.is_none() {
+            env.storage().persistent().set(&confusable, caller);
+            env.storage().persistent().extend_ttl(
+                &confusable,
+                PROFILE_TTL_THRESHOLD,
+                PROFILE_TTL_EXTEND,
+            );
+        }
+
+        // Extend TTL
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Username(username.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        Self::extend_profile_ttl(env, caller);
+
+        // Increment profile count, using the old count as the index slot
+        let count: u64 = env
+            .storage()
+            .instance()
+            .get(&ProfileKey::ProfileCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ProfileKey::ProfileCount, &(count + 1));
+        Self::adjust_count(env, &ProfileKey::ActiveCount, 1);
+
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::ProfileIndex(count), &username);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::IndexSlot(username.clone()), &count);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::ProfileIndex(count),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::IndexSlot(username.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+
+        // Add to the first-letter directory
+        Self::add_to_directory(env, &username, caller);
+
+        // Apply the configured default field, if any
+        let auto_field: Option<(Symbol, FieldValue)> =
+            env.storage().instance().get(&ProfileKey::AutoField);
+        if let Some((field, value)) = auto_field {
+            Self::write_field(env, caller, &field, &value);
+        }
+
+        // Emit event
+        emit_profile_registered(env, caller, &username);
+    }
+
+    fn require_admin(env: &Env, caller: &Address) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&ProfileKey::Admin)
+            .unwrap_or_else(|| panic_with_error!(env, ProfileError::NotInitialized));
+
+        if *caller != admin {
+            panic_with_error!(env, ProfileError::NotAuthorized);
+        }
+
+        caller.require_auth();
+    }
+
+    /// Load the caller's profile, panicking unless it exists, is active,
+    /// and is owned by the caller.
+    fn require_owned_profile(env: &Env, caller: &Address) -> Profile {
+        let profile: Profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(env, ProfileError::ProfileNotFound));
+
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(env, ProfileError::ProfileBanned);
+        }
+
+        if profile.deleted {
+            panic_with_error!(env, ProfileError::ProfileDeleted);
+        }
+
+        if profile.owner != *caller {
+            panic_with_error!(env, ProfileError::NotAuthorized);
+        }
+
+        profile
+    }
+
+    /// Add a registration to today's bucket.
+    fn count_daily_registration(env: &Env) {
+        let key = ProfileKey::DailyRegistrations((env.ledger().timestamp() / 86_400) as u32);
+        let count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(count + 1));
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// The (window_start, registrations) of the throttle window that is
+    /// still open, if the throttle is enabled.
+    fn current_throttle_window(env: &Env) -> Option<(u64, u32)> {
+        let (_, window_seconds, _): (u32, u64, u32) = env
+            .storage()
+            .instance()
+            .get(&ProfileKey::RegistrationThrottle)?;
+        let (start, count): (u64, u32) =
+            env.storage().instance().get(&ProfileKey::ThrottleWindow)?;
+
+        if env.ledger().timestamp() < start.saturating_add(window_seconds) {
+            Some((start, count))
+        } else {
+            None
+        }
+    }
+
+    /// Count a registration against the throttle, panicking with
+    /// `ThrottleExceeded` if the window is full.
+    fn count_throttled_registration(env: &Env) {
+        let (max, _, _) = match Self::registration_throttle(env.clone()) {
+            Some(throttle) => throttle,
+            None => return,
+        };
+
+        // A new window starts with the first registration after the last one ends
+        let (start, count) =
+            Self::current_throttle_window(env).unwrap_or((env.ledger().timestamp(), 0));
+
+        if count >= max {
+            panic_with_error!(env, ProfileError::ThrottleExceeded);
+        }
+
+        env.storage().instance().set(
+            &ProfileKey::ThrottleWindow,
+            &(start, count.saturating_add(1)),
+        );
+    }
+
+    /// Extend the TTL of the profile entry at `address`, recording the
+    /// ledger whenever the extension takes effect.
+    ///
+    /// `extend_ttl` only extends entries below the threshold, so the
+    /// record is updated under the same condition.
+    fn extend_profile_ttl(env: &Env, address: &Address) {
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Profile(address.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+
+        let key = ProfileKey::TtlExtendedAt(address.clone());
+        let remaining = Self::profile_ttl_remaining(env.clone(), address.clone());
+        // Spelled out rather than `is_none_or`, which needs Rust 1.82
+        if !matches!(remaining, Some(ledgers) if ledgers >= PROFILE_TTL_THRESHOLD) {
+            env.storage()
+                .persistent()
+                .set(&key, &env.ledger().sequence());
+        }
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Record or clear the registration ledger of the profile at `address`.
+    fn write_created_at_ledger(env: &Env, address: &Address, sequence: Option<u32>) {
+        let key = ProfileKey::CreatedAtSeq(address.clone());
+        match sequence {
+            Some(sequence) => {
+                env.storage().persistent().set(&key, &sequence);
+                env.storage().persistent().extend_ttl(
+                    &key,
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    fn write_display_name_changed_at(env: &Env, address: &Address, timestamp: Option<u64>) {
+        let key = ProfileKey::DisplayNameChangedAt(address.clone());
+        match timestamp {
+            Some(timestamp) => {
+                env.storage().persistent().set(&key, &timestamp);
+                env.storage().persistent().extend_ttl(
+                    &key,
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    fn write_profile_schema(env: &Env, address: &Address, schema: Option<u32>) {
+        let key = ProfileKey::ProfileSchema(address.clone());
+        match schema {
+            Some(schema) => {
+                env.storage().persistent().set(&key, &schema);
+                env.storage().persistent().extend_ttl(
+                    &key,
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get the address a username or former username belongs to.
+    fn resolve_username(env: &Env, username: &Bytes) -> Option<Address> {
+        let address: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Username(username.clone()));
+        if address.is_some() {
+            return address;
+        }
+
+        let current: Bytes = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::UsernameAlias(username.clone()))?;
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Username(current))
+    }
+
+    /// Record `old` as a former username of `owner` and point all of its
+    /// former usernames at `new`. Beyond `MAX_FORMER_USERNAMES`, the
+    /// oldest stops redirecting.
+    fn add_former_username(env: &Env, owner: &Address, old: &Bytes, new: &Bytes) {
+        let mut former = Self::former_usernames(env.clone(), owner.clone());
+        former.push_back(old.clone());
+        while former.len() > MAX_FORMER_USERNAMES {
+            if let Some(expired) = former.pop_front() {
+                env.storage()
+                    .persistent()
+                    .remove(&ProfileKey::UsernameAlias(expired));
+            }
+        }
+
+        for name in former.iter() {
+            let key = ProfileKey::UsernameAlias(name);
+            env.storage().persistent().set(&key, new);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        }
+        Self::write_former_usernames(env, owner, &former);
+    }
+
+    /// Store `links` as the links of `owner`. An empty list removes the
+    /// entry.
+    fn write_links(env: &Env, owner: &Address, links: &soroban_sdk::Vec<(String, String)>) {
+        let key = ProfileKey::Links(owner.clone());
+        if links.is_empty() {
+            env.storage().persistent().remove(&key);
+            return;
+        }
+
+        env.storage().persistent().set(&key, links);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Store `former` as the former usernames of `owner`. An empty list
+    /// removes the entry.
+    fn write_former_usernames(env: &Env, owner: &Address, former: &soroban_sdk::Vec<Bytes>) {
+        let key = ProfileKey::FormerUsernames(owner.clone());
+        if former.is_empty() {
+            env.storage().persistent().remove(&key);
+            return;
+        }
+
+        env.storage().persistent().set(&key, former);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Store `aliases` as the aliases of `owner` and point each alias
+    /// mapping at `owner`. An empty list removes the entry.
+    fn write_aliases(env: &Env, owner: &Address, aliases: &soroban_sdk::Vec<Bytes>) {
+        if aliases.is_empty() {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Aliases(owner.clone()));
+            return;
+        }
+
+        for alias in aliases.iter() {
+            env.storage()
+                .persistent()
+                .set(&ProfileKey::Username(alias.clone()), owner);
+            env.storage().persistent().extend_ttl(
+                &ProfileKey::Username(alias),
+                PROFILE_TTL_THRESHOLD,
+                PROFILE_TTL_EXTEND,
+            );
+        }
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Aliases(owner.clone()), aliases);
+        env.storage().persistent().extend_ttl(
+            &ProfileKey::Aliases(owner.clone()),
+            PROFILE_TTL_THRESHOLD,
+            PROFILE_TTL_EXTEND,
+        );
+    }
+
+    fn require_not_frozen(env: &Env, address: &Address, field: &Symbol) {
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::FrozenField(address.clone(), field.clone()))
+        {
+            panic_with_error!(env, ProfileError::FieldFrozen);
+        }
+    }
+
+    fn unhide(env: &Env, address: &Address) {
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Hidden(address.clone()))
+        {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::Hidden(address.clone()));
+            Self::bump_generation(env, address);
+
+            emit_profile_unhidden(env, address);
+        }
+    }
+
+    fn write_username_bounds(env: &Env, min_len: u32, max_len: u32, caller: &Address) {
+        if min_len < MIN_USERNAME_LENGTH || max_len < min_len || max_len > USERNAME_LENGTH_CEILING {
+            panic_with_error!(env, ProfileError::InvalidConfig);
+        }
+
+        let bounds = Some((min_len, max_len));
+        Self::set_config(
+            env,
+            &ProfileKey::UsernameBounds,
+            "username_bounds",
+            bounds,
+            caller,
+        );
+    }
+
+    fn write_field_limits(env: &Env, max_field_len: u32, max_fields: u32, caller: &Address) {
+        if max_field_len == 0 || max_fields == 0 {
+            panic_with_error!(env, ProfileError::InvalidConfig);
+        }
+
+        let limits = Some((max_field_len, max_fields));
+        Self::set_config(
+            env,
+            &ProfileKey::FieldLimits,
+            "field_limits",
+            limits,
+            caller,
+        );
+    }
+
+    /// Store an instance config setting, or clear it with `None`, and
+    /// emit `admin_config_changed` with the value it replaced.
+    ///
+    /// Every admin config setter writes through here so governance
+    /// tooling sees each change.
+    fn set_config<V>(env: &Env, key: &ProfileKey, setting: &str, value: Option<V>, caller: &Address)
+    where
+        V: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    {
+        let old: Option<V> = env.storage().instance().get(key);
+        match &value {
+            Some(value) => env.storage().instance().set(key, value),
+            None => env.storage().instance().remove(key),
+        }
+        emit_admin_config_changed(env, setting, old, value, caller);
+    }
+
+    /// Address a username maps to, if that address has no profile.
+    fn orphan_owner(env: &Env, username: &Bytes) -> Option<Address> {
+        let owner: Address = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Username(username.clone()))?;
+        let has_profile = env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::Profile(owner.clone()));
+        (!has_profile).then_some(owner)
+    }
+
+    fn display_name_key(env: &Env, name: &String) -> Bytes {
+        normalize_display_name(env, &soroban_render_sdk::bytes::string_to_bytes(env, name))
+    }
+
+    /// Addresses indexed under the normalized form of a display name.
+    fn display_name_matches(env: &Env, name: &String) -> soroban_sdk::Vec<Address> {
+        let key = Self::display_name_key(env, name);
+        if key.is_empty() {
+            return soroban_sdk::Vec::new(env);
+        }
+
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::DisplayNameIndex(key))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(env))
+    }
+
+    fn index_display_name(env: &Env, address: &Address, name: &String) {
+        let key = Self::display_name_key(env, name);
+        if key.is_empty() {
+            return;
+        }
+
+        let key = ProfileKey::DisplayNameIndex(key);
+        let mut addresses: soroban_sdk::Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| soroban_sdk::Vec::new(env));
+        if addresses.contains(address) || addresses.len() >= MAX_DISPLAY_NAME_INDEX {
+            return;
+        }
+
+        addresses.push_back(address.clone());
+        env.storage().persistent().set(&key, &addresses);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    fn unindex_display_name(env: &Env, address: &Address, name: &String) {
+        let key = Self::display_name_key(env, name);
+        let key = ProfileKey::DisplayNameIndex(key);
+        let stored: Option<soroban_sdk::Vec<Address>> = env.storage().persistent().get(&key);
+        let mut addresses = match stored {
+            Some(addresses) => addresses,
+            None => return,
+        };
+
+        if let Some(i) = addresses.first_index_of(address) {
+            addresses.remove(i);
+            if addresses.is_empty() {
+                env.storage().persistent().remove(&key);
+            } else {
+                env.storage().persistent().set(&key, &addresses);
+            }
+        }
+    }
+
+    /// Deactivation only hides a profile, so deactivated profiles stay
+    /// eligible and each address costs a single read.
+    fn is_eligible(env: &Env, address: Address, registered_before: u64) -> bool {
+        let profile: Option<Profile> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address));
+        match profile.filter(|p| p.is_active()) {
+            Some(p) => {
+                p.created_at >= LEGACY_TIMESTAMP_THRESHOLD && p.created_at < registered_before
+            }
+            None => false,
+        }
+    }
+
+    fn notify_transfer_hook(env: &Env, username: &Bytes, from: &Address, to: &Address) {
+        let hook = match Self::transfer_hook(env.clone()) {
+            Some(hook) => hook,
+            None => return,
+        };
+
+        let args: soroban_sdk::Vec<Val> = vec![
+            env,
+            username.into_val(env),
+            from.into_val(env),
+            to.into_val(env),
+        ];
+        // Best-effort: the hook's failure is ignored
+        let _ = env.try_invoke_contract::<(), soroban_sdk::Error>(
+            &hook,
+            &Symbol::new(env, "on_transfer"),
+            args,
+        );
+    }
+
+    fn username_is_valid(env: &Env, username: &Bytes) -> bool {
+        if Self::is_whitelist_mode(env.clone()) {
+            return Self::is_whitelisted(env.clone(), username.clone());
+        }
+
+        let (min_len, max_len) = Self::username_bounds(env.clone());
+        let builtin = || validation::validate_username_with_bounds(username, min_len, max_len);
+
+        let (validator, replace_builtin) = match Self::username_validator(env.clone()) {
+            Some(config) => config,
+            None => return builtin(),
+        };
+
+        let args: soroban_sdk::Vec<Val> = vec![env, username.into_val(env)];
+        let external = env.try_invoke_contract::<bool, soroban_sdk::Error>(
+            &validator,
+            &Symbol::new(env, "validate"),
+            args,
+        );
+
+        match external {
+            Ok(Ok(valid)) if replace_builtin => valid,
+            Ok(Ok(valid)) => valid && builtin(),
+            // The validator failed or returned garbage; fall back
+            _ => builtin(),
+        }
+    }
+
+    /// Names of the fields indexed for an address. Profiles whose fields
+    /// predate the index may hold fields not listed here.
+    fn field_names(env: &Env, address: &Address) -> soroban_sdk::Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::FieldIndex(address.clone()))
+            .unwrap_or_else(|| soroban_sdk::Vec::new(env))
+    }
+
+    fn set_field_names(env: &Env, address: &Address, names: &soroban_sdk::Vec<Symbol>) {
+        let key = ProfileKey::FieldIndex(address.clone());
+        env.storage().persistent().set(&key, names);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    /// Binary-search `[lo, hi)` for the first index created at or after `ts`.
+    fn first_index_created_at_or_after(env: &Env, mut lo: u64, mut hi: u64, ts: u64) -> u64 {
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if Self::index_created_at(env, mid) < ts {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Creation time of the profile in an index slot, read from the
+    /// tombstone if the slot was purged.
+    fn index_created_at(env: &Env, index: u64) -> u64 {
+        let username: Option<Bytes> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ProfileIndex(index));
+        if username.is_none() {
+            return env
+                .storage()
+                .persistent()
+                .get(&ProfileKey::PurgedIndex(index))
+                .unwrap_or(0);
+        }
+
+        let owner: Option<Address> = username.and_then(|username| {
+            env.storage()
+                .persistent()
+                .get(&ProfileKey::Username(username))
+        });
+        let profile: Option<Profile> =
+            owner.and_then(|owner| env.storage().persistent().get(&ProfileKey::Profile(owner)));
+
+        profile.map(|p| p.created_at).unwrap_or(0)
+    }
+
+    fn bump_if_present(env: &Env, key: &ProfileKey) {
+        if env.storage().persistent().has(key) {
+            env.storage()
+                .persistent()
+                .extend_ttl(key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
         }
+    }
 
-        if profile.owner != caller {
-            panic_with_error!(&env, ProfileError::NotAuthorized);
+    fn is_reserved(env: &Env, username: &Bytes) -> bool {
+        if env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::ReservedUsername(username.clone()))
+        {
+            return true;
         }
 
-        // Check new owner doesn't already have a profile
+        // A former username is held while it still redirects
         if env
             .storage()
             .persistent()
-            .has(&ProfileKey::Profile(new_owner.clone()))
+            .has(&ProfileKey::UsernameAlias(username.clone()))
         {
-            panic_with_error!(&env, ProfileError::ProfileExists);
+            return true;
         }
 
-        let username = profile.username.clone();
+        let expiry: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::ReservedUntil(username.clone()));
 
-        // Update profile ownership
-        profile.owner = new_owner.clone();
-        profile.updated_at = env.ledger().timestamp();
+        matches!(expiry, Some(expiry) if env.ledger().timestamp() < expiry)
+    }
 
-        // Update username mapping
-        env.storage()
+    fn add_to_directory(env: &Env, username: &Bytes, owner: &Address) {
+        let letter = username.get(0).unwrap_or(0) as u32;
+        let slot: u32 = env
+            .storage()
             .persistent()
-            .set(&ProfileKey::Username(username.clone()), &new_owner);
+            .get(&ProfileKey::DirectoryCount(letter))
+            .unwrap_or(0);
 
-        // Remove old profile entry
-        env.storage()
+        let page_key = ProfileKey::DirectoryPage(letter, slot / DIRECTORY_PAGE_SIZE);
+        let mut page: soroban_sdk::Vec<Address> = env
+            .storage()
             .persistent()
-            .remove(&ProfileKey::Profile(caller.clone()));
+            .get(&page_key)
+            .unwrap_or_else(|| soroban_sdk::Vec::new(env));
+        page.push_back(owner.clone());
 
-        // Set new profile entry
+        env.storage().persistent().set(&page_key, &page);
         env.storage()
             .persistent()
-            .set(&ProfileKey::Profile(new_owner.clone()), &profile);
-
-        // Extend TTL
-        env.storage().persistent().extend_ttl(
-            &ProfileKey::Username(username.clone()),
-            PROFILE_TTL_THRESHOLD,
-            PROFILE_TTL_EXTEND,
-        );
-        env.storage().persistent().extend_ttl(
-            &ProfileKey::Profile(new_owner.clone()),
-            PROFILE_TTL_THRESHOLD,
-            PROFILE_TTL_EXTEND,
-        );
+            .set(&ProfileKey::DirectorySlot(username.clone()), &slot);
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::DirectoryCount(letter), &(slot + 1));
 
-        emit_username_transferred(&env, &username, &caller, &new_owner);
+        for key in [
+            page_key,
+            ProfileKey::DirectorySlot(username.clone()),
+            ProfileKey::DirectoryCount(letter),
+        ] {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        }
     }
 
-    // ========== Admin Functions ==========
+    /// Point the registration index and directory at a renamed username.
+    fn rename_in_indexes(env: &Env, old: &Bytes, new: &Bytes, owner: &Address) {
+        let index: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::IndexSlot(old.clone()));
+        if let Some(index) = index {
+            env.storage()
+                .persistent()
+                .remove(&ProfileKey::IndexSlot(old.clone()));
+            env.storage()
+                .persistent()
+                .set(&ProfileKey::IndexSlot(new.clone()), &index);
+            env.storage()
+                .persistent()
+                .set(&ProfileKey::ProfileIndex(index), new);
+            for key in [
+                ProfileKey::IndexSlot(new.clone()),
+                ProfileKey::ProfileIndex(index),
+            ] {
+                env.storage().persistent().extend_ttl(
+                    &key,
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+        }
 
-    /// Reserve a username (admin only).
-    pub fn reserve_username(env: Env, username: Bytes, caller: Address) {
-        Self::require_admin(&env, &caller);
+        let slot: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::DirectorySlot(old.clone()));
 
-        if !validation::validate_username(&username) {
-            panic_with_error!(&env, ProfileError::InvalidUsername);
+        // Same bucket: keep the slot. Otherwise blank the entry in the old
+        // bucket so a later rename back does not list the owner twice.
+        match slot {
+            Some(slot) if old.get(0) == new.get(0) => {
+                env.storage()
+                    .persistent()
+                    .remove(&ProfileKey::DirectorySlot(old.clone()));
+                env.storage()
+                    .persistent()
+                    .set(&ProfileKey::DirectorySlot(new.clone()), &slot);
+                env.storage().persistent().extend_ttl(
+                    &ProfileKey::DirectorySlot(new.clone()),
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+            _ => {
+                Self::remove_from_directory(env, old);
+                Self::add_to_directory(env, new, owner);
+            }
         }
+    }
 
-        env.storage()
-            .persistent()
-            .set(&ProfileKey::ReservedUsername(username.clone()), &true);
+    /// Reject `username` while look-alikes are blocked and its folded form
+    /// is held by an address other than `owner`.
+    fn require_not_confusable(env: &Env, username: &Bytes, owner: &Address) {
+        if !Self::blocks_confusables(env.clone()) {
+            return;
+        }
 
-        emit_username_reserved(&env, &username);
+        let key = ProfileKey::ConfusableKey(confusable_username(env, username));
+        let holder: Option<Address> = env.storage().persistent().get(&key);
+        if matches!(holder, Some(holder) if holder != *owner) {
+            panic_with_error!(env, ProfileError::UsernameConfusable);
+        }
     }
 
-    /// Release a reserved username (admin only).
-    pub fn unreserve_username(env: Env, username: Bytes, caller: Address) {
-        Self::require_admin(&env, &caller);
+    /// Point a username's confusable key at a new owner, or remove it, if
+    /// it belongs to `from`. Keys held by an earlier look-alike are left
+    /// alone.
+    fn move_confusable_key(env: &Env, username: &Bytes, from: &Address, to: Option<&Address>) {
+        let key = ProfileKey::ConfusableKey(confusable_username(env, username));
+        let owner: Option<Address> = env.storage().persistent().get(&key);
+        if owner.as_ref() != Some(from) {
+            return;
+        }
 
-        env.storage()
-            .persistent()
-            .remove(&ProfileKey::ReservedUsername(username.clone()));
+        match to {
+            Some(to) => {
+                env.storage().persistent().set(&key, to);
+                env.storage().persistent().extend_ttl(
+                    &key,
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
 
-        emit_username_unreserved(&env, &username);
+    fn starts_with(bytes: &Bytes, prefix: &Bytes) -> bool {
+        bytes.len() >= prefix.len() && bytes.slice(0..prefix.len()) == *prefix
     }
 
-    /// Set the registration fee (admin only).
-    pub fn set_registration_fee(env: Env, fee_stroops: i128, caller: Address) {
-        Self::require_admin(&env, &caller);
+    fn update_directory_owner(env: &Env, username: &Bytes, owner: &Address) {
+        let slot: Option<u32> = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::DirectorySlot(username.clone()));
 
-        env.storage()
-            .instance()
-            .set(&ProfileKey::RegistrationFee, &fee_stroops);
+        if let Some(slot) = slot {
+            let letter = username.get(0).unwrap_or(0) as u32;
+            let page_key = ProfileKey::DirectoryPage(letter, slot / DIRECTORY_PAGE_SIZE);
+            let page: Option<soroban_sdk::Vec<Address>> = env.storage().persistent().get(&page_key);
+
+            if let Some(mut page) = page {
+                page.set(slot % DIRECTORY_PAGE_SIZE, owner.clone());
+                env.storage().persistent().set(&page_key, &page);
+                env.storage().persistent().extend_ttl(
+                    &page_key,
+                    PROFILE_TTL_THRESHOLD,
+                    PROFILE_TTL_EXTEND,
+                );
+            }
+        }
     }
 
-    /// Get the current registration fee.
-    pub fn registration_fee(env: Env) -> i128 {
+    /// Blank a username's directory entry and forget its slot.
+    ///
+    /// Slots are positional, so the entry is overwritten with the
+    /// contract's own address rather than removed. It never holds a
+    /// profile, so the directory page skips it.
+    fn remove_from_directory(env: &Env, username: &Bytes) {
+        Self::update_directory_owner(env, username, &env.current_contract_address());
         env.storage()
-            .instance()
-            .get(&ProfileKey::RegistrationFee)
-            .unwrap_or(0)
+            .persistent()
+            .remove(&ProfileKey::DirectorySlot(username.clone()));
     }
 
-    /// Ban a profile (admin only).
-    ///
-    /// This soft-deletes the profile.
-    pub fn ban_profile(env: Env, address: Address, caller: Address) {
-        Self::require_admin(&env, &caller);
+    fn set_field_internal(env: &Env, caller: &Address, field: Symbol, value: FieldValue) {
+        caller.require_auth();
+        if Self::apply_field(env, caller, field.clone(), value) {
+            let generation = Self::bump_generation(env, caller);
+            emit_profile_updated(env, caller, &field, generation);
+        }
+    }
 
-        let mut profile: Profile = env
+    /// Validate and write a field for an already-authorized caller.
+    ///
+    /// Returns false if the field already held the value. Callers bump
+    /// the generation and emit `profile_updated` for changed fields.
+    fn apply_field(env: &Env, caller: &Address, field: Symbol, value: FieldValue) -> bool {
+        // Verify profile exists and is active
+        let profile: Profile = env
             .storage()
             .persistent()
-            .get(&ProfileKey::Profile(address.clone()))
-            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::ProfileNotFound));
+            .get(&ProfileKey::Profile(caller.clone()))
+            .unwrap_or_else(|| panic_with_error!(env, ProfileError::ProfileNotFound));
 
-        profile.deleted = true;
-        profile.updated_at = env.ledger().timestamp();
+        if Self::is_banned(env.clone(), caller.clone()) {
+            panic_with_error!(env, ProfileError::ProfileBanned);
+        }
 
-        env.storage()
+        if profile.deleted {
+            panic_with_error!(env, ProfileError::ProfileDeleted);
+        }
+
+        if profile.owner != *caller {
+            panic_with_error!(env, ProfileError::NotAuthorized);
+        }
+
+        Self::require_not_frozen(env, caller, &field);
+
+        let (max_field_len, max_fields) = Self::field_limits(env.clone());
+        let value_len = match &value {
+            FieldValue::StringField(s) => s.len(),
+            FieldValue::BytesField(b) => b.len(),
+            _ => 0,
+        };
+        if value_len > max_field_len {
+            panic_with_error!(env, ProfileError::ValueTooLong);
+        }
+
+        if !env
+            .storage()
             .persistent()
-            .set(&ProfileKey::Profile(address.clone()), &profile);
+            .has(&ProfileKey::Field(caller.clone(), field.clone()))
+            && Self::field_names(env, caller).len() >= max_fields
+        {
+            panic_with_error!(env, ProfileError::TooManyFields);
+        }
 
-        emit_profile_banned(&env, &address);
+        if let FieldValue::BytesField(bytes) = &value {
+            if field == Symbol::new(env, standard_fields::AVATAR) && bytes.len() > MAX_AVATAR_BYTES
+            {
+                panic_with_error!(env, ProfileError::ValueTooLong);
+            }
+        }
+
+        if field == Symbol::new(env, standard_fields::CONTACT) {
+            match &value {
+                FieldValue::StringField(text) if text.len() > MAX_CONTACT_LENGTH => {
+                    panic_with_error!(env, ProfileError::ValueTooLong);
+                }
+                FieldValue::StringField(_) => {}
+                _ => panic_with_error!(env, ProfileError::InvalidFieldValue),
+            }
+        }
+
+        if field == Symbol::new(env, standard_fields::DWEB) {
+            let valid = match &value {
+                FieldValue::StringField(url) => {
+                    validate_dweb_url(&soroban_render_sdk::bytes::string_to_bytes(env, url))
+                }
+                _ => false,
+            };
+            if !valid {
+                panic_with_error!(env, ProfileError::InvalidFieldValue);
+            }
+        }
+
+        let key = ProfileKey::Field(caller.clone(), field.clone());
+        let previous: Option<FieldValue> = env.storage().persistent().get(&key);
+        if previous.as_ref() == Some(&value) {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+            Self::extend_profile_ttl(env, caller);
+            return false;
+        }
+
+        if let Some(previous) = previous {
+            if Self::is_audited_field(env.clone(), field.clone()) {
+                Self::record_field_history(env, caller, &field, previous);
+            }
+        }
+
+        Self::write_field(env, caller, &field, &value);
+        true
     }
 
-    /// Upgrade the contract WASM (admin only).
-    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&ProfileKey::Admin)
-            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::NotInitialized));
+    /// Remove a field and drop it from the field index, keeping the removed
+    /// value in its history if the field is audited.
+    ///
+    /// Returns false if the field was not set.
+    fn clear_field(env: &Env, address: &Address, field: &Symbol) -> bool {
+        let key = ProfileKey::Field(address.clone(), field.clone());
+        let previous: FieldValue = match env.storage().persistent().get(&key) {
+            Some(previous) => previous,
+            None => return false,
+        };
 
-        admin.require_auth();
+        if Self::is_audited_field(env.clone(), field.clone()) {
+            Self::record_field_history(env, address, field, previous);
+        }
 
-        env.deployer().update_current_contract_wasm(new_wasm_hash);
+        env.storage().persistent().remove(&key);
+        let mut names = Self::field_names(env, address);
+        if let Some(i) = names.first_index_of(field) {
+            names.remove(i);
+            Self::set_field_names(env, address, &names);
+        }
+        true
     }
 
-    // ========== Internal Helpers ==========
+    /// Set a presence flag to true, or remove it.
+    fn write_flag(env: &Env, key: &ProfileKey, set: bool) {
+        if set {
+            env.storage().persistent().set(key, &true);
+            env.storage()
+                .persistent()
+                .extend_ttl(key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        } else {
+            env.storage().persistent().remove(key);
+        }
+    }
 
-    fn require_admin(env: &Env, caller: &Address) {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&ProfileKey::Admin)
-            .unwrap_or_else(|| panic_with_error!(env, ProfileError::NotInitialized));
+    /// Increment a profile's generation and return the new value.
+    fn bump_generation(env: &Env, address: &Address) -> u64 {
+        let generation = Self::generation(env.clone(), address.clone()).saturating_add(1);
+        Self::write_generation(env, address, generation);
+        generation
+    }
 
-        if *caller != admin {
-            panic_with_error!(env, ProfileError::NotAuthorized);
+    fn write_generation(env: &Env, address: &Address, generation: u64) {
+        let key = ProfileKey::Generation(address.clone());
+        env.storage().persistent().set(&key, &generation);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+    }
+
+    fn record_field_history(env: &Env, address: &Address, field: &Symbol, previous: FieldValue) {
+        let mut history = Self::field_history(env.clone(), address.clone(), field.clone());
+        if history.len() >= MAX_FIELD_HISTORY {
+            history.pop_front();
         }
+        history.push_back((previous, env.ledger().timestamp()));
 
-        caller.require_auth();
+        let key = ProfileKey::FieldHistory(address.clone(), field.clone());
+        env.storage().persistent().set(&key, &history);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+
+        let list = ProfileKey::HistoryFields(address.clone());
+        Self::add_to_symbol_list(env, &list, field, u32::MAX);
     }
 
-    fn set_field_internal(env: &Env, caller: &Address, field: Symbol, value: FieldValue) {
-        caller.require_auth();
+    /// Get the characters of a symbol, read back from its XDR encoding
+    /// (a 4-byte type tag, then a length-prefixed string).
+    fn symbol_bytes(env: &Env, symbol: &Symbol) -> Bytes {
+        let xdr = symbol.clone().to_xdr(env);
+        let mut len = [0u8; 4];
+        xdr.slice(4..8).copy_into_slice(&mut len);
+        xdr.slice(8..8 + u32::from_be_bytes(len))
+    }
 
-        // Verify profile exists and is active
-        let profile: Profile = env
-            .storage()
+    /// Collect a profile's verified links by platform.
+    fn verified_links_of(env: &Env, address: &Address) -> Map<Symbol, (String, BytesN<32>)> {
+        let mut links = Map::new(env);
+        let platforms = Self::symbol_list(env, &ProfileKey::VerifiedLinks(address.clone()));
+        for platform in platforms.iter() {
+            if let Some(link) = Self::verified_link(env.clone(), address.clone(), platform.clone())
+            {
+                links.set(platform, link);
+            }
+        }
+        links
+    }
+
+    /// Read a list of symbols kept under `key`.
+    fn symbol_list(env: &Env, key: &ProfileKey) -> soroban_sdk::Vec<Symbol> {
+        env.storage()
             .persistent()
-            .get(&ProfileKey::Profile(caller.clone()))
-            .unwrap_or_else(|| panic_with_error!(env, ProfileError::ProfileNotFound));
+            .get(key)
+            .unwrap_or_else(|| soroban_sdk::Vec::new(env))
+    }
 
-        if profile.deleted {
-            panic_with_error!(env, ProfileError::ProfileDeleted);
+    /// Append `item` to the list under `key` unless already present.
+    ///
+    /// Returns false, leaving the list unchanged, if adding it would grow
+    /// the list beyond `max_len`.
+    fn add_to_symbol_list(env: &Env, key: &ProfileKey, item: &Symbol, max_len: u32) -> bool {
+        let mut list = Self::symbol_list(env, key);
+        if list.contains(item) {
+            return true;
+        }
+        if list.len() >= max_len {
+            return false;
         }
 
-        if profile.owner != *caller {
-            panic_with_error!(env, ProfileError::NotAuthorized);
+        list.push_back(item.clone());
+        env.storage().persistent().set(key, &list);
+        env.storage()
+            .persistent()
+            .extend_ttl(key, PROFILE_TTL_THRESHOLD, PROFILE_TTL_EXTEND);
+        true
+    }
+
+    fn write_field(env: &Env, address: &Address, field: &Symbol, value: &FieldValue) {
+        let key = ProfileKey::Field(address.clone(), field.clone());
+        if !env.storage().persistent().has(&key) {
+            let mut names = Self::field_names(env, address);
+            names.push_back(field.clone());
+            Self::set_field_names(env, address, &names);
         }
 
-        // Set field
         env.storage()
             .persistent()
-            .set(&ProfileKey::Field(caller.clone(), field.clone()), &value);
+            .set(&ProfileKey::Field(address.clone(), field.clone()), value);
 
-        // Extend TTL
         env.storage().persistent().extend_ttl(
-            &ProfileKey::Field(caller.clone(), field.clone()),
+            &ProfileKey::Field(address.clone(), field.clone()),
             PROFILE_TTL_THRESHOLD,
             PROFILE_TTL_EXTEND,
         );
-
-        emit_profile_updated(env, caller, &field);
     }
 }
 
@@ -616,6 +4887,14 @@ impl UserProfileContract {
         render::render_profile_card(&env, &address)
     }
 
+    /// Render a profile card using a host app's registered card options.
+    ///
+    /// Apps without registered options get the standard card.
+    pub fn render_profile_card_for_app(env: Env, address: Address, app: Address) -> Bytes {
+        let options = Self::app_card_prefs(env.clone(), app).unwrap_or_default();
+        render::render_profile_card_with(&env, &address, &options)
+    }
+
     /// Render a compact profile card.
     pub fn render_profile_card_compact(env: Env, address: Address) -> Bytes {
         render::render_profile_card_compact(&env, &address)
@@ -638,6 +4917,61 @@ impl UserProfileContract {
         render::render_username(&env, &address)
     }
 
+    /// Render a profile card on behalf of an embedding contract.
+    ///
+    /// Under the `allowlist` embed policy, unapproved embedders get the
+    /// anonymous card.
+    pub fn render_profile_card_for(env: Env, address: Address, embedder: Option<Address>) -> Bytes {
+        render::render_profile_card_for(&env, &address, embedder)
+    }
+
+    /// Render a profile card as one line of plain text, without HTML.
+    ///
+    /// For hosts that strip HTML. The line has the form
+    /// `Alice (@alice001) — bio — profile:/u/alice001`, with the bio cut
+    /// to 80 bytes without splitting a character.
+    pub fn render_profile_card_text(env: Env, address: Address) -> Bytes {
+        render::render_profile_card_text(&env, &address, true)
+    }
+
+    /// Render a profile card only if the profile changed.
+    ///
+    /// Returns None when `client_hash` matches `profile_hash`, so
+    /// embedders can skip re-rendering cards they already hold. Otherwise
+    /// renders as `render_profile_card_for` does, so the embed policy
+    /// applies to `embedder`.
+    pub fn render_if_changed(
+        env: Env,
+        address: Address,
+        client_hash: BytesN<32>,
+        embedder: Option<Address>,
+    ) -> Option<Bytes> {
+        if Self::profile_hash(env.clone(), address.clone()) == client_hash {
+            return None;
+        }
+        Some(render::render_profile_card_for(&env, &address, embedder))
+    }
+
+    /// Render the username on behalf of an embedding contract.
+    ///
+    /// Under the `allowlist` embed policy, unapproved embedders get the
+    /// truncated address.
+    pub fn render_username_for(env: Env, address: Address, embedder: Option<Address>) -> Bytes {
+        render::render_username_for(&env, &address, embedder)
+    }
+
+    /// Render a `key: value` metadata block (title, description, image)
+    /// for link previews.
+    pub fn render_profile_meta(env: Env, username: Bytes) -> Bytes {
+        let username = canonicalize_username(&username);
+        render::render_profile_meta(&env, &username)
+    }
+
+    /// Render the small avatar-and-name chip hosts show for the viewer.
+    pub fn render_viewer_chip(env: Env, viewer: Option<Address>) -> Bytes {
+        render::render_viewer_chip(&env, &viewer)
+    }
+
     /// Render a navigation link for embedding in nav bars.
     ///
     /// Returns "@username" link if viewer has a profile,
@@ -655,3 +4989,32 @@ impl UserProfileContract {
         render::render_nav_link_return(&env, &viewer, return_path)
     }
 }
+
+// ========== Recovery ==========
+
+#[cfg(feature = "allow-reinit")]
+#[contractimpl]
+impl UserProfileContract {
+    /// Set a new admin on a contract whose admin key is missing.
+    ///
+    /// Only compiled with the `allow-reinit` feature, for recovering a
+    /// contract that lost its admin through a bug. Requires the recovery
+    /// address given to `init_with_config`; without one, recovery fails
+    /// with `NotAuthorized`. Fails with `AlreadyInitialized` whenever an
+    /// admin is set. Unlike `init`, the profile count and all other data
+    /// are left untouched.
+    pub fn reinit(env: Env, admin: Address) {
+        if env.storage().instance().has(&ProfileKey::Admin) {
+            panic_with_error!(&env, ProfileError::AlreadyInitialized);
+        }
+
+        let recovery: Address = env
+            .storage()
+            .instance()
+            .get(&ProfileKey::RecoveryAddress)
+            .unwrap_or_else(|| panic_with_error!(&env, ProfileError::NotAuthorized));
+        recovery.require_auth();
+        admin.require_auth();
+        env.storage().instance().set(&ProfileKey::Admin, &admin);
+    }
+}
@@ -0,0 +1,67 @@
+//! Moderation log of admin actions taken against profiles.
+//!
+//! Actions that bypass the profile owner's authorization are recorded
+//! here so they can be audited later.
+
+use soroban_sdk::{contracttype, Address, Bytes, Env, Symbol};
+
+use crate::storage::{ProfileKey, PROFILE_TTL_EXTEND, PROFILE_TTL_THRESHOLD};
+
+/// A single moderation log entry.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ModLogEntry {
+    /// Short action name, e.g. `rename`.
+    pub action: Symbol,
+
+    /// Address of the affected profile.
+    pub target: Address,
+
+    /// Admin who performed the action.
+    pub admin: Address,
+
+    /// Action-specific detail, e.g. the previous username.
+    pub detail: Bytes,
+
+    /// Ledger timestamp when the action was taken.
+    pub timestamp: u64,
+}
+
+/// Append an entry to the moderation log and return its index.
+pub fn record(env: &Env, action: &str, target: &Address, admin: &Address, detail: Bytes) -> u64 {
+    let index = count(env);
+    let entry = ModLogEntry {
+        action: Symbol::new(env, action),
+        target: target.clone(),
+        admin: admin.clone(),
+        detail,
+        timestamp: env.ledger().timestamp(),
+    };
+
+    env.storage()
+        .persistent()
+        .set(&ProfileKey::ModLog(index), &entry);
+    env.storage().persistent().extend_ttl(
+        &ProfileKey::ModLog(index),
+        PROFILE_TTL_THRESHOLD,
+        PROFILE_TTL_EXTEND,
+    );
+    env.storage()
+        .instance()
+        .set(&ProfileKey::ModLogCount, &(index + 1));
+
+    index
+}
+
+/// Number of entries in the moderation log.
+pub fn count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&ProfileKey::ModLogCount)
+        .unwrap_or(0)
+}
+
+/// Get a moderation log entry by index.
+pub fn get(env: &Env, index: u64) -> Option<ModLogEntry> {
+    env.storage().persistent().get(&ProfileKey::ModLog(index))
+}
@@ -1,6 +1,19 @@
 //! Profile struct and related types.
 
-use soroban_sdk::{contracttype, Address, Bytes, String};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Map, String, Symbol, Vec};
+
+use crate::fields::FieldValue;
+use crate::format::DateStyle;
+
+/// `created_at` values below this are legacy ledger sequence numbers
+/// rather than Unix timestamps.
+pub const LEGACY_TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+
+/// Schema version recorded for newly registered profiles.
+pub const PROFILE_SCHEMA_VERSION: u32 = 2;
+
+/// Schema version of profiles registered before versions were recorded.
+pub const UNVERSIONED_PROFILE_SCHEMA: u32 = 1;
 
 /// User profile metadata.
 ///
@@ -18,7 +31,8 @@ pub struct Profile {
     /// Profile owner's blockchain address.
     pub owner: Address,
 
-    /// Timestamp when the profile was created (ledger sequence).
+    /// Unix timestamp when the profile was created. Legacy profiles hold
+    /// a ledger sequence instead; see `LEGACY_TIMESTAMP_THRESHOLD`.
     pub created_at: u64,
 
     /// Timestamp when the profile was last updated (ledger sequence).
@@ -50,4 +64,158 @@ impl Profile {
     pub fn is_active(&self) -> bool {
         !self.deleted
     }
+
+    /// Seconds since the profile was created, as of `now`.
+    ///
+    /// Returns None for legacy profiles whose `created_at` holds a ledger
+    /// sequence number instead of a Unix timestamp.
+    pub fn age_seconds(&self, now: u64) -> Option<u64> {
+        if self.created_at < LEGACY_TIMESTAMP_THRESHOLD {
+            None
+        } else {
+            Some(now.saturating_sub(self.created_at))
+        }
+    }
+}
+
+/// A profile bundled with a requested subset of its fields.
+///
+/// Lets consumers resolve a username and read fields such as avatar and
+/// bio in a single call.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ProfileView {
+    /// The profile itself.
+    pub profile: Profile,
+
+    /// Requested fields that are set. Unset fields are absent.
+    pub fields: Map<Symbol, FieldValue>,
+}
+
+/// A profile bundled with every standard field it has set.
+///
+/// Returned by `get_full_profile`, which covers what a typical profile
+/// page needs in one call.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct FullProfile {
+    /// The profile itself.
+    pub profile: Profile,
+
+    /// Standard fields that are set. Unset fields are absent.
+    pub fields: Map<Symbol, FieldValue>,
+}
+
+/// A complete record of one directory slot, for snapshotting the registry.
+///
+/// Returned by `export_page`. Deleted and banned profiles are included
+/// so a snapshot reflects every registered name. Not covered: field
+/// history, frozen-field markers, the avatar email hash, the email
+/// display setting, source attribution and moderation data (reports,
+/// admin notes and the moderation log).
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ProfileExport {
+    /// Registration index of the profile.
+    pub index: u64,
+
+    /// The profile record, including its `deleted` flag.
+    pub profile: Profile,
+
+    /// Every indexed field of the profile.
+    pub fields: Map<Symbol, FieldValue>,
+
+    /// Whether the admin banned the profile.
+    pub banned: bool,
+
+    /// Whether the profile is hidden pending review.
+    pub hidden: bool,
+
+    /// Whether the owner has temporarily deactivated the profile.
+    pub deactivated: bool,
+
+    /// Secondary usernames, in the order they were added.
+    pub aliases: Vec<Bytes>,
+
+    /// Previous usernames that still redirect, oldest first.
+    pub former_usernames: Vec<Bytes>,
+
+    /// Links as (label, url) pairs, in display order.
+    pub links: Vec<(String, String)>,
+
+    /// Verified external accounts by platform, as (handle, proof hash).
+    pub verified_links: Map<Symbol, (String, BytesN<32>)>,
+
+    /// Preferred locale, if set.
+    pub locale: Option<Symbol>,
+
+    /// How dates are shown on the profile page.
+    pub date_style: DateStyle,
+
+    /// Ledger sequence the profile was registered in, if recorded.
+    pub created_at_ledger: Option<u32>,
+
+    /// Schema version of the profile record, if recorded.
+    pub schema: Option<u32>,
+
+    /// The profile's generation.
+    pub generation: u64,
+}
+
+/// Activity counters for a profile's "about" pane.
+///
+/// Returned by `profile_stats`. Counters for data a profile never set
+/// are zero.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProfileStats {
+    /// Number of indexed fields set on the profile.
+    pub fields: u32,
+
+    /// Number of links on the profile page.
+    pub links: u32,
+
+    /// Number of aliases held by the profile.
+    pub aliases: u32,
+}
+
+/// What a host contract needs to know about the current viewer.
+///
+/// Returned by `viewer_context` so hosts can build their header from a
+/// single call.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ViewerContext {
+    /// Whether the viewer has an active profile.
+    pub has_profile: bool,
+
+    /// The viewer's username, if they have an active profile.
+    pub username: Option<Bytes>,
+
+    /// The viewer's display name, if they have an active profile.
+    pub display_name: Option<String>,
+
+    /// The viewer's avatar URL. None for unset and on-chain image avatars.
+    pub avatar: Option<String>,
+
+    /// Whether the viewer is the contract admin.
+    pub is_admin: bool,
+}
+
+/// Configuration applied atomically by `init_with_config`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct InitConfig {
+    /// Registration fee in stroops, or None to leave it at 0.
+    pub registration_fee: Option<i128>,
+
+    /// Whether registrations are open. False starts the contract paused.
+    pub registration_open: bool,
+
+    /// Usernames to reserve, validated as `reserve_username` does.
+    pub reserved_usernames: Vec<Bytes>,
+
+    /// Address that may call `reinit` if the admin key goes missing, or
+    /// None to rule recovery out. Cannot be changed later.
+    pub recovery_address: Option<Address>,
 }
@@ -1,44 +1,109 @@
 //! Render functions for the user profile contract.
 
-use soroban_sdk::{Address, Bytes, Env, String, Symbol};
+use soroban_render_sdk::bytes::string_to_bytes;
 use soroban_render_sdk::prelude::*;
+use soroban_sdk::{Address, Bytes, BytesN, Env, String, Symbol, Vec};
 
 use crate::fields::FieldValue;
-use crate::profile::Profile;
-use crate::storage::ProfileKey;
+use crate::format::{
+    address_to_bytes, bytes_to_hex, format_duration, format_relative, format_timestamp_style,
+    i128_to_bytes, parse_u32, truncate_address_bytes_with, u64_to_bytes, CardOptions, DateStyle,
+    DEFAULT_TRUNCATE_PREFIX, DEFAULT_TRUNCATE_SUFFIX,
+};
+use crate::profile::{Profile, LEGACY_TIMESTAMP_THRESHOLD};
+use crate::storage::{
+    ProfileKey, DIRECTORY_PAGE_SIZE, LEDGER_CLOSE_SECONDS, PROFILE_TTL_THRESHOLD,
+};
+use crate::validation::{canonicalize_username, dweb_scheme, validate_username, MAX_AVATAR_BYTES};
+
+/// Link labels, matched case-insensitively as substrings, that are also
+/// listed in a profile's Contact section.
+const CONTACT_LINK_LABELS: [&[u8]; 3] = [b"contact", b"matrix", b"mail"];
+
+/// Profile age, in seconds, for the `profile-age-1y` milestone class.
+const AGE_MILESTONE_1Y: u64 = 365 * 86_400;
+
+/// Profile age, in seconds, for the `profile-age-og` milestone class.
+const AGE_MILESTONE_OG: u64 = 3 * 365 * 86_400;
 
 /// Main render entry point with routing.
 pub fn render(env: &Env, path: Option<String>, viewer: Option<Address>) -> Bytes {
+    // `/@{username}` is shorthand for `/u/{username}`. The Router matches
+    // whole segments, so the `@` prefix is resolved before routing.
+    if let Some(p) = &path {
+        let bytes = string_to_bytes(env, p);
+        if bytes.len() > 2 && bytes.get(0) == Some(b'/') && bytes.get(1) == Some(b'@') {
+            return render_profile_by_username_with_return(env, &bytes.slice(2..), &viewer, None);
+        }
+    }
+
     Router::new(env, path)
         .handle(b"/", |_| render_home(env, &viewer))
         // Handle registration with return path: /register/from/{return_path}
         .or_handle(b"/register/from/*", |req| {
             let return_path = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
-            render_register_form_with_return(env, &viewer, Some(return_path))
+            render_register_form_with_return(env, &viewer, Some(return_path), None)
+        })
+        // Registration form with the username filled in
+        .or_handle(b"/register/{username}", |req| {
+            let username = req.get_var(b"username").unwrap_or_else(|| Bytes::new(env));
+            render_register_form_with_return(env, &viewer, None, Some(username))
+        })
+        .or_handle(b"/register", |_| {
+            render_register_form_with_return(env, &viewer, None, None)
         })
-        .or_handle(b"/register", |_| render_register_form_with_return(env, &viewer, None))
         // Handle edit with return path: /edit/from/{return_path}
         .or_handle(b"/edit/from/*", |req| {
             let return_path = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
             render_edit_form_with_return(env, &viewer, Some(return_path))
         })
-        .or_handle(b"/edit", |_| render_edit_form_with_return(env, &viewer, None))
+        .or_handle(b"/edit", |_| {
+            render_edit_form_with_return(env, &viewer, None)
+        })
         .or_handle(b"/help", |_| render_help(env))
+        .or_handle(b"/stats", |_| render_stats(env))
+        // Side-by-side moderation view: /compare/{first}/{second}
+        .or_handle(b"/compare/{first}/{second}", |req| {
+            let first = req.get_var(b"first").unwrap_or_else(|| Bytes::new(env));
+            let second = req.get_var(b"second").unwrap_or_else(|| Bytes::new(env));
+            render_compare(env, &viewer, &first, &second)
+        })
+        .or_handle(b"/u/{username}/stats", |req| {
+            let username = req.get_var(b"username").unwrap_or_else(|| Bytes::new(env));
+            render_profile_stats(env, &username)
+        })
         // Handle profile with return path: /u/{username}/from/{return_path}
         .or_handle(b"/u/{username}/from/*", |req| {
             let username = req.get_var(b"username").unwrap_or_else(|| Bytes::new(env));
             let return_path = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
             render_profile_by_username_with_return(env, &username, &viewer, Some(return_path))
         })
+        .or_handle(b"/embed/u/*", |req| {
+            let username = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
+            render_embed(env, &username)
+        })
         .or_handle(b"/u/*", |req| {
             let username = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
             render_profile_by_username_with_return(env, &username, &viewer, None)
         })
+        // Handle directory pagination: /dir/{letter}/{page}
+        .or_handle(b"/dir/{letter}/{page}", |req| {
+            let letter = req.get_var(b"letter").unwrap_or_else(|| Bytes::new(env));
+            let page = req
+                .get_var(b"page")
+                .and_then(|p| parse_u32(&p))
+                .unwrap_or(1);
+            render_directory(env, &letter, page)
+        })
+        .or_handle(b"/dir/*", |req| {
+            let letter = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
+            render_directory(env, &letter, 1)
+        })
         .or_handle(b"/a/*", |req| {
             let _addr_str = req.get_wildcard().unwrap_or_else(|| Bytes::new(env));
             render_profile_by_address_str(env, &viewer)
         })
-        .or_default(|_| render_home(env, &viewer))
+        .or_default(|_| render_not_found(env))
 }
 
 /// Render the home page with search form.
@@ -111,10 +176,14 @@ fn render_home(env: &Env, viewer: &Option<Address>) -> Bytes {
 }
 
 /// Render the registration form with optional return path.
+///
+/// A valid `username` is filled in, with a warning if the admin has
+/// soft-reserved it.
 fn render_register_form_with_return(
     env: &Env,
     viewer: &Option<Address>,
     return_path: Option<Bytes>,
+    username: Option<Bytes>,
 ) -> Bytes {
     let mut md = MarkdownBuilder::new(env).h1("Register Profile");
 
@@ -122,6 +191,13 @@ fn render_register_form_with_return(
     md = render_back_link(env, md, &return_path);
     md = md.newline().newline();
 
+    // Don't offer a form whose transaction would revert
+    if is_paused(env) {
+        return md
+            .warning("Registrations temporarily closed. Please check back later.")
+            .build();
+    }
+
     if viewer.is_none() {
         return md
             .warning("Please connect your wallet to register.")
@@ -147,6 +223,22 @@ fn render_register_form_with_return(
         }
     }
 
+    let username = username
+        .map(|name| canonical_username(&name))
+        .filter(validate_username);
+
+    if let Some(name) = &username {
+        let soft_reserved = env
+            .storage()
+            .persistent()
+            .has(&ProfileKey::SoftReserved(name.clone()));
+        if soft_reserved {
+            md = md.warning(
+                "This username is discouraged by the registry. You can still register it.",
+            );
+        }
+    }
+
     md = md
         .h2("Username Requirements")
         .paragraph("Your username must:")
@@ -156,8 +248,18 @@ fn render_register_form_with_return(
         .raw_str("- Only contain lowercase letters, digits, and underscores\n")
         .raw_str("\n**Examples:** `alice001`, `bob_smith123`, `crypto_fan_999`\n\n")
         .h2("Registration Form")
-        .raw_str("<input type=\"text\" name=\"username\" placeholder=\"Username (e.g., alice001)\" required />\n")
-        .raw_str("<input type=\"text\" name=\"display_name\" placeholder=\"Display Name\" required />\n")
+        .raw_str("<input type=\"text\" name=\"username\" ");
+
+    // Validated usernames contain only [a-z0-9_], so they are safe to embed
+    if let Some(name) = username {
+        md = md.raw_str("value=\"").raw(name).raw_str("\" ");
+    }
+
+    md = md
+        .raw_str("placeholder=\"Username (e.g., alice001)\" required />\n")
+        .raw_str(
+            "<input type=\"text\" name=\"display_name\" placeholder=\"Display Name\" required />\n",
+        )
         .raw_str("<input type=\"hidden\" name=\"caller\" value=\"")
         .raw(address_to_bytes(env, viewer_addr))
         .raw_str("\" />\n")
@@ -213,18 +315,21 @@ fn render_edit_form_with_return(
         Some(p) if !p.is_active() => md.warning("Your profile has been deleted.").build(),
         Some(p) => {
             // Show timestamps
+            let style = date_style(env, viewer_addr);
             md = md
                 .raw_str("<div class=\"profile-timestamps\">")
                 .raw_str("<small>")
                 .text("Created: ")
-                .raw(format_timestamp(env, p.created_at))
+                .raw(format_timestamp_style(env, p.created_at, style))
                 .text(" · Updated: ")
-                .raw(format_timestamp(env, p.updated_at))
+                .raw(format_timestamp_style(env, p.updated_at, style))
                 .raw_str("</small>")
                 .raw_str("</div>")
                 .newline()
                 .newline();
 
+            md = render_ttl_warning(env, md, viewer_addr);
+
             // Display name edit
             md = md
                 .h2("Display Name")
@@ -245,6 +350,7 @@ fn render_edit_form_with_return(
                 ("bio", "Bio", "Tell us about yourself..."),
                 ("avatar", "Avatar URL", "https://..."),
                 ("homepage", "Homepage", "https://..."),
+                ("dweb", "Decentralized Site", "ipfs://..."),
                 ("location", "Location", "City, Country"),
                 ("github", "GitHub", "username"),
                 ("twitter", "Twitter", "@handle"),
@@ -305,7 +411,69 @@ fn render_edit_form_with_return(
     }
 }
 
+/// Render a minimal embeddable profile for host apps.
+///
+/// Shows only the avatar, name, handle and bio plus a link to the full
+/// profile: no navigation, edit controls, timestamps or back links.
+/// Deleted and hidden profiles render the same as unknown usernames.
+fn render_embed(env: &Env, username: &Bytes) -> Bytes {
+    let address = crate::UserProfileContract::resolve_username(env, &canonical_username(username));
+    let profile: Option<Profile> = address.as_ref().and_then(|addr| {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Profile(addr.clone()))
+    });
+
+    match (address, profile) {
+        (Some(addr), Some(p)) if p.is_active() && !is_hidden(env, &addr) => {
+            let mut md = MarkdownBuilder::new(env).raw_str("<div class=\"profile-embed\">");
+            md = render_avatar(env, md, &addr, "\" alt=\"Avatar\" />");
+
+            md = md
+                .raw_str("<div class=\"profile-embed-name\">")
+                .text_string(&p.display_name)
+                .raw_str("</div>")
+                .raw_str("<div class=\"profile-embed-username\">@")
+                .raw(p.username.clone())
+                .raw_str("</div>");
+
+            if let Some(FieldValue::StringField(bio)) = env
+                .storage()
+                .persistent()
+                .get(&ProfileKey::Field(addr, Symbol::new(env, "bio")))
+            {
+                md = md
+                    .raw_str("<p class=\"profile-embed-bio\">")
+                    .text_string(&bio)
+                    .raw_str("</p>");
+            }
+
+            md.raw_str("<a href=\"render:@profile:/u/")
+                .raw(p.username)
+                .raw_str("\">View full profile</a>")
+                .raw_str("</div>")
+                .build()
+        }
+        _ => MarkdownBuilder::new(env)
+            .raw_str("<div class=\"profile-embed-missing\">Profile not found</div>")
+            .build(),
+    }
+}
+
+/// Normalize a username taken from a path: trailing slashes are dropped
+/// and the rest canonicalized, so `/u/Alice001/` finds `alice001`.
+fn canonical_username(raw: &Bytes) -> Bytes {
+    let mut end = raw.len();
+    while end > 0 && raw.get(end - 1) == Some(b'/') {
+        end -= 1;
+    }
+    canonicalize_username(&raw.slice(0..end))
+}
+
 /// Render profile by username with optional return path.
+///
+/// The username is matched case-insensitively and may carry a trailing
+/// slash.
 fn render_profile_by_username_with_return(
     env: &Env,
     username: &Bytes,
@@ -313,10 +481,7 @@ fn render_profile_by_username_with_return(
     return_path: Option<Bytes>,
 ) -> Bytes {
     // Look up address from username
-    let address: Option<Address> = env
-        .storage()
-        .persistent()
-        .get(&ProfileKey::Username(username.clone()));
+    let address = crate::UserProfileContract::resolve_username(env, &canonical_username(username));
 
     match address {
         Some(addr) => render_full_profile(env, &addr, viewer, return_path),
@@ -330,6 +495,45 @@ fn render_profile_by_username_with_return(
     }
 }
 
+/// Render a profile's activity counters as a definition list.
+fn render_profile_stats(env: &Env, username: &Bytes) -> Bytes {
+    let profile = crate::UserProfileContract::resolve_username(env, &canonical_username(username))
+        .filter(|address| !is_hidden(env, address))
+        .and_then(|address| crate::UserProfileContract::get_by_address(env.clone(), address));
+    let profile = match profile {
+        Some(profile) => profile,
+        None => {
+            return MarkdownBuilder::new(env)
+                .h1("Profile Not Found")
+                .paragraph("No profile found with that username.")
+                .render_link("Back to Home", "/")
+                .build();
+        }
+    };
+    let stats = crate::UserProfileContract::profile_stats(env.clone(), profile.owner.clone());
+
+    let mut md = MarkdownBuilder::new(env)
+        .h1("Activity")
+        .text("@")
+        .raw(profile.username)
+        .newline()
+        .newline()
+        .raw_str("<dl class=\"profile-stats\">\n");
+    for (label, count) in [
+        ("Fields", stats.fields),
+        ("Links", stats.links),
+        ("Aliases", stats.aliases),
+    ] {
+        md = md
+            .raw_str("<dt>")
+            .raw_str(label)
+            .raw_str("</dt><dd>")
+            .raw(u64_to_bytes(env, count as u64))
+            .raw_str("</dd>\n");
+    }
+    md.raw_str("</dl>\n").build()
+}
+
 /// Render a "Go Back" or "Back to Home" link based on return path.
 ///
 /// Supports:
@@ -400,22 +604,40 @@ fn render_full_profile(
             md = render_back_link(env, md, &return_path);
             md.build()
         }
+        Some(p) if is_deactivated(env, address) && viewer.as_ref() != Some(&p.owner) => {
+            let mut md = MarkdownBuilder::new(env)
+                .h1("Profile Not Found")
+                .paragraph("This profile is not currently active.");
+            md = render_back_link(env, md, &return_path);
+            md.build()
+        }
+        Some(p) if is_hidden(env, address) && viewer.as_ref() != Some(&p.owner) => {
+            let mut md = MarkdownBuilder::new(env)
+                .h1("Profile Under Review")
+                .paragraph("This profile has been hidden pending moderator review.");
+            md = render_back_link(env, md, &return_path);
+            md.build()
+        }
         Some(p) => {
             let mut md = MarkdownBuilder::new(env);
             md = render_back_link(env, md, &return_path);
             md = md.newline().newline();
 
-            // Avatar if present
-            if let Some(FieldValue::StringField(avatar)) = env
-                .storage()
-                .persistent()
-                .get(&ProfileKey::Field(address.clone(), Symbol::new(env, "avatar")))
-            {
-                md = md
-                    .raw_str("<img class=\"profile-avatar\" src=\"")
-                    .text_string(&avatar)
-                    .raw_str("\" alt=\"Avatar\" style=\"width: 100px; border-radius: 50%;\" />\n\n");
+            // Only the owner gets past the interstitial for a hidden profile
+            if is_deactivated(env, address) {
+                md = md.warning("Your profile is deactivated and hidden from others.");
             }
+            if is_under_review(env, address) {
+                md = md.warning("Your profile is hidden from others pending moderator review.");
+            }
+
+            // Avatar if present
+            md = render_avatar(
+                env,
+                md,
+                address,
+                "\" alt=\"Avatar\" style=\"width: 100px; border-radius: 50%;\" />\n\n",
+            );
 
             // Name and username
             md = md
@@ -437,6 +659,11 @@ fn render_full_profile(
                 md = md.text_string(&bio).raw_str("\n\n");
             }
 
+            // Decentralized website as the primary call to action
+            if let Some((url, scheme)) = dweb_link(env, address) {
+                md = render_dweb_link(md, url, scheme).raw_str("\n\n");
+            }
+
             // Other fields
             let fields = [
                 ("location", "Location"),
@@ -446,29 +673,69 @@ fn render_full_profile(
             ];
 
             for (field_name, label) in fields.iter() {
-                if let Some(FieldValue::StringField(value)) = env
-                    .storage()
-                    .persistent()
-                    .get(&ProfileKey::Field(address.clone(), Symbol::new(env, field_name)))
-                {
+                if let Some(FieldValue::StringField(value)) = env.storage().persistent().get(
+                    &ProfileKey::Field(address.clone(), Symbol::new(env, field_name)),
+                ) {
+                    md = md.text("**").text(label).text(":** ").text_string(&value);
+                    if is_verified(env, address, field_name, &value) {
+                        md = md.raw_str(
+                            " <span class=\"profile-verified\" title=\"Verified\">✓</span>",
+                        );
+                    }
+                    md = md.newline();
+                }
+            }
+
+            // Labeled links, in the owner's order. URLs are validated on
+            // write and cannot contain markdown delimiters.
+            let links: Vec<(String, String)> = env
+                .storage()
+                .persistent()
+                .get(&ProfileKey::Links(address.clone()))
+                .unwrap_or_else(|| Vec::new(env));
+            if !links.is_empty() {
+                md = md.newline();
+                for (label, url) in links.iter() {
+                    let label = single_line(env, &string_to_bytes(env, &label));
                     md = md
-                        .text("**")
-                        .text(label)
-                        .text(":** ")
-                        .text_string(&value)
-                        .newline();
+                        .raw_str("- [")
+                        .raw(escape_markdown(env, &label))
+                        .raw_str("](")
+                        .text_string(&url)
+                        .raw_str(")\n");
+                }
+            }
+
+            md = render_contact(env, md, address, &links);
+
+            // Membership age, omitted for legacy ledger-sequence timestamps
+            if let Some(age) = p.age_seconds(env.ledger().timestamp()) {
+                md = md.newline().raw_str("<div class=\"profile-age");
+                if age >= AGE_MILESTONE_1Y {
+                    md = md.raw_str(" profile-age-1y");
+                }
+                if age >= AGE_MILESTONE_OG {
+                    md = md.raw_str(" profile-age-og");
                 }
+                md = md
+                    .raw_str("\">")
+                    .text("Member for ")
+                    .raw(format_duration(env, age))
+                    .raw_str("</div>")
+                    .newline();
             }
 
-            // Timestamps
+            // Creation date in the owner's preferred style; updates are
+            // always shown relative to now
+            let style = date_style(env, address);
             md = md
                 .newline()
                 .raw_str("<div class=\"profile-timestamps\">")
                 .raw_str("<small>")
                 .text("Created: ")
-                .raw(format_timestamp(env, p.created_at))
+                .raw(format_timestamp_style(env, p.created_at, style))
                 .text(" · Updated: ")
-                .raw(format_timestamp(env, p.updated_at))
+                .raw(format_relative(env, p.updated_at, env.ledger().timestamp()))
                 .raw_str("</small>")
                 .raw_str("</div>");
 
@@ -489,6 +756,22 @@ fn render_full_profile(
                             md = md.render_link("Edit Profile", "/edit");
                         }
                     }
+                } else if has_active_profile(env, viewer_addr) {
+                    // Let other registered users flag this profile
+                    // Input order must match contract signature: target, reason, reporter
+                    md = md
+                        .newline()
+                        .newline()
+                        .raw_str("<div data-form class=\"profile-report\">\n")
+                        .raw_str("<input type=\"hidden\" name=\"target\" value=\"")
+                        .raw(address_to_bytes(env, address))
+                        .raw_str("\" />\n")
+                        .raw_str("<input type=\"text\" name=\"reason\" placeholder=\"Reason (e.g., spam)\" />\n")
+                        .raw_str("<input type=\"hidden\" name=\"reporter\" value=\"")
+                        .raw(address_to_bytes(env, viewer_addr))
+                        .raw_str("\" />\n")
+                        .form_link("Report Profile", "report_profile")
+                        .raw_str("</div>\n");
                 }
             }
 
@@ -497,6 +780,347 @@ fn render_full_profile(
     }
 }
 
+/// Render the username directory for a single first letter.
+///
+/// Pages are 1-based and hold up to `DIRECTORY_PAGE_SIZE` entries.
+/// Entries whose profile is deleted or no longer resolves are skipped.
+fn render_directory(env: &Env, letter: &Bytes, page: u32) -> Bytes {
+    let mut md = MarkdownBuilder::new(env)
+        .h1("Directory")
+        .render_link("Back to Home", "/")
+        .newline()
+        .newline();
+
+    // Letter navigation
+    for c in b'a'..=b'z' {
+        md = md
+            .raw_str("[")
+            .raw(Bytes::from_slice(env, &[c]))
+            .raw_str("](render:/dir/")
+            .raw(Bytes::from_slice(env, &[c]))
+            .raw_str(") ");
+    }
+    md = md.newline().newline();
+
+    let first = letter.get(0).unwrap_or(0);
+    if letter.len() != 1 || !first.is_ascii_lowercase() {
+        return md.paragraph("Choose a letter to browse usernames.").build();
+    }
+
+    let bucket = first as u32;
+    let count: u32 = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::DirectoryCount(bucket))
+        .unwrap_or(0);
+
+    md = md
+        .raw_str("## Usernames starting with \"")
+        .raw(letter.clone())
+        .raw_str("\"\n\n");
+
+    let page = if page == 0 { 1 } else { page };
+    let start = (page - 1).saturating_mul(DIRECTORY_PAGE_SIZE);
+
+    if start >= count {
+        return md.paragraph("No usernames found for this letter.").build();
+    }
+
+    let has_next = count - start > DIRECTORY_PAGE_SIZE;
+
+    let owners: Vec<Address> = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::DirectoryPage(bucket, page - 1))
+        .unwrap_or_else(|| Vec::new(env));
+
+    for owner in owners.iter() {
+        let profile: Option<Profile> = env.storage().persistent().get(&ProfileKey::Profile(owner));
+
+        if let Some(p) = profile.filter(|p| p.is_active() && p.username.get(0) == Some(first)) {
+            md = md
+                .raw_str("- [@")
+                .raw(p.username.clone())
+                .raw_str("](render:/u/")
+                .raw(p.username)
+                .raw_str(") — ")
+                .text_string(&p.display_name)
+                .newline();
+        }
+    }
+
+    md = md.newline();
+
+    // Pagination links
+    if page > 1 {
+        md = md
+            .raw_str("[Previous](render:/dir/")
+            .raw(letter.clone())
+            .raw_str("/")
+            .raw(u64_to_bytes(env, (page - 1) as u64))
+            .raw_str(")");
+        if has_next {
+            md = md.text(" | ");
+        }
+    }
+    if has_next {
+        md = md
+            .raw_str("[Next](render:/dir/")
+            .raw(letter.clone())
+            .raw_str("/")
+            .raw(u64_to_bytes(env, (page + 1) as u64))
+            .raw_str(")");
+    }
+
+    md.build()
+}
+
+/// Render the fallback page for paths that match no route.
+fn render_not_found(env: &Env) -> Bytes {
+    MarkdownBuilder::new(env)
+        .h1("Page Not Found")
+        .paragraph("The page you requested does not exist.")
+        .render_link("Back to Home", "/")
+        .build()
+}
+
+/// Render the page shown to viewers who may not see a page.
+fn render_not_authorized(env: &Env) -> Bytes {
+    MarkdownBuilder::new(env)
+        .h1("Not Authorized")
+        .paragraph("You do not have permission to view this page.")
+        .render_link("Back to Home", "/")
+        .build()
+}
+
+/// String fields shown side by side on the compare page.
+const COMPARE_FIELDS: [(&str, &str); 6] = [
+    ("bio", "Bio"),
+    ("homepage", "Website"),
+    ("dweb", "Decentralized Site"),
+    ("location", "Location"),
+    ("github", "GitHub"),
+    ("twitter", "Twitter"),
+];
+
+/// Render two profiles side by side for the admin.
+///
+/// Values that are identical in both profiles get the `compare-match`
+/// class. Deleted and hidden profiles are shown, since they are often
+/// what is being investigated.
+fn render_compare(env: &Env, viewer: &Option<Address>, first: &Bytes, second: &Bytes) -> Bytes {
+    let admin: Option<Address> = env.storage().instance().get(&ProfileKey::Admin);
+    if viewer.is_none() || *viewer != admin {
+        return render_not_authorized(env);
+    }
+
+    let lookup = |name: &Bytes| -> Option<(Address, Profile)> {
+        let address = crate::UserProfileContract::resolve_username(env, &canonical_username(name))?;
+        let profile = env
+            .storage()
+            .persistent()
+            .get(&ProfileKey::Profile(address.clone()))?;
+        Some((address, profile))
+    };
+    let first = lookup(first);
+    let second = lookup(second);
+
+    let mut md = MarkdownBuilder::new(env)
+        .h1("Compare Profiles")
+        .render_link("Back to Home", "/")
+        .newline()
+        .newline()
+        .raw_str("<div class=\"profile-compare\">\n");
+    md = render_compare_column(env, md, &first, &second);
+    md = render_compare_column(env, md, &second, &first);
+    md.raw_str("</div>\n").build()
+}
+
+/// Render one column of the compare page, matching against `other`.
+fn render_compare_column<'a>(
+    env: &Env,
+    md: MarkdownBuilder<'a>,
+    this: &Option<(Address, Profile)>,
+    other: &Option<(Address, Profile)>,
+) -> MarkdownBuilder<'a> {
+    let mut md = md.raw_str("<div class=\"profile-compare-col\">\n");
+
+    let (address, profile) = match this {
+        Some(entry) => entry,
+        None => {
+            return md
+                .raw_str("<div class=\"compare-field\">Profile not found</div>\n")
+                .raw_str("</div>\n");
+        }
+    };
+
+    md = render_avatar(env, md, address, "\" />\n");
+
+    let name_match = matches!(other, Some((_, p)) if p.display_name == profile.display_name);
+    md = compare_field_open(md, "display_name", name_match)
+        .text_string(&profile.display_name)
+        .raw_str("</div>\n")
+        .raw_str("<div class=\"compare-field\" data-field=\"username\">@")
+        .raw(profile.username.clone())
+        .raw_str("</div>\n")
+        .raw_str("<div class=\"compare-field\" data-field=\"created\">Created: ")
+        .raw(format_timestamp_style(
+            env,
+            profile.created_at,
+            DateStyle::Full,
+        ))
+        .raw_str("</div>\n");
+
+    if !profile.is_active() {
+        md = md.raw_str("<div class=\"compare-field\" data-field=\"status\">Deleted</div>\n");
+    } else if is_deactivated(env, address) {
+        md = md.raw_str("<div class=\"compare-field\" data-field=\"status\">Deactivated</div>\n");
+    } else if is_under_review(env, address) {
+        md = md.raw_str("<div class=\"compare-field\" data-field=\"status\">Hidden</div>\n");
+    }
+
+    let collisions =
+        crate::UserProfileContract::display_name_collisions(env.clone(), address.clone());
+    if collisions > 0 {
+        md = md
+            .raw_str("<div class=\"compare-field compare-warning\" data-field=\"similar_names\">")
+            .text("Similar display names: ")
+            .raw(u64_to_bytes(env, collisions as u64))
+            .raw_str("</div>\n");
+    }
+
+    for (field_name, label) in COMPARE_FIELDS.iter() {
+        let value = string_field(env, address, field_name);
+        let value = match value {
+            Some(value) => value,
+            None => continue,
+        };
+        let matched = match other {
+            Some((other_addr, _)) => {
+                string_field(env, other_addr, field_name) == Some(value.clone())
+            }
+            None => false,
+        };
+        md = compare_field_open(md, field_name, matched)
+            .text(label)
+            .text(": ")
+            .text_string(&value)
+            .raw_str("</div>\n");
+    }
+
+    md.raw_str("</div>\n")
+}
+
+/// Open a compare-page field `<div>`, marking values found in both
+/// profiles.
+fn compare_field_open<'a>(
+    md: MarkdownBuilder<'a>,
+    field: &str,
+    matched: bool,
+) -> MarkdownBuilder<'a> {
+    md.raw_str(if matched {
+        "<div class=\"compare-field compare-match\" data-field=\""
+    } else {
+        "<div class=\"compare-field\" data-field=\""
+    })
+    .raw_str(field)
+    .raw_str("\">")
+}
+
+/// Get a string field of a profile.
+fn string_field(env: &Env, address: &Address, field_name: &str) -> Option<String> {
+    match env.storage().persistent().get(&ProfileKey::Field(
+        address.clone(),
+        Symbol::new(env, field_name),
+    )) {
+        Some(FieldValue::StringField(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Warn the owner when their profile is close to being archived, with a
+/// one-click form to extend it.
+fn render_ttl_warning<'a>(
+    env: &Env,
+    md: MarkdownBuilder<'a>,
+    address: &Address,
+) -> MarkdownBuilder<'a> {
+    let estimate = crate::UserProfileContract::profile_ttl_remaining(env.clone(), address.clone());
+    let remaining = match estimate {
+        Some(ledgers) if ledgers < PROFILE_TTL_THRESHOLD => ledgers,
+        _ => return md,
+    };
+
+    md.raw_str("<div class=\"profile-ttl-warning\">\n")
+        .warning("Your profile will be archived soon unless it is extended.")
+        .text("Estimated time left: ")
+        .raw(format_duration(
+            env,
+            remaining as u64 * LEDGER_CLOSE_SECONDS,
+        ))
+        .newline()
+        .newline()
+        .raw_str("<div data-form>\n")
+        .raw_str("<input type=\"hidden\" name=\"address\" value=\"")
+        .raw(address_to_bytes(env, address))
+        .raw_str("\" />\n")
+        .form_link("Keep Profile Active", "bump_profile")
+        .raw_str("</div>\n")
+        .raw_str("</div>\n")
+        .newline()
+}
+
+/// Render registry statistics.
+fn render_stats(env: &Env) -> Bytes {
+    let storage = env.storage().instance();
+    let total: u64 = storage.get(&ProfileKey::ProfileCount).unwrap_or(0);
+    let active: u64 = storage.get(&ProfileKey::ActiveCount).unwrap_or(0);
+    let reserved: u64 = storage.get(&ProfileKey::ReservedCount).unwrap_or(0);
+    let fee = crate::UserProfileContract::current_registration_fee(env.clone());
+
+    let mut md = MarkdownBuilder::new(env)
+        .h1("Statistics")
+        .render_link("Back to Home", "/")
+        .newline()
+        .newline()
+        .text("**Total profiles:** ")
+        .raw(u64_to_bytes(env, total))
+        .newline()
+        .text("**Active profiles:** ")
+        .raw(u64_to_bytes(env, active))
+        .newline()
+        .text("**Reserved usernames:** ")
+        .raw(u64_to_bytes(env, reserved))
+        .newline()
+        .text("**Registration fee:** ")
+        .raw(i128_to_bytes(env, fee))
+        .text(" stroops")
+        .newline();
+
+    // Registrations over the last week, newest first. Skipped when the
+    // ledger time is too early to format as a date.
+    let now = env.ledger().timestamp();
+    if now >= LEGACY_TIMESTAMP_THRESHOLD + 6 * 86_400 {
+        md = md.newline().h2("Registrations, Last 7 Days");
+        let today = (now / 86_400) as u32;
+        for day in (today - 6..=today).rev() {
+            let count = crate::UserProfileContract::registrations_on(env.clone(), day);
+            md = md
+                .raw_str("- ")
+                .raw(format_timestamp_style(
+                    env,
+                    day as u64 * 86_400,
+                    DateStyle::DateOnly,
+                ))
+                .raw_str(": ")
+                .raw(u64_to_bytes(env, count))
+                .newline();
+        }
+    }
+
+    md.build()
+}
+
 /// Render help page.
 fn render_help(env: &Env) -> Bytes {
     MarkdownBuilder::new(env)
@@ -517,8 +1141,9 @@ fn render_help(env: &Env) -> Bytes {
         .h2("Standard Fields")
         .paragraph("You can set these profile fields:")
         .raw_str("- **bio** - Your biography\n")
-        .raw_str("- **avatar** - Avatar image URL\n")
+        .raw_str("- **avatar** - Avatar image URL, or a small image set with set_bytes_field\n")
         .raw_str("- **homepage** - Your website\n")
+        .raw_str("- **dweb** - Your ipfs://, ipns:// or ar:// site\n")
         .raw_str("- **location** - Where you're based\n")
         .raw_str("- **github** - GitHub username\n")
         .raw_str("- **twitter** - Twitter handle\n")
@@ -527,43 +1152,38 @@ fn render_help(env: &Env) -> Bytes {
 
 /// Render a profile card for embedding in other contracts.
 pub fn render_profile_card(env: &Env, address: &Address) -> Bytes {
+    render_profile_card_with(env, address, &CardOptions::default())
+}
+
+/// Render a profile card showing the parts selected in `options`.
+pub fn render_profile_card_with(env: &Env, address: &Address, options: &CardOptions) -> Bytes {
+    if options.text {
+        return render_profile_card_text(env, address, options.show_bio && !options.compact);
+    }
+
+    if options.compact {
+        return render_profile_card_compact(env, address);
+    }
+
     let profile: Option<Profile> = env
         .storage()
         .persistent()
         .get(&ProfileKey::Profile(address.clone()));
 
     match profile {
-        None => {
-            // Anonymous card
-            MarkdownBuilder::new(env)
-                .raw_str("<div class=\"profile-card profile-card-anonymous\">")
-                .raw_str("<span class=\"profile-address\">")
-                .raw(truncate_address_bytes(env, address))
-                .raw_str("</span>")
-                .raw_str("</div>")
-                .build()
-        }
-        Some(p) if !p.is_active() => {
-            MarkdownBuilder::new(env)
-                .raw_str("<div class=\"profile-card profile-card-deleted\">")
-                .raw_str("<span class=\"profile-deleted\">[deleted]</span>")
-                .raw_str("</div>")
-                .build()
-        }
+        None => render_anonymous_card(env, address),
+        Some(p) if !p.is_active() => MarkdownBuilder::new(env)
+            .raw_str("<div class=\"profile-card profile-card-deleted\">")
+            .raw_str("<span class=\"profile-deleted\">[deleted]</span>")
+            .raw_str("</div>")
+            .build(),
+        Some(_) if is_hidden(env, address) => render_anonymous_card(env, address),
         Some(p) => {
-            let mut md = MarkdownBuilder::new(env)
-                .raw_str("<div class=\"profile-card\">");
+            let mut md = MarkdownBuilder::new(env).raw_str("<div class=\"profile-card\">");
 
             // Avatar if present
-            if let Some(FieldValue::StringField(avatar)) = env
-                .storage()
-                .persistent()
-                .get(&ProfileKey::Field(address.clone(), Symbol::new(env, "avatar")))
-            {
-                md = md
-                    .raw_str("<img class=\"profile-avatar\" src=\"")
-                    .text_string(&avatar)
-                    .raw_str("\" />");
+            if options.show_avatar {
+                md = render_avatar(env, md, address, "\" />");
             }
 
             // Info
@@ -574,8 +1194,39 @@ pub fn render_profile_card(env: &Env, address: &Address) -> Bytes {
                 .raw_str("</span>")
                 .raw_str("<span class=\"profile-username\">@")
                 .raw(p.username.clone())
-                .raw_str("</span>")
-                .raw_str("</div>");
+                .raw_str("</span>");
+
+            if options.show_bio {
+                if let Some(FieldValue::StringField(bio)) = env
+                    .storage()
+                    .persistent()
+                    .get(&ProfileKey::Field(address.clone(), Symbol::new(env, "bio")))
+                {
+                    md = md
+                        .raw_str("<span class=\"profile-bio\">")
+                        .text_string(&bio)
+                        .raw_str("</span>");
+                }
+            }
+
+            // Join date; cards always use the short form regardless of the
+            // owner's date style. Omitted for legacy ledger-sequence values.
+            if options.show_joined && p.age_seconds(env.ledger().timestamp()).is_some() {
+                md = md
+                    .raw_str("<span class=\"profile-joined\">Joined ")
+                    .raw(format_timestamp_style(
+                        env,
+                        p.created_at,
+                        DateStyle::DateOnly,
+                    ))
+                    .raw_str("</span>");
+            }
+            if options.show_dweb {
+                if let Some((url, scheme)) = dweb_link(env, address) {
+                    md = render_dweb_link(md, url, scheme);
+                }
+            }
+            md = md.raw_str("</div>");
 
             // Link to profile (uses @profile alias for cross-contract navigation)
             md = md
@@ -589,6 +1240,95 @@ pub fn render_profile_card(env: &Env, address: &Address) -> Bytes {
     }
 }
 
+/// The `dweb` field's URL and scheme, if it holds a valid decentralized
+/// website URL.
+fn dweb_link(env: &Env, address: &Address) -> Option<(Bytes, &'static str)> {
+    let dweb = string_field(env, address, "dweb")?;
+    let url = string_to_bytes(env, &dweb);
+    let scheme = dweb_scheme(&url)?;
+    Some((url, scheme))
+}
+
+/// Render the "Visit site" link to a decentralized website.
+fn render_dweb_link<'a>(md: MarkdownBuilder<'a>, url: Bytes, scheme: &str) -> MarkdownBuilder<'a> {
+    md.raw_str("<a class=\"profile-dweb\" href=\"")
+        .raw(url)
+        .raw_str("\">Visit site <span class=\"profile-dweb-scheme\">")
+        .raw_str(scheme)
+        .raw_str("</span></a>")
+}
+
+/// Longest bio excerpt in a plain-text card, in bytes.
+const TEXT_CARD_BIO_LENGTH: u32 = 80;
+
+/// Render a profile card as one line of plain text with no HTML.
+///
+/// Unknown and hidden profiles render as the truncated address, and
+/// deleted profiles as `[deleted]`. Angle brackets in user text are
+/// dropped and line breaks become spaces.
+pub fn render_profile_card_text(env: &Env, address: &Address, show_bio: bool) -> Bytes {
+    let profile: Option<Profile> = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::Profile(address.clone()));
+
+    let profile = match profile {
+        Some(p) if !p.is_active() => return Bytes::from_slice(env, b"[deleted]"),
+        Some(p) if !is_hidden(env, address) => p,
+        _ => return truncated_address(env, address),
+    };
+
+    let mut out = plain_text(env, &string_to_bytes(env, &profile.display_name));
+    out.append(&Bytes::from_slice(env, b" (@"));
+    out.append(&profile.username);
+    out.push_back(b')');
+
+    if show_bio {
+        if let Some(bio) = string_field(env, address, "bio") {
+            let bio = plain_text(env, &string_to_bytes(env, &bio));
+            out.append(&Bytes::from_slice(env, " — ".as_bytes()));
+            out.append(&truncate_utf8(env, &bio, TEXT_CARD_BIO_LENGTH));
+        }
+    }
+
+    out.append(&Bytes::from_slice(env, " — profile:/u/".as_bytes()));
+    out.append(&profile.username);
+    out
+}
+
+/// Flatten user text for a plain-text card: line breaks become spaces
+/// and angle brackets are dropped.
+fn plain_text(env: &Env, value: &Bytes) -> Bytes {
+    let mut result = Bytes::new(env);
+    for b in single_line(env, value).iter() {
+        if b != b'<' && b != b'>' {
+            result.push_back(b);
+        }
+    }
+    result
+}
+
+/// Render an anonymous card showing only the truncated address.
+fn render_anonymous_card(env: &Env, address: &Address) -> Bytes {
+    MarkdownBuilder::new(env)
+        .raw_str("<div class=\"profile-card profile-card-anonymous\">")
+        .raw_str("<span class=\"profile-address\">")
+        .raw(truncated_address(env, address))
+        .raw_str("</span>")
+        .raw_str("</div>")
+        .build()
+}
+
+/// Render a profile card, falling back to the anonymous card when the
+/// embed policy does not approve `embedder`.
+pub fn render_profile_card_for(env: &Env, address: &Address, embedder: Option<Address>) -> Bytes {
+    if crate::UserProfileContract::is_embedder_allowed(env.clone(), embedder) {
+        render_profile_card(env, address)
+    } else {
+        render_anonymous_card(env, address)
+    }
+}
+
 /// Render a compact profile card (for author attribution).
 pub fn render_profile_card_compact(env: &Env, address: &Address) -> Bytes {
     render_profile_card_compact_with_return(env, address, None)
@@ -610,7 +1350,7 @@ pub fn render_profile_card_compact_with_return(
         .get(&ProfileKey::Profile(address.clone()));
 
     match profile {
-        Some(p) if p.is_active() => {
+        Some(p) if p.is_active() && !is_hidden(env, address) => {
             // Uses @profile alias for cross-contract navigation
             let mut md = MarkdownBuilder::new(env)
                 .raw_str("<span class=\"profile-compact\">")
@@ -630,13 +1370,11 @@ pub fn render_profile_card_compact_with_return(
                 .raw_str("</span>")
                 .build()
         }
-        _ => {
-            MarkdownBuilder::new(env)
-                .raw_str("<span class=\"profile-compact profile-anonymous\">")
-                .raw(truncate_address_bytes(env, address))
-                .raw_str("</span>")
-                .build()
-        }
+        _ => MarkdownBuilder::new(env)
+            .raw_str("<span class=\"profile-compact profile-anonymous\">")
+            .raw(truncated_address(env, address))
+            .raw_str("</span>")
+            .build(),
     }
 }
 
@@ -647,18 +1385,166 @@ pub fn render_username(env: &Env, address: &Address) -> Bytes {
         .persistent()
         .get(&ProfileKey::Profile(address.clone()));
 
+    match profile {
+        Some(p) if p.is_active() && !is_hidden(env, address) => {
+            MarkdownBuilder::new(env).text("@").raw(p.username).build()
+        }
+        _ => MarkdownBuilder::new(env)
+            .raw(truncated_address(env, address))
+            .build(),
+    }
+}
+
+/// Render the username, falling back to the truncated address when the
+/// embed policy does not approve `embedder`.
+pub fn render_username_for(env: &Env, address: &Address, embedder: Option<Address>) -> Bytes {
+    if crate::UserProfileContract::is_embedder_allowed(env.clone(), embedder) {
+        render_username(env, address)
+    } else {
+        MarkdownBuilder::new(env)
+            .raw(truncated_address(env, address))
+            .build()
+    }
+}
+
+/// Maximum length of the description in a profile metadata block, in bytes.
+const META_DESCRIPTION_LENGTH: u32 = 160;
+
+/// Render a link-preview metadata block for a profile.
+///
+/// Emits `title`, `description` and `image` lines in `key: value` form
+/// for a frontend to translate into OpenGraph tags. The description is
+/// the bio, truncated to `META_DESCRIPTION_LENGTH` bytes. Lines without
+/// a value are omitted, and unknown, deleted or hidden profiles produce
+/// an empty block.
+pub fn render_profile_meta(env: &Env, username: &Bytes) -> Bytes {
+    let address: Option<Address> = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::Username(username.clone()));
+    let profile: Option<Profile> = address.as_ref().and_then(|addr| {
+        env.storage()
+            .persistent()
+            .get(&ProfileKey::Profile(addr.clone()))
+    });
+
+    let (address, profile) = match (address, profile) {
+        (Some(addr), Some(p)) if p.is_active() && !is_hidden(env, &addr) => (addr, p),
+        _ => return Bytes::new(env),
+    };
+
+    let mut out = Bytes::from_slice(env, b"title: ");
+    out.append(&single_line(
+        env,
+        &string_to_bytes(env, &profile.display_name),
+    ));
+    out.push_back(b'\n');
+
+    if let Some(FieldValue::StringField(bio)) = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::Field(address.clone(), Symbol::new(env, "bio")))
+    {
+        out.append(&Bytes::from_slice(env, b"description: "));
+        let bio = single_line(env, &string_to_bytes(env, &bio));
+        out.append(&truncate_utf8(env, &bio, META_DESCRIPTION_LENGTH));
+        out.push_back(b'\n');
+    }
+
+    if let Some(FieldValue::StringField(url)) = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::Field(address, Symbol::new(env, "avatar")))
+    {
+        out.append(&Bytes::from_slice(env, b"image: "));
+        out.append(&single_line(env, &string_to_bytes(env, &url)));
+        out.push_back(b'\n');
+    }
+
+    out
+}
+
+/// Replace line breaks with spaces so a value stays on one line.
+fn single_line(env: &Env, value: &Bytes) -> Bytes {
+    let mut result = Bytes::new(env);
+    for b in value.iter() {
+        result.push_back(if b == b'\n' || b == b'\r' { b' ' } else { b });
+    }
+    result
+}
+
+/// Backslash-escape markdown and HTML metacharacters so text renders
+/// literally.
+fn escape_markdown(env: &Env, text: &Bytes) -> Bytes {
+    let mut result = Bytes::new(env);
+    for b in text.iter() {
+        let special = matches!(
+            b,
+            b'\\' | b'[' | b']' | b'(' | b')' | b'<' | b'>' | b'*' | b'_' | b'`' | b'#' | b'!'
+        );
+        if special {
+            result.push_back(b'\\');
+        }
+        result.push_back(b);
+    }
+    result
+}
+
+/// Truncate UTF-8 text to at most `max` bytes followed by "...", without
+/// splitting a character.
+fn truncate_utf8(env: &Env, text: &Bytes, max: u32) -> Bytes {
+    if text.len() <= max {
+        return text.clone();
+    }
+
+    // Back up past continuation bytes to a character boundary
+    let mut end = max;
+    while end > 0 && text.get(end).map(|b| b & 0xC0 == 0x80).unwrap_or(false) {
+        end -= 1;
+    }
+
+    let mut result = text.slice(0..end);
+    result.append(&Bytes::from_slice(env, b"..."));
+    result
+}
+
+/// Render the viewer chip for a host contract's nav bar.
+///
+/// Viewers with a profile get their avatar and display name linking to
+/// their profile, connected viewers without one get their truncated
+/// address and a registration link, and anonymous viewers get a
+/// "Not connected" label.
+pub fn render_viewer_chip(env: &Env, viewer: &Option<Address>) -> Bytes {
+    let viewer = match viewer {
+        Some(viewer) => viewer,
+        None => {
+            return MarkdownBuilder::new(env)
+                .raw_str("<span class=\"profile-chip profile-chip-guest\">Not connected</span>")
+                .build();
+        }
+    };
+
+    let profile: Option<Profile> = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::Profile(viewer.clone()));
+
     match profile {
         Some(p) if p.is_active() => {
-            MarkdownBuilder::new(env)
-                .text("@")
+            let mut md = MarkdownBuilder::new(env).raw_str("<span class=\"profile-chip\">");
+            md = render_avatar(env, md, viewer, "\" alt=\"\" />");
+            md.raw_str("<a href=\"render:@profile:/u/")
                 .raw(p.username)
+                .raw_str("\">")
+                .text_string(&p.display_name)
+                .raw_str("</a></span>")
                 .build()
         }
-        _ => {
-            MarkdownBuilder::new(env)
-                .raw(truncate_address_bytes(env, address))
-                .build()
-        }
+        _ => MarkdownBuilder::new(env)
+            .raw_str("<span class=\"profile-chip profile-chip-anonymous\">")
+            .raw(truncated_address(env, viewer))
+            .raw_str(" <a href=\"render:@profile:/register\">Create Profile</a></span>")
+            .build(),
     }
 }
 
@@ -748,175 +1634,335 @@ fn render_nav_link_with_return(
 
 // ========== Helper Functions ==========
 
-/// Convert Address to Bytes for display.
-fn address_to_bytes(env: &Env, address: &Address) -> Bytes {
-    // Convert Address to String, then to Bytes
-    let addr_string = address.to_string();
-    soroban_render_sdk::bytes::string_to_bytes(env, &addr_string)
+/// Append an avatar `<img>` tag, ending with `tail` after the `src` value.
+///
+/// Bytes avatars up to `MAX_AVATAR_BYTES` in a recognised image format
+/// are embedded as a base64 `data:` URI, and string avatars are used as
+/// URLs. Otherwise a Gravatar URL is built from the owner's email hash,
+/// which serves an identicon for unknown hashes.
+fn render_avatar<'a>(
+    env: &Env,
+    md: MarkdownBuilder<'a>,
+    address: &Address,
+    tail: &str,
+) -> MarkdownBuilder<'a> {
+    let avatar: Option<FieldValue> = env.storage().persistent().get(&ProfileKey::Field(
+        address.clone(),
+        Symbol::new(env, "avatar"),
+    ));
+
+    match avatar {
+        Some(FieldValue::StringField(url)) => md
+            .raw_str("<img class=\"profile-avatar\" src=\"")
+            .text_string(&url)
+            .raw_str(tail),
+        Some(FieldValue::BytesField(data)) if data.len() <= MAX_AVATAR_BYTES => {
+            match image_mime_type(&data) {
+                Some(mime) => md
+                    .raw_str("<img class=\"profile-avatar\" src=\"data:")
+                    .raw_str(mime)
+                    .raw_str(";base64,")
+                    .raw(base64_encode(env, &data))
+                    .raw_str(tail),
+                None => render_gravatar(env, md, address, tail),
+            }
+        }
+        _ => render_gravatar(env, md, address, tail),
+    }
 }
 
-/// Truncate an address for display (returns Bytes).
-fn truncate_address_bytes(env: &Env, address: &Address) -> Bytes {
-    let full_bytes = address_to_bytes(env, address);
-    let len = full_bytes.len();
+/// Append a Gravatar `<img>` tag if the owner set an email hash.
+fn render_gravatar<'a>(
+    env: &Env,
+    md: MarkdownBuilder<'a>,
+    address: &Address,
+    tail: &str,
+) -> MarkdownBuilder<'a> {
+    let hash: Option<Bytes> = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::AvatarEmailHash(address.clone()));
+
+    match hash {
+        Some(hash) => md
+            .raw_str("<img class=\"profile-avatar\" src=\"https://gravatar.com/avatar/")
+            .raw(bytes_to_hex(env, &hash))
+            .raw_str("?d=identicon")
+            .raw_str(tail),
+        None => md,
+    }
+}
 
-    if len <= 12 {
-        // Short enough, return as-is
-        return full_bytes;
+/// Detect an image MIME type from its leading magic bytes.
+fn image_mime_type(data: &Bytes) -> Option<&'static str> {
+    let starts_with = |offset: u32, magic: &[u8]| {
+        magic
+            .iter()
+            .enumerate()
+            .all(|(i, b)| data.get(offset + i as u32) == Some(*b))
+    };
+
+    if starts_with(0, b"\x89PNG") {
+        Some("image/png")
+    } else if starts_with(0, b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if starts_with(0, b"GIF8") {
+        Some("image/gif")
+    } else if starts_with(0, b"RIFF") && starts_with(8, b"WEBP") {
+        Some("image/webp")
+    } else {
+        None
     }
+}
 
-    // Truncate: GXXX...XXXX
-    let mut result = Bytes::new(env);
+/// Encode bytes as standard padded base64.
+fn base64_encode(env: &Env, data: &Bytes) -> Bytes {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
-    // First 4 characters
-    for i in 0..4 {
-        if let Some(c) = full_bytes.get(i) {
-            result.push_back(c);
-        }
+    let mut result = Bytes::new(env);
+    let len = data.len();
+    let mut i = 0;
+    while i < len {
+        let b0 = data.get(i).unwrap();
+        let b1 = data.get(i + 1);
+        let b2 = data.get(i + 2);
+        let n = ((b0 as u32) << 16) | ((b1.unwrap_or(0) as u32) << 8) | b2.unwrap_or(0) as u32;
+
+        let chunk = [
+            ALPHABET[(n >> 18) as usize & 63],
+            ALPHABET[(n >> 12) as usize & 63],
+            if b1.is_some() {
+                ALPHABET[(n >> 6) as usize & 63]
+            } else {
+                b'='
+            },
+            if b2.is_some() {
+                ALPHABET[n as usize & 63]
+            } else {
+                b'='
+            },
+        ];
+        result.extend_from_slice(&chunk);
+        i += 3;
     }
+    result
+}
 
-    // Ellipsis
-    result.push_back(b'.');
-    result.push_back(b'.');
-    result.push_back(b'.');
+/// Check whether an address has an active profile.
+fn has_active_profile(env: &Env, address: &Address) -> bool {
+    let profile: Option<Profile> = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::Profile(address.clone()));
 
-    // Last 4 characters
-    for i in (len - 4)..len {
-        if let Some(c) = full_bytes.get(i) {
-            result.push_back(c);
-        }
-    }
+    profile.map(|p| p.is_active()).unwrap_or(false)
+}
 
-    result
+/// Check whether a profile is hidden from others, either pending
+/// moderator review or deactivated by its owner.
+fn is_hidden(env: &Env, address: &Address) -> bool {
+    is_under_review(env, address) || is_deactivated(env, address)
 }
 
-/// Convert u64 to Bytes.
-fn u64_to_bytes(env: &Env, n: u64) -> Bytes {
-    if n == 0 {
-        return Bytes::from_slice(env, b"0");
-    }
+/// Check whether a profile is hidden pending moderator review.
+fn is_under_review(env: &Env, address: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ProfileKey::Hidden(address.clone()))
+}
 
-    let mut buffer = [0u8; 20];
-    let mut idx = 20;
-    let mut num = n;
+/// Check whether a profile is deactivated by its owner.
+fn is_deactivated(env: &Env, address: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ProfileKey::Deactivated(address.clone()))
+}
 
-    while num > 0 {
-        idx -= 1;
-        buffer[idx] = b'0' + (num % 10) as u8;
-        num /= 10;
-    }
+/// Check whether a field's value matches its verified link handle.
+fn is_verified(env: &Env, address: &Address, field_name: &str, value: &String) -> bool {
+    let link: Option<(String, BytesN<32>)> = env.storage().persistent().get(
+        &ProfileKey::VerifiedLink(address.clone(), Symbol::new(env, field_name)),
+    );
 
-    Bytes::from_slice(env, &buffer[idx..])
+    link.map(|(handle, _)| handle == *value).unwrap_or(false)
 }
 
-/// Convert i128 to Bytes.
-fn i128_to_bytes(env: &Env, n: i128) -> Bytes {
-    if n == 0 {
-        return Bytes::from_slice(env, b"0");
+/// Render a profile's Contact section: the `contact` blurb, a mailto link
+/// if the owner opted in with `show_email`, and contact-ish links.
+fn render_contact<'a>(
+    env: &Env,
+    mut md: MarkdownBuilder<'a>,
+    address: &Address,
+    links: &Vec<(String, String)>,
+) -> MarkdownBuilder<'a> {
+    let blurb = string_field(env, address, "contact");
+    let show_email: bool = env
+        .storage()
+        .persistent()
+        .get(&ProfileKey::ShowEmail(address.clone()))
+        .unwrap_or(false);
+    let email = string_field(env, address, "email")
+        .filter(|_| show_email)
+        .map(|email| string_to_bytes(env, &email))
+        .filter(is_mailto_safe);
+
+    let mut contact_links = Vec::new(env);
+    for (label, url) in links.iter() {
+        let label = string_to_bytes(env, &label);
+        if CONTACT_LINK_LABELS
+            .iter()
+            .any(|needle| contains_ignore_case(&label, needle))
+        {
+            contact_links.push_back((label, url));
+        }
     }
 
-    let is_negative = n < 0;
-    let mut num = if is_negative { -n } else { n } as u128;
-    let mut buffer = [0u8; 40];
-    let mut idx = 40;
-
-    while num > 0 {
-        idx -= 1;
-        buffer[idx] = b'0' + (num % 10) as u8;
-        num /= 10;
+    if blurb.is_none() && email.is_none() && contact_links.is_empty() {
+        return md;
     }
 
-    if is_negative {
-        idx -= 1;
-        buffer[idx] = b'-';
+    md = md.newline().raw_str("### Contact\n\n");
+    if let Some(blurb) = blurb {
+        md = md
+            .raw(escape_markdown(env, &string_to_bytes(env, &blurb)))
+            .raw_str("\n\n");
+    }
+    if let Some(email) = email {
+        md = md
+            .raw_str("- [")
+            .raw(escape_markdown(env, &email))
+            .raw_str("](mailto:")
+            .raw(email)
+            .raw_str(")\n");
     }
+    for (label, url) in contact_links.iter() {
+        md = md
+            .raw_str("- [")
+            .raw(escape_markdown(env, &single_line(env, &label)))
+            .raw_str("](")
+            .text_string(&url)
+            .raw_str(")\n");
+    }
+    md
+}
 
-    Bytes::from_slice(env, &buffer[idx..])
+/// Check that an email address can be placed in a mailto URL as is.
+fn is_mailto_safe(email: &Bytes) -> bool {
+    email.iter().any(|b| b == b'@')
+        && email
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'@' | b'.' | b'-' | b'_' | b'+'))
 }
 
-/// Format a Unix timestamp as a readable date string.
-/// Returns format: "YYYY-MM-DD HH:MM:SS UTC"
-fn format_timestamp(env: &Env, timestamp: u64) -> Bytes {
-    // Handle legacy ledger sequence numbers (small values)
-    // Unix timestamps for 2024+ are ~1700000000+
-    if timestamp < 1_000_000_000 {
-        // This is likely a ledger sequence, not a timestamp
-        let mut result = Bytes::from_slice(env, b"Ledger ");
-        result.append(&u64_to_bytes(env, timestamp));
-        return result;
+/// ASCII case-insensitive substring search.
+fn contains_ignore_case(haystack: &Bytes, needle: &[u8]) -> bool {
+    let len = needle.len() as u32;
+    if haystack.len() < len {
+        return false;
     }
+    (0..=haystack.len() - len).any(|start| {
+        needle.iter().enumerate().all(|(i, n)| {
+            haystack
+                .get_unchecked(start + i as u32)
+                .to_ascii_lowercase()
+                == *n
+        })
+    })
+}
 
-    // Convert Unix timestamp to date components
-    // Days since Unix epoch
-    let total_seconds = timestamp;
-    let total_minutes = total_seconds / 60;
-    let total_hours = total_minutes / 60;
-    let total_days = total_hours / 24;
-
-    let seconds = (total_seconds % 60) as u8;
-    let minutes = (total_minutes % 60) as u8;
-    let hours = (total_hours % 24) as u8;
-
-    // Calculate year, month, day from days since epoch (Jan 1, 1970)
-    let (year, month, day) = days_to_date(total_days as i64);
-
-    // Format: "YYYY-MM-DD HH:MM:SS UTC"
-    let mut buffer = [0u8; 24];
-
-    // Year (4 digits)
-    buffer[0] = b'0' + ((year / 1000) % 10) as u8;
-    buffer[1] = b'0' + ((year / 100) % 10) as u8;
-    buffer[2] = b'0' + ((year / 10) % 10) as u8;
-    buffer[3] = b'0' + (year % 10) as u8;
-    buffer[4] = b'-';
-
-    // Month (2 digits)
-    buffer[5] = b'0' + ((month / 10) % 10) as u8;
-    buffer[6] = b'0' + (month % 10) as u8;
-    buffer[7] = b'-';
-
-    // Day (2 digits)
-    buffer[8] = b'0' + ((day / 10) % 10) as u8;
-    buffer[9] = b'0' + (day % 10) as u8;
-    buffer[10] = b' ';
+/// Truncate an address using the admin-configured lengths.
+fn truncated_address(env: &Env, address: &Address) -> Bytes {
+    let (prefix, suffix) = env
+        .storage()
+        .instance()
+        .get(&ProfileKey::AddrTruncate)
+        .unwrap_or((DEFAULT_TRUNCATE_PREFIX, DEFAULT_TRUNCATE_SUFFIX));
+    truncate_address_bytes_with(env, address, prefix, suffix)
+}
 
-    // Hours (2 digits)
-    buffer[11] = b'0' + ((hours / 10) % 10);
-    buffer[12] = b'0' + (hours % 10);
-    buffer[13] = b':';
+/// Get a profile owner's preferred date style.
+fn date_style(env: &Env, address: &Address) -> DateStyle {
+    env.storage()
+        .persistent()
+        .get(&ProfileKey::DateStyle(address.clone()))
+        .unwrap_or(DateStyle::Full)
+}
 
-    // Minutes (2 digits)
-    buffer[14] = b'0' + ((minutes / 10) % 10);
-    buffer[15] = b'0' + (minutes % 10);
-    buffer[16] = b':';
+/// Check whether new registrations are paused.
+fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&ProfileKey::Paused)
+        .unwrap_or(false)
+}
 
-    // Seconds (2 digits)
-    buffer[17] = b'0' + ((seconds / 10) % 10);
-    buffer[18] = b'0' + (seconds % 10);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        let env = Env::default();
+        let encode = |input: &[u8]| base64_encode(&env, &Bytes::from_slice(&env, input));
+
+        assert_eq!(encode(b""), Bytes::new(&env));
+        assert_eq!(encode(b"f"), Bytes::from_slice(&env, b"Zg=="));
+        assert_eq!(encode(b"fo"), Bytes::from_slice(&env, b"Zm8="));
+        assert_eq!(encode(b"foo"), Bytes::from_slice(&env, b"Zm9v"));
+        assert_eq!(encode(b"foobar"), Bytes::from_slice(&env, b"Zm9vYmFy"));
+        assert_eq!(encode(&[0xff, 0xfe]), Bytes::from_slice(&env, b"//4="));
+    }
 
-    // " UTC"
-    buffer[19] = b' ';
-    buffer[20] = b'U';
-    buffer[21] = b'T';
-    buffer[22] = b'C';
+    #[test]
+    fn test_canonical_username() {
+        let env = Env::default();
+        let canonical = |input: &[u8]| canonical_username(&Bytes::from_slice(&env, input));
+
+        assert_eq!(canonical(b"alice001"), Bytes::from_slice(&env, b"alice001"));
+        assert_eq!(
+            canonical(b"Alice_B001"),
+            Bytes::from_slice(&env, b"alice_b001")
+        );
+        assert_eq!(
+            canonical(b"alice001//"),
+            Bytes::from_slice(&env, b"alice001")
+        );
+        assert_eq!(canonical(b"/"), Bytes::new(&env));
+    }
 
-    Bytes::from_slice(env, &buffer[..23])
-}
+    #[test]
+    fn test_escape_markdown() {
+        let env = Env::default();
+        let escape =
+            |input: &str| escape_markdown(&env, &Bytes::from_slice(&env, input.as_bytes()));
+
+        assert_eq!(escape("My Channel"), Bytes::from_slice(&env, b"My Channel"));
+        assert_eq!(escape("[x](y)"), Bytes::from_slice(&env, b"\\[x\\]\\(y\\)"));
+        assert_eq!(
+            escape("<b>*hi*</b>"),
+            Bytes::from_slice(&env, b"\\<b\\>\\*hi\\*\\</b\\>")
+        );
+    }
 
-/// Convert days since Unix epoch to (year, month, day).
-fn days_to_date(days: i64) -> (i32, u8, u8) {
-    // Algorithm based on Howard Hinnant's date algorithms
-    // http://howardhinnant.github.io/date_algorithms.html
-    let z = days + 719468;
-    let era = if z >= 0 { z } else { z - 146096 } / 146097;
-    let doe = (z - era * 146097) as u32; // day of era
-    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // year of era
-    let y = yoe as i64 + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // day of year
-    let mp = (5 * doy + 2) / 153; // month index
-    let d = doy - (153 * mp + 2) / 5 + 1;
-    let m = if mp < 10 { mp + 3 } else { mp - 9 };
-    let year = if m <= 2 { y + 1 } else { y };
-
-    (year as i32, m as u8, d as u8)
+    #[test]
+    fn test_truncate_utf8() {
+        let env = Env::default();
+        let truncate =
+            |input: &str, max| truncate_utf8(&env, &Bytes::from_slice(&env, input.as_bytes()), max);
+
+        assert_eq!(truncate("hello", 5), Bytes::from_slice(&env, b"hello"));
+        assert_eq!(
+            truncate("hello world", 5),
+            Bytes::from_slice(&env, b"hello...")
+        );
+        // "é" is two bytes; cutting inside it backs up to the boundary
+        assert_eq!(
+            truncate("caf\u{e9}s", 4),
+            Bytes::from_slice(&env, b"caf...")
+        );
+        assert_eq!(
+            truncate("caf\u{e9}s", 5),
+            Bytes::from_slice(&env, "caf\u{e9}...".as_bytes())
+        );
+    }
 }
@@ -11,28 +11,318 @@ pub enum ProfileKey {
     /// Contract administrator address.
     Admin,
 
+    /// Address allowed to set a new admin through `reinit`, fixed at
+    /// `init_with_config`.
+    RecoveryAddress,
+
     /// Total count of registered profiles.
     ProfileCount,
 
+    /// Number of profiles that are neither deleted nor banned.
+    ActiveCount,
+
+    /// Number of permanently reserved usernames (`ReservedUsername`).
+    ReservedCount,
+
+    /// Maps registration order (0-based) to username.
+    /// Slots are assigned from the profile count at registration.
+    ProfileIndex(u64),
+
+    /// Creation time of a `ProfileIndex` slot emptied by a hard delete,
+    /// kept so time-ordered searches over the index stay sorted.
+    PurgedIndex(u64),
+
     /// Maps username (Bytes) to owner Address.
     /// Used to enforce username uniqueness.
     Username(Bytes),
 
+    /// Secondary usernames (aliases) held by a profile.
+    /// Each alias also has a `Username` entry pointing at the owner.
+    Aliases(Address),
+
+    /// Maps a former username to the profile's current username, so old
+    /// links keep resolving after a rename.
+    UsernameAlias(Bytes),
+
+    /// Former usernames of a profile that still redirect, oldest first.
+    FormerUsernames(Address),
+
     /// Maps Address to Profile struct.
     /// Primary storage for profile data.
     Profile(Address),
 
+    /// Ledger sequence at which a profile was registered.
+    /// Kept beside the Profile to avoid changing its layout.
+    CreatedAtSeq(Address),
+
+    /// Schema version of a profile's stored data. Kept beside the Profile,
+    /// since adding a field would make existing entries undecodable.
+    ProfileSchema(Address),
+
+    /// Ledger sequence at which a profile's TTL was last extended to
+    /// `PROFILE_TTL_EXTEND`. Contracts cannot read entry TTLs at runtime,
+    /// so this is used to estimate the remaining TTL.
+    TtlExtendedAt(Address),
+
+    /// Maps (Address, platform) to a verified external handle and the
+    /// hash of its off-chain proof, as (String, BytesN<32>).
+    VerifiedLink(Address, Symbol),
+
+    /// Platforms with a `VerifiedLink` entry for a profile, so they can
+    /// be bumped and purged without relying on the field index.
+    VerifiedLinks(Address),
+
+    /// Gravatar email hash (MD5 or SHA-256) used as an avatar fallback.
+    /// The email itself is never stored.
+    AvatarEmailHash(Address),
+
+    /// Ordered (label, url) links shown on a profile page.
+    Links(Address),
+
+    /// Addresses whose display name normalizes to the given form, used to
+    /// find look-alike names.
+    DisplayNameIndex(Bytes),
+
     /// Maps (Address, field_name) to FieldValue.
     /// Used for extensible profile fields.
     Field(Address, Symbol),
 
+    /// Marks a field as write-once; it can no longer be set or removed.
+    FrozenField(Address, Symbol),
+
+    /// Field whose previous values are kept as evidence for disputes.
+    AuditedField(Symbol),
+
+    /// Previous values of an audited field with the timestamp each was
+    /// replaced, oldest first: Vec<(FieldValue, u64)>.
+    FieldHistory(Address, Symbol),
+
+    /// Fields with a `FieldHistory` entry for a profile. History outlives
+    /// the field itself, so this is kept apart from the field index.
+    HistoryFields(Address),
+
+    /// Marks a profile as banned by the admin, as opposed to self-deleted.
+    Banned(Address),
+
+    /// Preferred locale of a profile owner.
+    Locale(Address),
+
+    /// Number of times a profile has been changed, for cache invalidation.
+    Generation(Address),
+
+    /// Timestamp of a profile's last display name change.
+    DisplayNameChangedAt(Address),
+
+    /// Seconds that must pass between display name changes (0 = disabled).
+    DisplayNameCooldown,
+
+    /// Whether the owner's email is linked on their profile page.
+    ShowEmail(Address),
+
+    /// Owner's preferred `DateStyle` for their profile page.
+    DateStyle(Address),
+
+    /// `CardOptions` a host app registered for cards rendered inside it.
+    AppCardPrefs(Address),
+
     /// Reserved usernames that cannot be registered.
     ReservedUsername(Bytes),
 
+    /// Usernames reserved until a Unix timestamp, after which they
+    /// become available for registration again.
+    ReservedUntil(Bytes),
+
+    /// Reserved username assigned to a beneficiary who may claim it
+    /// once a condition holds: (beneficiary, condition).
+    ReservedFor(Bytes),
+
+    /// Usernames the admin discourages but does not block.
+    SoftReserved(Bytes),
+
+    /// Whether new registrations are paused.
+    Paused,
+
+    /// Username prefixes delegated to registrar contracts, as
+    /// (prefix, registrar) pairs.
+    Namespaces,
+
+    /// Whether registrations that look like a registered username are
+    /// rejected.
+    BlockConfusables,
+
+    /// Confusable-folded form of a registered username -> its owner.
+    ConfusableKey(Bytes),
+
+    /// Whether only invited addresses may register.
+    InviteOnly,
+
+    /// Address invited to register while registration is invite-only.
+    InvitedAddress(Address),
+
+    /// Whether registrations must use a display name that differs from
+    /// the username.
+    RequireDistinctDisplayName,
+
     /// Optional registration fee in stroops.
     RegistrationFee,
+
+    /// Global registration throttle:
+    /// (max_per_window, window_seconds, surge_multiplier).
+    RegistrationThrottle,
+
+    /// Current throttle window: (window_start, registrations).
+    ThrottleWindow,
+
+    /// Number of registrations on a UTC day, keyed by days since the
+    /// Unix epoch.
+    DailyRegistrations(u32),
+
+    /// Internal moderation note attached to a profile.
+    AdminNote(Address),
+
+    /// Maps (target, reporter) to an open report's (reason, timestamp).
+    Report(Address, Address),
+
+    /// Addresses with an open report against a profile.
+    Reporters(Address),
+
+    /// Number of open reports that hides a profile pending review (0 = disabled).
+    AutoHideThreshold,
+
+    /// Marks a profile as hidden pending moderator review.
+    Hidden(Address),
+
+    /// Marks a profile as temporarily deactivated by its owner.
+    Deactivated(Address),
+
+    /// Field (name, value) applied to every newly registered profile.
+    AutoField,
+
+    /// Optional Symbol prepended as the leading topic on all events.
+    EventPrefix,
+
+    /// Admin-configured (prefix, suffix) lengths for truncated addresses.
+    AddrTruncate,
+
+    /// Registration source approved by the admin for attribution.
+    ApprovedSource(Symbol),
+
+    /// App or source a profile was registered through.
+    Source(Address),
+
+    /// Number of registrations attributed to a source.
+    SourceCount(Symbol),
+
+    /// Number of `register_from` calls made with an app id, whether or
+    /// not the id is an approved source.
+    AppRegistrationCount(Symbol),
+
+    /// Embed policy for render components: `open` (default, unset) or
+    /// `allowlist`.
+    EmbedPolicy,
+
+    /// Contract approved to embed full profile cards in allowlist mode.
+    AllowedEmbedder(Address),
+
+    /// Admin-configured (min, max) username length bounds.
+    UsernameBounds,
+
+    /// External username validator contract and whether it replaces
+    /// the built-in rules: (validator, replace_builtin).
+    UsernameValidator,
+
+    /// Contract notified after each profile transfer.
+    TransferHook,
+
+    /// Whether only whitelisted usernames may be registered.
+    WhitelistMode,
+
+    /// Username pre-approved for registration in whitelist mode.
+    WhitelistedUsername(Bytes),
+
+    /// Admin-configured (max_field_len, max_fields) limits.
+    FieldLimits,
+
+    /// Names of the fields set on a profile.
+    /// Used to enforce the field cap and to purge fields on hard delete.
+    FieldIndex(Address),
+
+    /// Maps username to its `ProfileIndex` slot.
+    IndexSlot(Bytes),
+
+    /// Number of entries in the moderation log.
+    ModLogCount,
+
+    /// Moderation log entry by index.
+    ModLog(u64),
+
+    /// Minimum profile age, in seconds, before it can be transferred.
+    MinTransferAge,
+
+    /// Number of entries in a first-letter directory bucket.
+    DirectoryCount(u32),
+
+    /// Maps (first letter, page) to the owner addresses on that page.
+    /// Used to render the browsable `/dir/{letter}` listing.
+    DirectoryPage(u32, u32),
+
+    /// Maps username to its slot within its directory bucket.
+    DirectorySlot(Bytes),
 }
 
 /// Time-to-live for profile data in ledger entries.
 pub const PROFILE_TTL_THRESHOLD: u32 = 518400; // ~30 days
 pub const PROFILE_TTL_EXTEND: u32 = 2592000; // ~150 days
+
+/// Approximate ledger close time, used to convert TTLs to durations.
+pub const LEDGER_CLOSE_SECONDS: u64 = 5;
+
+/// Maximum number of open reports held against a single profile.
+pub const MAX_OPEN_REPORTS: u32 = 50;
+
+/// Number of former usernames that keep redirecting after renames.
+pub const MAX_FORMER_USERNAMES: u32 = 3;
+
+/// Maximum number of links a profile may hold.
+pub const MAX_LINKS: u32 = 10;
+
+/// Number of previous values kept per audited field.
+pub const MAX_FIELD_HISTORY: u32 = 5;
+
+/// Maximum number of addresses indexed under one normalized display
+/// name. Later registrations with the same form are not indexed.
+pub const MAX_DISPLAY_NAME_INDEX: u32 = 50;
+
+/// Maximum number of aliases a profile may hold.
+pub const MAX_ALIASES: u32 = 5;
+
+/// Maximum number of username prefixes delegated to registrars.
+pub const MAX_NAMESPACES: u32 = 10;
+
+/// Maximum number of addresses accepted by batch lookups.
+pub const MAX_BATCH_SIZE: u32 = 50;
+
+/// Maximum number of addresses accepted by `eligibility_check` and
+/// `count_eligible`. Each address reads one profile entry, and a
+/// transaction footprint holds at most 100 entries including the
+/// contract's own instance and code.
+pub const MAX_ELIGIBILITY_BATCH: u32 = 90;
+
+/// Maximum number of directory slots `export_page` covers per call.
+/// Each profile reads about six entries plus one per field, so a page of
+/// profiles with a few fields each stays under the 100-entry footprint.
+pub const MAX_EXPORT_PAGE: u32 = 5;
+
+/// Maximum number of fields accepted by `register_full`.
+pub const MAX_REGISTER_FIELDS: u32 = 10;
+
+/// Number of most recent registrations searched by time-window counts.
+/// Each search probe reads three entries, so two binary searches over
+/// 2^16 slots stay within the per-invocation footprint limit.
+pub const MAX_WINDOW_SCAN: u64 = 65_536;
+
+/// Maximum number of days `registrations_between` returns per call.
+pub const MAX_STATS_DAYS: u32 = 90;
+
+/// Number of entries stored per directory page.
+pub const DIRECTORY_PAGE_SIZE: u32 = 50;
@@ -8,7 +8,7 @@
 //! - Ends with 3 digits
 //! - Examples: alice001, bob_smith123, crypto_fan_999
 
-use soroban_sdk::Bytes;
+use soroban_sdk::{Bytes, Env};
 
 /// Minimum username length (3 letters + 3 digits).
 pub const MIN_USERNAME_LENGTH: u32 = 6;
@@ -16,12 +16,46 @@ pub const MIN_USERNAME_LENGTH: u32 = 6;
 /// Maximum username length.
 pub const MAX_USERNAME_LENGTH: u32 = 17;
 
+/// Largest maximum length an admin may configure.
+pub const USERNAME_LENGTH_CEILING: u32 = 32;
+
 /// Minimum number of leading letters required.
 pub const MIN_LEADING_LETTERS: u32 = 3;
 
 /// Number of trailing digits required.
 pub const TRAILING_DIGITS: u32 = 3;
 
+/// Maximum length of a decentralized website URL, in bytes.
+pub const MAX_DWEB_URL_LENGTH: u32 = 256;
+
+/// Maximum length of a profile link URL, in bytes.
+pub const MAX_LINK_URL_LENGTH: u32 = 256;
+
+/// Maximum length of a profile link label, in bytes.
+pub const MAX_LINK_LABEL_LENGTH: u32 = 64;
+
+/// Maximum length of the `contact` field, in bytes.
+pub const MAX_CONTACT_LENGTH: u32 = 280;
+
+/// URL schemes accepted for profile links, besides the dweb schemes.
+const LINK_SCHEMES: [&str; 2] = ["https", "http"];
+
+/// URL schemes accepted for the `dweb` field.
+const DWEB_SCHEMES: [&str; 3] = ["ipfs", "ipns", "ar"];
+
+/// Default maximum length of a string or bytes field value, in bytes.
+pub const DEFAULT_MAX_FIELD_LENGTH: u32 = 1024;
+
+/// Default maximum number of fields per profile.
+pub const DEFAULT_MAX_FIELDS: u32 = 32;
+
+/// Maximum size of an on-chain avatar stored as a bytes field.
+/// Larger byte avatars are rejected and never rendered.
+pub const MAX_AVATAR_BYTES: u32 = 1024;
+
+/// Maximum length of an admin note, in bytes.
+pub const MAX_ADMIN_NOTE_LENGTH: u32 = 512;
+
 /// Validate a username according to the pattern.
 ///
 /// Returns true if the username is valid, false otherwise.
@@ -32,10 +66,19 @@ pub const TRAILING_DIGITS: u32 = 3;
 /// - Middle chars: lowercase letters, digits, or underscores
 /// - Last 3 chars: digits (0-9)
 pub fn validate_username(username: &Bytes) -> bool {
+    validate_username_with_bounds(username, MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH)
+}
+
+/// Validate a username using custom length bounds.
+///
+/// The character pattern is the same as `validate_username`; only the
+/// overall length limits change. `min_len` is never allowed below
+/// `MIN_USERNAME_LENGTH`, since shorter names cannot fit the pattern.
+pub fn validate_username_with_bounds(username: &Bytes, min_len: u32, max_len: u32) -> bool {
     let len = username.len();
 
     // Check length bounds
-    if len < MIN_USERNAME_LENGTH || len > MAX_USERNAME_LENGTH {
+    if len < min_len.max(MIN_USERNAME_LENGTH) || len > max_len {
         return false;
     }
 
@@ -85,6 +128,23 @@ fn is_valid_middle_char(b: u8) -> bool {
     is_lowercase_letter(b) || is_digit(b) || b == b'_'
 }
 
+/// Canonical form of a username, the form it is stored and looked up in.
+///
+/// ASCII letters are lowercased and other bytes are kept, so validation
+/// still rejects them. Every contract entry point that takes a username
+/// canonicalizes it first, so `Alice001` and `alice001` name the same
+/// profile. Applying it twice changes nothing.
+///
+/// Usernames stored before canonicalization passed lowercase-only
+/// validation, so they are already canonical and need no migration.
+pub fn canonicalize_username(username: &Bytes) -> Bytes {
+    let mut result = Bytes::new(username.env());
+    for b in username.iter() {
+        result.push_back(b.to_ascii_lowercase());
+    }
+    result
+}
+
 /// Normalize a username to lowercase.
 ///
 /// This converts any uppercase letters to lowercase. Returns None if
@@ -118,6 +178,218 @@ pub fn normalize_username(username: &Bytes) -> Option<Bytes> {
     }
 }
 
+/// Maximum length of a normalized display name, in bytes. Longer names
+/// are compared by their first `MAX_NORMALIZED_NAME_LENGTH` bytes.
+pub const MAX_NORMALIZED_NAME_LENGTH: u32 = 64;
+
+/// Base letters for U+00C0..U+00FF, indexed by the low five bits of the
+/// second UTF-8 byte. `*` keeps the character as is.
+const LATIN1_FOLD: &[u8; 32] = b"aaaaaaaceeeeiiiidnooooo*ouuuuy*s";
+
+/// Fold a display name to a form in which look-alike names collide.
+///
+/// ASCII is lowercased, Latin-1 accented letters lose their accents,
+/// common look-alike characters (`0`/`o`, `1`/`i`/`l`, `3`/`e`, `4`/`a`,
+/// `5`/`s`) are merged, zero-width characters are dropped and runs of
+/// whitespace, `.`, `-` and `_` become a single space. Other characters
+/// are kept as is. This is a cheap collision check, not full Unicode
+/// confusable detection.
+pub fn normalize_display_name(env: &Env, name: &Bytes) -> Bytes {
+    let mut result = Bytes::new(env);
+    let mut pending_space = false;
+    let len = name.len();
+    let mut i = 0;
+
+    while i < len && result.len() < MAX_NORMALIZED_NAME_LENGTH {
+        let b = name.get(i).unwrap();
+        let next = name.get(i + 1).unwrap_or(0);
+        let third = name.get(i + 2).unwrap_or(0);
+
+        // (folded byte, bytes consumed); a folded byte of 0 copies the
+        // consumed bytes unchanged
+        let (folded, width) = match b {
+            b' ' | b'\t' | b'\n' | b'\r' | b'.' | b'-' | b'_' => {
+                pending_space = !result.is_empty();
+                i += 1;
+                continue;
+            }
+            0x00..=0x7f => (fold_ascii(b.to_ascii_lowercase()), 1),
+            // No-break space
+            0xc2 if next == 0xa0 => {
+                pending_space = !result.is_empty();
+                i += 2;
+                continue;
+            }
+            0xc3 if (0x80..=0xbf).contains(&next) => match LATIN1_FOLD[(next & 0x1f) as usize] {
+                b'*' => (0, 2),
+                b's' if next == 0xbf => (b'y', 2),
+                base => (fold_ascii(base), 2),
+            },
+            // Zero-width space, non-joiner and joiner, word joiner, and
+            // byte order mark
+            0xe2 if next == 0x80 && (0x8b..=0x8d).contains(&third) => {
+                i += 3;
+                continue;
+            }
+            0xe2 if next == 0x81 && third == 0xa0 => {
+                i += 3;
+                continue;
+            }
+            0xef if next == 0xbb && third == 0xbf => {
+                i += 3;
+                continue;
+            }
+            _ => (0, 1),
+        };
+
+        if pending_space {
+            result.push_back(b' ');
+            pending_space = false;
+        }
+        if folded == 0 {
+            result.append(&name.slice(i..i + width));
+        } else {
+            result.push_back(folded);
+        }
+        i += width;
+    }
+
+    result
+}
+
+/// Fold a username to a form in which look-alike usernames collide.
+///
+/// Uses the same look-alike merges as `normalize_display_name`, so
+/// `alice001` and `a1ice001` fold to the same bytes.
+pub fn confusable_username(env: &Env, username: &Bytes) -> Bytes {
+    let mut result = Bytes::new(env);
+    for b in username.iter() {
+        result.push_back(fold_ascii(b.to_ascii_lowercase()));
+    }
+    result
+}
+
+/// Merge ASCII characters that are commonly swapped in impersonations.
+fn fold_ascii(b: u8) -> u8 {
+    match b {
+        b'0' => b'o',
+        b'1' | b'i' | b'!' | b'|' => b'l',
+        b'3' => b'e',
+        b'4' | b'@' => b'a',
+        b'5' | b'$' => b's',
+        _ => b,
+    }
+}
+
+/// Get the scheme of a decentralized website URL.
+///
+/// Accepts `ipfs://CID`, `ipns://name` and `ar://txid`. Returns the
+/// scheme name, or None if the URL is not in one of those forms or
+/// contains whitespace, quotes or angle brackets.
+pub fn dweb_scheme(url: &Bytes) -> Option<&'static str> {
+    let len = url.len();
+    if len > MAX_DWEB_URL_LENGTH {
+        return None;
+    }
+
+    for scheme in DWEB_SCHEMES.iter() {
+        let prefix_len = scheme.len() as u32 + 3;
+        if len <= prefix_len {
+            continue;
+        }
+
+        let mut matches = true;
+        for (i, expected) in scheme.bytes().chain(*b"://").enumerate() {
+            if url.get(i as u32).unwrap() != expected {
+                matches = false;
+                break;
+            }
+        }
+        if !matches {
+            continue;
+        }
+
+        for i in prefix_len..len {
+            if !is_dweb_path_char(url.get(i).unwrap()) {
+                return None;
+            }
+        }
+        return Some(scheme);
+    }
+
+    None
+}
+
+/// Validate a decentralized website URL. See `dweb_scheme`.
+pub fn validate_dweb_url(url: &Bytes) -> bool {
+    dweb_scheme(url).is_some()
+}
+
+/// Validate a profile link URL.
+///
+/// Accepts `https://` and `http://` URLs as well as dweb URLs. Besides
+/// the characters `dweb_scheme` rejects, parentheses and square
+/// brackets are rejected so the URL cannot break out of a markdown link.
+pub fn validate_link_url(url: &Bytes) -> bool {
+    let len = url.len();
+    if len > MAX_LINK_URL_LENGTH {
+        return false;
+    }
+
+    for i in 0..len {
+        if matches!(url.get(i).unwrap(), b'(' | b')' | b'[' | b']') {
+            return false;
+        }
+    }
+
+    if dweb_scheme(url).is_some() {
+        return true;
+    }
+
+    for scheme in LINK_SCHEMES.iter() {
+        let prefix_len = scheme.len() as u32 + 3;
+        if len <= prefix_len {
+            continue;
+        }
+
+        let matches = scheme
+            .bytes()
+            .chain(*b"://")
+            .enumerate()
+            .all(|(i, expected)| url.get(i as u32) == Some(expected));
+        if matches {
+            return (prefix_len..len).all(|i| is_dweb_path_char(url.get(i).unwrap()));
+        }
+    }
+
+    false
+}
+
+/// Check if a byte may appear after the scheme of a dweb URL.
+/// Printable ASCII, excluding characters that could break out of markup.
+#[inline]
+fn is_dweb_path_char(b: u8) -> bool {
+    (0x21..=0x7e).contains(&b) && !matches!(b, b'"' | b'\'' | b'<' | b'>' | b'\\' | b'`')
+}
+
+/// Validate a locale code such as `en`, `pt_BR` or `zh_Hant`.
+///
+/// Accepts a 2-3 letter lowercase language code, optionally followed by
+/// `_` and a 2-4 character region or script subtag (letters or digits).
+pub fn validate_locale(code: &[u8]) -> bool {
+    let lang_len = code.iter().position(|b| *b == b'_').unwrap_or(code.len());
+    if !(2..=3).contains(&lang_len) || !code[..lang_len].iter().all(|b| is_lowercase_letter(*b)) {
+        return false;
+    }
+
+    if lang_len == code.len() {
+        return true;
+    }
+
+    let subtag = &code[lang_len + 1..];
+    (2..=4).contains(&subtag.len()) && subtag.iter().all(|b| b.is_ascii_alphanumeric())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,4 +437,153 @@ mod tests {
         assert!(!validate_username(&Bytes::from_slice(&env, b"ABC123"))); // uppercase not allowed
         assert!(!validate_username(&Bytes::from_slice(&env, b"abc.123"))); // dot not allowed
     }
+
+    #[test]
+    fn test_dweb_urls() {
+        let env = Env::default();
+
+        assert_eq!(
+            dweb_scheme(&Bytes::from_slice(
+                &env,
+                b"ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"
+            )),
+            Some("ipfs")
+        );
+        assert_eq!(
+            dweb_scheme(&Bytes::from_slice(&env, b"ipns://alice.eth")),
+            Some("ipns")
+        );
+        assert_eq!(
+            dweb_scheme(&Bytes::from_slice(
+                &env,
+                b"ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U"
+            )),
+            Some("ar")
+        );
+
+        assert!(!validate_dweb_url(&Bytes::from_slice(
+            &env,
+            b"https://example.com"
+        )));
+        assert!(!validate_dweb_url(&Bytes::from_slice(&env, b"ipfs://")));
+        assert!(!validate_dweb_url(&Bytes::from_slice(&env, b"ipfs:/Qm123")));
+        assert!(!validate_dweb_url(&Bytes::from_slice(
+            &env,
+            b"ipfs://Qm 123"
+        )));
+        assert!(!validate_dweb_url(&Bytes::from_slice(
+            &env,
+            b"ar://tx\"><script>"
+        )));
+        assert!(!validate_dweb_url(&Bytes::from_slice(&env, b"")));
+    }
+
+    #[test]
+    fn test_link_urls() {
+        let env = Env::default();
+        let valid = |url: &str| validate_link_url(&Bytes::from_slice(&env, url.as_bytes()));
+
+        assert!(valid("https://www.youtube.com/@alice"));
+        assert!(valid("http://example.com"));
+        assert!(valid(
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"
+        ));
+
+        assert!(!valid(""));
+        assert!(!valid("https://"));
+        assert!(!valid("javascript:alert(1)"));
+        assert!(!valid("ftp://example.com"));
+        assert!(!valid("https://example.com/a b"));
+        assert!(!valid("https://example.com/\"><script>"));
+        assert!(!valid("https://example.com/x)[y](z"));
+    }
+
+    #[test]
+    fn test_custom_bounds() {
+        let env = Env::default();
+        let long = Bytes::from_slice(&env, b"abcdefghijklmn123");
+
+        assert!(validate_username_with_bounds(&long, 6, 17));
+        assert!(!validate_username_with_bounds(&long, 6, 10));
+        assert!(validate_username_with_bounds(&long, 6, 20));
+        assert!(!validate_username_with_bounds(
+            &Bytes::from_slice(&env, b"abc123"),
+            8,
+            17
+        ));
+
+        // The pattern still applies with relaxed bounds
+        assert!(!validate_username_with_bounds(
+            &Bytes::from_slice(&env, b"ab12"),
+            0,
+            17
+        ));
+    }
+
+    #[test]
+    fn test_canonicalize_username() {
+        let env = Env::default();
+        let canon = |s: &str| canonicalize_username(&Bytes::from_slice(&env, s.as_bytes()));
+
+        assert_eq!(canon("Alice_001"), Bytes::from_slice(&env, b"alice_001"));
+        assert_eq!(canon("alice_001"), Bytes::from_slice(&env, b"alice_001"));
+        assert_eq!(
+            canonicalize_username(&canon("BoB_SMITH123")),
+            canon("BoB_SMITH123")
+        );
+        // Non-letters are kept for validation to reject
+        assert_eq!(canon("Ab-C1"), Bytes::from_slice(&env, b"ab-c1"));
+    }
+
+    #[test]
+    fn test_confusable_username() {
+        let env = Env::default();
+        let fold = |s: &str| confusable_username(&env, &Bytes::from_slice(&env, s.as_bytes()));
+
+        assert_eq!(fold("alice001"), fold("a1ice001"));
+        assert_eq!(fold("alice001"), fold("alice0o1"));
+        assert_eq!(fold("bob5mith"), fold("bobsmith"));
+        assert_ne!(fold("alice001"), fold("alice002"));
+    }
+
+    #[test]
+    fn test_normalize_display_name() {
+        let env = Env::default();
+        let norm = |s: &str| normalize_display_name(&env, &Bytes::from_slice(&env, s.as_bytes()));
+
+        let expected = Bytes::from_slice(&env, b"stellar foundatlon");
+        assert_eq!(norm("Stellar Foundation"), expected);
+        assert_eq!(norm("  stellar   FOUNDATION "), expected);
+        assert_eq!(norm("Stellar Foundatlon"), expected);
+        assert_eq!(norm("St3llar_F0undation"), expected);
+        assert_eq!(norm("Stéllar Foundatïon"), expected);
+        assert_eq!(norm("Stellar\u{200b} Foundation"), expected);
+        assert_eq!(norm("Stellar\u{a0}Foundation"), expected);
+
+        // Characters without a fold are kept
+        assert_eq!(
+            norm("Æsir × Þór"),
+            Bytes::from_slice(&env, "aslr × Þor".as_bytes())
+        );
+        assert_eq!(norm("日本"), Bytes::from_slice(&env, "日本".as_bytes()));
+        assert_eq!(norm(" ... "), Bytes::new(&env));
+        assert_eq!(norm(&"a".repeat(100)).len(), MAX_NORMALIZED_NAME_LENGTH);
+    }
+
+    #[test]
+    fn test_locales() {
+        assert!(validate_locale(b"en"));
+        assert!(validate_locale(b"fil"));
+        assert!(validate_locale(b"pt_BR"));
+        assert!(validate_locale(b"zh_Hant"));
+        assert!(validate_locale(b"es_419"));
+
+        assert!(!validate_locale(b""));
+        assert!(!validate_locale(b"e"));
+        assert!(!validate_locale(b"english"));
+        assert!(!validate_locale(b"EN"));
+        assert!(!validate_locale(b"en_"));
+        assert!(!validate_locale(b"en_B"));
+        assert!(!validate_locale(b"en_Latin1"));
+    }
 }
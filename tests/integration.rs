@@ -2,8 +2,18 @@
 
 #![cfg(feature = "testutils")]
 
-use soroban_sdk::{testutils::Address as _, Address, Bytes, Env, String, Symbol};
-use soroban_user_profile::{Profile, ProfileError, UserProfileContract, UserProfileContractClient};
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{storage::Persistent as _, Address as _, Events as _, Ledger as _},
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+use soroban_user_profile::{
+    canonicalize_username, normalize_display_name, CardOptions, DateStyle, FieldValue, FullProfile,
+    InitConfig, ModLogEntry, Profile, ProfileError, ProfileExport, ProfileKey, ProfileStats,
+    ProfileView, UserProfileContract, UserProfileContractClient, ViewerContext, EMBED_ALLOWLIST,
+    EMBED_OPEN, MAX_CONTACT_LENGTH, PROFILE_SCHEMA_VERSION, RESERVATION_FREE, RESERVATION_HARD,
+    RESERVATION_SOFT, RESERVATION_TAKEN, UNVERSIONED_PROFILE_SCHEMA,
+};
 
 fn setup() -> (Env, UserProfileContractClient<'static>, Address) {
     let env = Env::default();
@@ -18,6 +28,51 @@ fn setup() -> (Env, UserProfileContractClient<'static>, Address) {
     (env, client, admin)
 }
 
+fn register_user(
+    env: &Env,
+    client: &UserProfileContractClient,
+    username: &str,
+    display_name: &str,
+) -> Address {
+    let user = Address::generate(env);
+    client.register(
+        &String::from_str(env, username),
+        &String::from_str(env, display_name),
+        &user,
+    );
+    user
+}
+
+fn last_event_topics(env: &Env) -> Vec<Val> {
+    let (_, topics, _) = env.events().all().last().unwrap();
+    topics
+}
+
+fn last_event_data(env: &Env) -> Val {
+    let (_, _, data) = env.events().all().last().unwrap();
+    data
+}
+
+fn render_path(env: &Env, client: &UserProfileContractClient, path: &str) -> std::string::String {
+    render_path_as(env, client, path, None)
+}
+
+fn render_path_as(
+    env: &Env,
+    client: &UserProfileContractClient,
+    path: &str,
+    viewer: Option<Address>,
+) -> std::string::String {
+    let output = client.render(&Some(String::from_str(env, path)), &viewer);
+    to_std_string(&output)
+}
+
+fn to_std_string(bytes: &Bytes) -> std::string::String {
+    let mut buf = std::vec![0u8; bytes.len() as usize];
+    bytes.copy_into_slice(&mut buf);
+    std::string::String::from_utf8(buf).unwrap()
+}
+
 #[test]
 fn test_init() {
     let (env, client, admin) = setup();
@@ -32,7 +87,7 @@ fn test_register_profile() {
     let username = Bytes::from_slice(&env, b"alice001");
     let display_name = String::from_str(&env, "Alice");
 
-    let result = client.register(&username, &display_name, &user);
+    let result = client.register(&String::from_str(&env, "alice001"), &display_name, &user);
     assert!(result);
 
     // Check profile count
@@ -62,8 +117,10 @@ fn test_username_validation() {
 
     // Invalid usernames (format fails)
     assert!(!client.is_username_available(&Bytes::from_slice(&env, b"ab123"))); // too short
-    assert!(!client.is_username_available(&Bytes::from_slice(&env, b"ABC123"))); // uppercase
     assert!(!client.is_username_available(&Bytes::from_slice(&env, b"123abc"))); // starts with digits
+
+    // Uppercase is canonicalized rather than rejected
+    assert!(client.is_username_available(&Bytes::from_slice(&env, b"ABC123")));
 }
 
 #[test]
@@ -75,7 +132,7 @@ fn test_username_uniqueness() {
     let display_name = String::from_str(&env, "Alice");
 
     // First registration succeeds
-    client.register(&username, &display_name, &user1);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user1);
 
     // Username should no longer be available
     assert!(!client.is_username_available(&username));
@@ -88,7 +145,7 @@ fn test_set_fields() {
     let username = Bytes::from_slice(&env, b"alice001");
     let display_name = String::from_str(&env, "Alice");
 
-    client.register(&username, &display_name, &user);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user);
 
     // Set string field
     let bio = String::from_str(&env, "Hello, I am Alice!");
@@ -126,7 +183,7 @@ fn test_update_display_name() {
     let display_name = String::from_str(&env, "Alice");
     let new_display_name = String::from_str(&env, "Alice Smith");
 
-    client.register(&username, &display_name, &user);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user);
 
     // Update display name
     client.set_display_name(&new_display_name, &user);
@@ -143,7 +200,7 @@ fn test_delete_profile() {
     let username = Bytes::from_slice(&env, b"alice001");
     let display_name = String::from_str(&env, "Alice");
 
-    client.register(&username, &display_name, &user);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user);
 
     // Delete profile
     client.delete_profile(&user);
@@ -164,7 +221,7 @@ fn test_transfer_profile() {
     let username = Bytes::from_slice(&env, b"alice001");
     let display_name = String::from_str(&env, "Alice");
 
-    client.register(&username, &display_name, &user1);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user1);
 
     // Transfer to new owner
     client.transfer(&user2, &user1);
@@ -227,7 +284,7 @@ fn test_ban_profile() {
     let username = Bytes::from_slice(&env, b"alice001");
     let display_name = String::from_str(&env, "Alice");
 
-    client.register(&username, &display_name, &user);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user);
 
     // Ban profile
     client.ban_profile(&user, &admin);
@@ -243,7 +300,7 @@ fn test_remove_field() {
     let username = Bytes::from_slice(&env, b"alice001");
     let display_name = String::from_str(&env, "Alice");
 
-    client.register(&username, &display_name, &user);
+    client.register(&String::from_str(&env, "alice001"), &display_name, &user);
 
     // Set and remove field
     let bio = String::from_str(&env, "Hello!");
@@ -258,3 +315,3801 @@ fn test_remove_field() {
     // Field should be gone
     assert!(client.get_field(&user, &Symbol::new(&env, "bio")).is_none());
 }
+
+#[test]
+fn test_directory_populated_letter() {
+    let (env, client, _admin) = setup();
+    register_user(&env, &client, "alice001", "Alice");
+    register_user(&env, &client, "amber123", "Amber");
+    register_user(&env, &client, "bob_smith123", "Bob");
+
+    let page = render_path(&env, &client, "/dir/a");
+    assert!(page.contains("[@alice001](render:/u/alice001) — Alice"));
+    assert!(page.contains("[@amber123](render:/u/amber123) — Amber"));
+    assert!(!page.contains("bob_smith123"));
+}
+
+#[test]
+fn test_directory_empty_letter() {
+    let (env, client, _admin) = setup();
+    register_user(&env, &client, "alice001", "Alice");
+
+    let page = render_path(&env, &client, "/dir/z");
+    assert!(page.contains("No usernames found for this letter."));
+    assert!(!page.contains("alice001"));
+}
+
+#[test]
+fn test_directory_pagination() {
+    let (env, client, _admin) = setup();
+    for i in 0..51 {
+        register_user(&env, &client, &std::format!("abc{:03}", i), "User");
+    }
+
+    let first = render_path(&env, &client, "/dir/a");
+    assert!(first.contains("@abc000"));
+    assert!(first.contains("@abc049"));
+    assert!(!first.contains("@abc050"));
+    assert!(first.contains("[Next](render:/dir/a/2)"));
+
+    let second = render_path(&env, &client, "/dir/a/2");
+    assert!(second.contains("@abc050"));
+    assert!(!second.contains("@abc049"));
+    assert!(second.contains("[Previous](render:/dir/a/1)"));
+}
+
+#[test]
+fn test_event_prefix() {
+    let (env, client, admin) = setup();
+    let prefix = Symbol::new(&env, "profiles");
+
+    // Unprefixed by default
+    register_user(&env, &client, "alice001", "Alice");
+    let topics = last_event_topics(&env);
+    assert_eq!(topics.len(), 1);
+    let name: Symbol = topics.get(0).unwrap().into_val(&env);
+    assert_eq!(name, Symbol::new(&env, "profile_registered"));
+
+    client.set_event_prefix(&Some(prefix.clone()), &admin);
+    assert_eq!(client.event_prefix(), Some(prefix.clone()));
+
+    register_user(&env, &client, "bob_smith123", "Bob");
+    let topics = last_event_topics(&env);
+    assert_eq!(topics.len(), 2);
+    let leading: Symbol = topics.get(0).unwrap().into_val(&env);
+    let name: Symbol = topics.get(1).unwrap().into_val(&env);
+    assert_eq!(leading, prefix);
+    assert_eq!(name, Symbol::new(&env, "profile_registered"));
+}
+
+#[test]
+fn test_get_by_index() {
+    let (env, client, _admin) = setup();
+    let _alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    let carol = register_user(&env, &client, "carol777", "Carol");
+
+    let profile = client.get_by_index(&1).unwrap();
+    assert_eq!(profile.owner, bob);
+    assert_eq!(profile.username, Bytes::from_slice(&env, b"bob_smith123"));
+
+    // Empty slot
+    assert!(client.get_by_index(&3).is_none());
+
+    // Deleted profile
+    client.delete_profile(&carol);
+    assert!(client.get_by_index(&2).is_none());
+}
+
+#[test]
+fn test_reserve_until() {
+    let (env, client, admin) = setup();
+    let user = Address::generate(&env);
+    let username = Bytes::from_slice(&env, b"event2024001");
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+
+    client.reserve_until(&username, &1_700_086_400, &admin);
+    assert!(!client.is_username_available(&username));
+
+    // Registration fails before expiry
+    let result = client.try_register(
+        &String::from_str(&env, "event2024001"),
+        &String::from_str(&env, "Event"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::UsernameReserved.into())));
+
+    // Registration succeeds once the reservation has lapsed
+    env.ledger().with_mut(|li| li.timestamp = 1_700_086_400);
+    assert!(client.is_username_available(&username));
+    client.register(
+        &String::from_str(&env, "event2024001"),
+        &String::from_str(&env, "Event"),
+        &user,
+    );
+    assert_eq!(client.get_by_username(&username).unwrap().owner, user);
+}
+
+#[test]
+fn test_admin_note() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let note = String::from_str(&env, "warned on 2024-03-01 for impersonation");
+
+    client.set_admin_note(&user, &note, &admin);
+    assert_eq!(client.admin_note(&user, &admin), Some(note));
+
+    // Non-admins cannot read the note
+    let result = client.try_admin_note(&user, &user);
+    assert_eq!(result, Err(Ok(ProfileError::NotAuthorized.into())));
+
+    client.clear_admin_note(&user, &admin);
+    assert_eq!(client.admin_note(&user, &admin), None);
+
+    // Both changes are logged, without the note's text
+    assert_eq!(client.mod_log_count(), 2);
+    for (index, detail) in [(0, "set"), (1, "clear")] {
+        let entry: ModLogEntry = client.mod_log_entry(&index).unwrap();
+        assert_eq!(entry.action, Symbol::new(&env, "note"));
+        assert_eq!(entry.target, user);
+        assert_eq!(entry.admin, admin);
+        assert_eq!(entry.detail, Bytes::from_slice(&env, detail.as_bytes()));
+    }
+
+    // Clearing a missing note is not a change
+    client.clear_admin_note(&user, &admin);
+    assert_eq!(client.mod_log_count(), 2);
+}
+
+#[test]
+fn test_auto_field() {
+    let (env, client, admin) = setup();
+    let badge = Symbol::new(&env, "badge");
+
+    // Nothing is applied when unset
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    assert!(client.get_field(&alice, &badge).is_none());
+
+    client.set_auto_field(
+        &badge,
+        &FieldValue::StringField(String::from_str(&env, "newbie")),
+        &admin,
+    );
+
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    match client.get_field(&bob, &badge) {
+        Some(FieldValue::StringField(s)) => assert_eq!(s, String::from_str(&env, "newbie")),
+        _ => panic!("Expected StringField"),
+    }
+}
+
+#[test]
+fn test_report_profile() {
+    let (env, client, admin) = setup();
+    let target = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    let carol = register_user(&env, &client, "carol777", "Carol");
+    let spam = Symbol::new(&env, "spam");
+
+    client.report_profile(&target, &spam, &bob);
+    assert_eq!(client.report_count(&target), 1);
+
+    // Duplicate reports from the same reporter are rejected
+    let result = client.try_report_profile(&target, &spam, &bob);
+    assert_eq!(result, Err(Ok(ProfileError::AlreadyReported.into())));
+
+    client.report_profile(&target, &Symbol::new(&env, "impersonation"), &carol);
+    assert_eq!(client.report_count(&target), 2);
+
+    let reports = client.reports_of(&target, &admin);
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports.get(0).unwrap().0, bob);
+    assert_eq!(reports.get(0).unwrap().1, spam);
+
+    // Reporters without a profile cannot report
+    let stranger = Address::generate(&env);
+    let result = client.try_report_profile(&target, &spam, &stranger);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileNotFound.into())));
+
+    client.resolve_reports(&target, &admin);
+    assert_eq!(client.report_count(&target), 0);
+
+    // Bob may report again once resolved
+    client.report_profile(&target, &spam, &bob);
+    assert_eq!(client.report_count(&target), 1);
+}
+
+#[test]
+fn test_report_form_rendering() {
+    let (env, client, _admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+
+    let page = render_path_as(&env, &client, "/u/alice001", Some(bob));
+    assert!(page.contains("form:report_profile"));
+
+    let own_page = render_path_as(&env, &client, "/u/alice001", Some(alice));
+    assert!(!own_page.contains("form:report_profile"));
+
+    let anonymous = render_path(&env, &client, "/u/alice001");
+    assert!(!anonymous.contains("form:report_profile"));
+}
+
+#[test]
+fn test_render_unknown_route() {
+    let (env, client, _admin) = setup();
+
+    let page = render_path(&env, &client, "/nonexistent");
+    assert!(page.contains("Page Not Found"));
+    assert!(!page.contains("Find Profile"));
+
+    let home = render_path(&env, &client, "/");
+    assert!(home.contains("Find Profile"));
+}
+
+#[test]
+fn test_auto_hide_threshold() {
+    let (env, client, admin) = setup();
+    let target = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    let carol = register_user(&env, &client, "carol777", "Carol");
+    let spam = Symbol::new(&env, "spam");
+
+    client.set_auto_hide_threshold(&2, &admin);
+
+    client.report_profile(&target, &spam, &bob);
+    assert!(!client.is_hidden(&target));
+
+    client.report_profile(&target, &spam, &carol);
+    assert!(client.is_hidden(&target));
+
+    // Data stays available and the owner can still edit
+    assert!(client.get_by_address(&target).is_some());
+    client.set_display_name(&String::from_str(&env, "Alice B"), &target);
+
+    let page = render_path_as(&env, &client, "/u/alice001", Some(bob.clone()));
+    assert!(page.contains("Profile Under Review"));
+    assert!(!page.contains("Alice B"));
+
+    let card = to_std_string(&client.render_profile_card(&target));
+    assert!(card.contains("profile-card-anonymous"));
+
+    // Unhiding keeps the reports open
+    client.unhide_profile(&target, &admin);
+    assert!(!client.is_hidden(&target));
+    assert_eq!(client.report_count(&target), 2);
+
+    // Resolving also clears the hidden state
+    client.resolve_reports(&target, &admin);
+    client.report_profile(&target, &spam, &bob);
+    client.report_profile(&target, &spam, &carol);
+    assert!(client.is_hidden(&target));
+    client.resolve_reports(&target, &admin);
+    assert!(!client.is_hidden(&target));
+}
+
+#[test]
+fn test_deactivate_profile() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let name = Bytes::from_slice(&env, b"alice001");
+
+    client.deactivate(&alice);
+    let event: Symbol = last_event_topics(&env).get(0).unwrap().into_val(&env);
+    assert_eq!(event, Symbol::new(&env, "profile_deactivated"));
+    assert!(client.is_deactivated(&alice));
+    assert!(client.get_by_address(&alice).is_none());
+    assert!(client.get_by_username(&name).is_none());
+    assert_eq!(
+        client.try_expect_by_address(&alice).err(),
+        Some(Ok(ProfileError::ProfileDeactivated.into()))
+    );
+
+    // Others see nothing; the owner still sees and edits the profile
+    let card = to_std_string(&client.render_profile_card(&alice));
+    assert!(card.contains("profile-card-anonymous"));
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("Profile Not Found"));
+    assert!(!page.contains("Alice"));
+    client.set_display_name(&String::from_str(&env, "Alice B"), &alice);
+    let own = render_path_as(&env, &client, "/u/alice001", Some(alice.clone()));
+    assert!(own.contains("Your profile is deactivated"));
+    assert!(client.viewer_context(&Some(alice.clone())).has_profile);
+
+    // The username stays taken
+    assert!(!client.is_username_available(&name));
+
+    client.activate(&alice);
+    let event: Symbol = last_event_topics(&env).get(0).unwrap().into_val(&env);
+    assert_eq!(event, Symbol::new(&env, "profile_activated"));
+    assert!(!client.is_deactivated(&alice));
+    let profile = client.get_by_address(&alice).unwrap();
+    assert_eq!(profile.display_name, String::from_str(&env, "Alice B"));
+
+    // A banned profile cannot bring itself back
+    client.deactivate(&alice);
+    client.ban_profile(&alice, &admin);
+    assert_eq!(
+        client.try_activate(&alice),
+        Err(Ok(ProfileError::ProfileBanned.into()))
+    );
+    assert!(client.get_by_address(&alice).is_none());
+
+    // Deleting supersedes a deactivation
+    let bob = register_user(&env, &client, "bobby001", "Bob");
+    client.deactivate(&bob);
+    client.delete_profile(&bob);
+    assert!(!client.is_deactivated(&bob));
+    assert_eq!(
+        client.try_activate(&bob),
+        Err(Ok(ProfileError::ProfileDeleted.into()))
+    );
+}
+
+#[test]
+fn test_admin_swap_owners() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    let alice_name = Bytes::from_slice(&env, b"alice001");
+    let bob_name = Bytes::from_slice(&env, b"bob_smith123");
+
+    client.set_auto_hide_threshold(&1, &admin);
+    client.report_profile(&alice, &Symbol::new(&env, "spam"), &bob);
+    client.deactivate(&bob);
+    client.admin_swap_owners(&alice, &bob, &admin);
+
+    // The flags moved with the profiles
+    assert!(client.is_hidden(&bob));
+    assert!(!client.is_hidden(&alice));
+    assert!(client.is_deactivated(&alice));
+    assert!(!client.is_deactivated(&bob));
+    client.activate(&alice);
+
+    assert_eq!(client.get_by_username(&alice_name).unwrap().owner, bob);
+    assert_eq!(client.get_by_username(&bob_name).unwrap().owner, alice);
+    assert_eq!(client.get_by_address(&alice).unwrap().username, bob_name);
+
+    // A ban follows the banned profile too
+    let carol = register_user(&env, &client, "carol001", "Carol");
+    client.ban_profile(&carol, &admin);
+    client.admin_swap_owners(&alice, &carol, &admin);
+    assert!(client.is_banned(&alice));
+    assert!(!client.is_banned(&carol));
+    assert_eq!(client.get_by_address(&carol).unwrap().username, bob_name);
+    let result = client.try_hard_delete_profile(&true, &alice);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+
+    // Both addresses must have a profile
+    let stranger = Address::generate(&env);
+    let result = client.try_admin_swap_owners(&alice, &stranger, &admin);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileNotFound.into())));
+}
+
+#[test]
+fn test_profile_age() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let alice = register_user(&env, &client, "alice001", "Alice");
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_700_000_000 + 400 * 86_400);
+    assert_eq!(client.profile_age_seconds(&alice), Some(400 * 86_400));
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("<div class=\"profile-age profile-age-1y\">Member for 1 year</div>"));
+
+    // Unregistered addresses have no age
+    assert_eq!(client.profile_age_seconds(&Address::generate(&env)), None);
+}
+
+#[test]
+fn test_freeze_field() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+
+    client.set_string_field(&bio, &String::from_str(&env, "Original bio"), &user);
+    client.freeze_field(&bio, &user);
+
+    let result = client.try_set_string_field(&bio, &String::from_str(&env, "Changed"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::FieldFrozen.into())));
+
+    let result = client.try_remove_field(&bio, &user);
+    assert_eq!(result, Err(Ok(ProfileError::FieldFrozen.into())));
+
+    match client.get_field(&user, &bio) {
+        Some(FieldValue::StringField(s)) => assert_eq!(s, String::from_str(&env, "Original bio")),
+        _ => panic!("Expected StringField"),
+    }
+
+    // Other fields are unaffected
+    client.set_string_field(
+        &Symbol::new(&env, "github"),
+        &String::from_str(&env, "alice"),
+        &user,
+    );
+}
+
+#[test]
+fn test_min_age() {
+    let (env, client, _admin) = setup();
+    let day = 86_400u64;
+
+    // Registered at timestamp 0, so created_at is treated as legacy
+    let legacy = register_user(&env, &client, "legacy001", "Legacy");
+
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let old = register_user(&env, &client, "oldtimer001", "Old");
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_700_000_000 + 30 * day);
+    let new = register_user(&env, &client, "newcomer001", "New");
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_700_000_000 + 31 * day);
+
+    assert!(client.meets_min_age(&old, &(7 * day)));
+    client.require_min_age(&old, &(7 * day));
+
+    assert!(!client.meets_min_age(&new, &(7 * day)));
+    let result = client.try_require_min_age(&new, &(7 * day));
+    assert_eq!(result, Err(Ok(ProfileError::ProfileTooNew.into())));
+    assert!(client.meets_min_age(&new, &day));
+
+    assert!(client.meets_min_age(&legacy, &(365 * day)));
+    client.require_min_age(&legacy, &(365 * day));
+
+    client.delete_profile(&old);
+    assert!(!client.meets_min_age(&old, &0));
+    let result = client.try_require_min_age(&old, &0);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileNotFound.into())));
+
+    let stranger = Address::generate(&env);
+    let result = client.try_require_min_age(&stranger, &0);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileNotFound.into())));
+}
+
+#[test]
+fn test_usernames_of() {
+    let (env, client, _admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    let stranger = Address::generate(&env);
+
+    let addresses = Vec::from_array(&env, [alice.clone(), stranger.clone(), bob.clone()]);
+    let names = client.usernames_of(&addresses);
+
+    assert_eq!(names.len(), 2);
+    assert_eq!(names.get(alice), Some(Bytes::from_slice(&env, b"alice001")));
+    assert_eq!(
+        names.get(bob),
+        Some(Bytes::from_slice(&env, b"bob_smith123"))
+    );
+    assert_eq!(names.get(stranger), None);
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..51 {
+        too_many.push_back(Address::generate(&env));
+    }
+    let result = client.try_usernames_of(&too_many);
+    assert_eq!(result, Err(Ok(ProfileError::BatchTooLarge.into())));
+}
+
+#[test]
+fn test_dweb_field() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let dweb = Symbol::new(&env, "dweb");
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(!page.contains("profile-dweb"));
+
+    let result = client.try_set_string_field(
+        &dweb,
+        &String::from_str(&env, "https://alice.example"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::InvalidFieldValue.into())));
+
+    client.set_string_field(
+        &dweb,
+        &String::from_str(
+            &env,
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+        ),
+        &user,
+    );
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains(
+        "<a class=\"profile-dweb\" href=\"ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi\">Visit site <span class=\"profile-dweb-scheme\">ipfs</span></a>"
+    ));
+}
+
+#[test]
+fn test_min_transfer_age() {
+    let (env, client, admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let new_owner = Address::generate(&env);
+
+    assert_eq!(client.min_transfer_age(), 0);
+    client.set_min_transfer_age(&(7 * 86_400), &admin);
+    assert_eq!(client.min_transfer_age(), 7 * 86_400);
+
+    let result = client.try_transfer(&new_owner, &alice);
+    assert_eq!(result, Err(Ok(ProfileError::TransferTooEarly.into())));
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_700_000_000 + 7 * 86_400);
+    client.transfer(&new_owner, &alice);
+
+    let profile = client.get_by_address(&new_owner).unwrap();
+    assert_eq!(profile.owner, new_owner);
+}
+
+#[test]
+fn test_register_from_source() {
+    let (env, client, admin) = setup();
+    let wallet = Symbol::new(&env, "wallet");
+    let boards = Symbol::new(&env, "boards");
+    let other = Symbol::new(&env, "other");
+
+    client.add_source(&wallet, &admin);
+    client.add_source(&boards, &admin);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let dave = Address::generate(&env);
+    client.register_from(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &alice,
+        &wallet,
+    );
+    client.register_from(
+        &String::from_str(&env, "bob_smith123"),
+        &String::from_str(&env, "Bob"),
+        &bob,
+        &wallet,
+    );
+    client.register_from(
+        &String::from_str(&env, "carol001"),
+        &String::from_str(&env, "Carol"),
+        &carol,
+        &boards,
+    );
+    client.register_from(
+        &String::from_str(&env, "dave001"),
+        &String::from_str(&env, "Dave"),
+        &dave,
+        &Symbol::new(&env, "unknown"),
+    );
+
+    assert_eq!(client.registrations_by_source(&wallet), 2);
+    assert_eq!(client.registrations_by_source(&boards), 1);
+    assert_eq!(client.registrations_by_source(&other), 1);
+
+    // Per-app counts keep unapproved ids apart from `other`
+    assert_eq!(client.app_registration_count(&wallet), 2);
+    assert_eq!(
+        client.app_registration_count(&Symbol::new(&env, "unknown")),
+        1
+    );
+    assert_eq!(client.app_registration_count(&other), 0);
+    let game = Symbol::new(&env, "game");
+    for (name, user) in [
+        ("gina001", Address::generate(&env)),
+        ("hank001", Address::generate(&env)),
+    ] {
+        client.register_from(
+            &String::from_str(&env, name),
+            &String::from_str(&env, "Player"),
+            &user,
+            &game,
+        );
+    }
+    assert_eq!(client.app_registration_count(&game), 2);
+    assert_eq!(client.registrations_by_source(&game), 0);
+    assert_eq!(client.registrations_by_source(&other), 3);
+
+    assert_eq!(client.source_of(&alice), Some(wallet.clone()));
+    assert_eq!(client.source_of(&carol), Some(boards.clone()));
+    assert_eq!(client.source_of(&dave), Some(other.clone()));
+
+    // Plain registrations carry no attribution
+    let erin = register_user(&env, &client, "erin001", "Erin");
+    assert_eq!(client.source_of(&erin), None);
+
+    // Removed sources fall back to `other`
+    client.remove_source(&boards, &admin);
+    let frank = Address::generate(&env);
+    client.register_from(
+        &String::from_str(&env, "frank001"),
+        &String::from_str(&env, "Frank"),
+        &frank,
+        &boards,
+    );
+    assert_eq!(client.registrations_by_source(&boards), 1);
+    assert_eq!(client.registrations_by_source(&other), 4);
+    assert_eq!(client.app_registration_count(&boards), 2);
+}
+
+#[test]
+fn test_username_bounds() {
+    let (env, client, admin) = setup();
+    let long = Bytes::from_slice(&env, b"abcdefghijklmn123");
+
+    assert_eq!(client.username_bounds(), (6, 17));
+    assert!(client.is_valid_username(&long));
+
+    client.set_username_bounds(&6, &10, &admin);
+    assert_eq!(client.username_bounds(), (6, 10));
+    assert!(!client.is_valid_username(&long));
+    assert!(!client.is_username_available(&long));
+    assert!(client.is_valid_username(&Bytes::from_slice(&env, b"alice001")));
+
+    let user = Address::generate(&env);
+    let result = client.try_register(
+        &String::from_str(&env, "abcdefghijklmn123"),
+        &String::from_str(&env, "Long"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::InvalidUsername.into())));
+
+    // Bounds can't drop below the pattern minimum or invert
+    let result = client.try_set_username_bounds(&4, &10, &admin);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidConfig.into())));
+    let result = client.try_set_username_bounds(&12, &10, &admin);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidConfig.into())));
+}
+
+#[test]
+fn test_reservation_ttl() {
+    let (env, client, admin) = setup();
+    let kept = Bytes::from_slice(&env, b"kept_name001");
+    let reserved_ttl = |name: &Bytes| {
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get_ttl(&ProfileKey::ReservedUsername(name.clone()))
+        })
+    };
+
+    client.reserve_username(&kept, &admin);
+    assert_eq!(reserved_ttl(&kept), 2_592_000);
+
+    // Most of the lifetime passes; the reservation still blocks the name
+    client.bump_instance();
+    env.ledger().with_mut(|li| li.sequence_number += 2_500_000);
+    assert!(!client.is_username_available(&kept));
+    assert_eq!(reserved_ttl(&kept), 92_000);
+
+    client.bump_reservation(&kept);
+    assert_eq!(reserved_ttl(&kept), 2_592_000);
+}
+
+#[test]
+fn test_bump_profile() {
+    let (env, client, _admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &alice);
+    client.freeze_field(&bio, &alice);
+    let profile_ttl = || {
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get_ttl(&ProfileKey::Profile(alice.clone()))
+        })
+    };
+
+    client.bump_instance();
+    env.ledger().with_mut(|li| li.sequence_number += 2_500_000);
+    assert_eq!(profile_ttl(), 92_000);
+
+    client.bump_profile(&alice);
+    assert_eq!(profile_ttl(), 2_592_000);
+
+    // Fields, freeze markers and index entries are bumped with it
+    let username = Bytes::from_slice(&env, b"alice001");
+    let keys = [
+        ProfileKey::Field(alice.clone(), bio.clone()),
+        ProfileKey::FrozenField(alice.clone(), bio),
+        ProfileKey::IndexSlot(username),
+        ProfileKey::ProfileIndex(0),
+        ProfileKey::DisplayNameIndex(normalize_display_name(
+            &env,
+            &Bytes::from_slice(&env, b"Alice"),
+        )),
+    ];
+    env.as_contract(&client.address, || {
+        for key in keys.iter() {
+            let ttl = env.storage().persistent().get_ttl(key);
+            assert_eq!(ttl, 2_592_000, "{:?} was not bumped", key);
+        }
+    });
+
+    let result = client.try_bump_profile(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(ProfileError::ProfileNotFound.into())));
+}
+
+#[test]
+fn test_locale() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+
+    assert_eq!(client.get_locale(&user), None);
+
+    client.set_locale(&Symbol::new(&env, "en"), &user);
+    assert_eq!(client.get_locale(&user), Some(Symbol::new(&env, "en")));
+
+    client.set_locale(&Symbol::new(&env, "pt_BR"), &user);
+    assert_eq!(client.get_locale(&user), Some(Symbol::new(&env, "pt_BR")));
+
+    let result = client.try_set_locale(&Symbol::new(&env, "english"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidFieldValue.into())));
+}
+
+#[test]
+fn test_get_view_by_username() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let username = Bytes::from_slice(&env, b"alice001");
+    let avatar = Symbol::new(&env, "avatar");
+    let bio = Symbol::new(&env, "bio");
+    let location = Symbol::new(&env, "location");
+
+    client.set_string_field(&avatar, &String::from_str(&env, "ipfs://avatar"), &user);
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+
+    let names = Vec::from_array(&env, [avatar.clone(), bio.clone(), location.clone()]);
+    let view: ProfileView = client.get_view_by_username(&username, &names).unwrap();
+
+    let profile = client.get_by_username(&username).unwrap();
+    assert_eq!(view.profile.owner, profile.owner);
+    assert_eq!(view.profile.username, profile.username);
+    assert_eq!(view.profile.display_name, profile.display_name);
+    assert_eq!(view.fields, client.get_fields(&user, &names));
+    assert_eq!(view.fields.len(), 2);
+    assert!(!view.fields.contains_key(location));
+
+    // Unknown and deleted profiles have no view
+    assert!(client
+        .get_view_by_username(&Bytes::from_slice(&env, b"nobody001"), &names)
+        .is_none());
+    client.delete_profile(&user);
+    assert!(client.get_view_by_username(&username, &names).is_none());
+}
+
+#[test]
+fn test_limits() {
+    let (_env, client, admin) = setup();
+
+    assert_eq!(client.limits(), (6, 17, 1024, 32, 0));
+
+    client.configure(&8, &12, &64, &3, &5_000_000, &admin);
+    assert_eq!(client.limits(), (8, 12, 64, 3, 5_000_000));
+    assert_eq!(client.username_bounds(), (8, 12));
+    assert_eq!(client.field_limits(), (64, 3));
+    assert_eq!(client.registration_fee(), 5_000_000);
+
+    let result = client.try_configure(&8, &12, &0, &3, &0, &admin);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidConfig.into())));
+}
+
+#[test]
+fn test_field_limits_enforced() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    client.set_field_limits(&10, &2, &admin);
+
+    let bio = Symbol::new(&env, "bio");
+    let result = client.try_set_string_field(&bio, &String::from_str(&env, "far too long"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::ValueTooLong.into())));
+
+    client.set_string_field(&bio, &String::from_str(&env, "short"), &user);
+    client.set_string_field(
+        &Symbol::new(&env, "github"),
+        &String::from_str(&env, "alice"),
+        &user,
+    );
+
+    let result = client.try_set_string_field(
+        &Symbol::new(&env, "twitter"),
+        &String::from_str(&env, "@a"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::TooManyFields.into())));
+
+    // Overwriting an existing field doesn't count against the cap
+    client.set_string_field(&bio, &String::from_str(&env, "updated"), &user);
+
+    // Removing a field frees a slot
+    client.remove_field(&bio, &user);
+    client.set_string_field(
+        &Symbol::new(&env, "twitter"),
+        &String::from_str(&env, "@a"),
+        &user,
+    );
+}
+
+#[test]
+fn test_admin_rename() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "badword001", "Someone");
+    let old = Bytes::from_slice(&env, b"badword001");
+    let new = Bytes::from_slice(&env, b"goodword001");
+
+    client.admin_rename(&user, &new, &admin);
+
+    // New name resolves, old name redirects to it and is blocked
+    let profile = client.get_by_username(&new).unwrap();
+    assert_eq!(profile.owner, user);
+    assert_eq!(client.get_by_address(&user).unwrap().username, new);
+    assert_eq!(client.get_by_username(&old).unwrap().username, new);
+    assert!(!client.is_username_available(&old));
+    let other = Address::generate(&env);
+    let result = client.try_register(
+        &String::from_str(&env, "badword001"),
+        &String::from_str(&env, "Sniper"),
+        &other,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::UsernameReserved.into())));
+
+    // Index and directory follow the rename
+    assert_eq!(client.get_by_index(&0).unwrap().username, new);
+    let page = render_path(&env, &client, "/dir/g");
+    assert!(page.contains("@goodword001"));
+    let page = render_path(&env, &client, "/dir/b");
+    assert!(!page.contains("@badword001"));
+
+    // The action is recorded in the moderation log
+    assert_eq!(client.mod_log_count(), 1);
+    let entry: ModLogEntry = client.mod_log_entry(&0).unwrap();
+    assert_eq!(entry.action, Symbol::new(&env, "rename"));
+    assert_eq!(entry.target, user);
+    assert_eq!(entry.admin, admin);
+    assert_eq!(entry.detail, old);
+
+    // Renaming onto a taken name fails
+    register_user(&env, &client, "carol001", "Carol");
+    let result = client.try_admin_rename(&user, &Bytes::from_slice(&env, b"carol001"), &admin);
+    assert_eq!(result, Err(Ok(ProfileError::UsernameTaken.into())));
+}
+
+#[test]
+fn test_bytes_avatar() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let avatar = Symbol::new(&env, "avatar");
+
+    // PNG signature followed by a couple of bytes
+    let png = Bytes::from_slice(&env, &[0x89, b'P', b'N', b'G', 0x0d, 0x0a]);
+    client.set_bytes_field(&avatar, &png, &user);
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("<img class=\"profile-avatar\" src=\"data:image/png;base64,iVBORw0K\""));
+
+    let card = to_std_string(&client.render_profile_card(&user));
+    assert!(card.contains("src=\"data:image/png;base64,iVBORw0K\" />"));
+
+    // Oversized byte avatars are rejected
+    let mut big = Bytes::new(&env);
+    big.extend_from_slice(&[0u8; 1025]);
+    let result = client.try_set_bytes_field(&avatar, &big, &user);
+    assert_eq!(result, Err(Ok(ProfileError::ValueTooLong.into())));
+
+    // String avatars still render as URLs
+    client.set_string_field(
+        &avatar,
+        &String::from_str(&env, "https://example.com/a.png"),
+        &user,
+    );
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("src=\"https://example.com/a.png\""));
+}
+
+#[test]
+fn test_banned_profile_cannot_transfer() {
+    let (env, client, admin) = setup();
+    let banned = register_user(&env, &client, "spammer001", "Spam");
+    let deleted = register_user(&env, &client, "leaver001", "Leaver");
+    let new_owner = Address::generate(&env);
+
+    client.ban_profile(&banned, &admin);
+    assert!(client.is_banned(&banned));
+    let result = client.try_transfer(&new_owner, &banned);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+
+    // Self-deleted profiles report the deletion, not a ban
+    client.delete_profile(&deleted);
+    assert!(!client.is_banned(&deleted));
+    let result = client.try_transfer(&new_owner, &deleted);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileDeleted.into())));
+}
+
+#[test]
+fn test_count_registered_between() {
+    let (env, client, _admin) = setup();
+    let day = 86_400u64;
+    let base = 1_700_000_000u64;
+
+    let names = ["alice001", "bob_smith123", "carol001", "dave001", "erin001"];
+    for (i, name) in names.iter().enumerate() {
+        env.ledger()
+            .with_mut(|li| li.timestamp = base + i as u64 * day);
+        register_user(&env, &client, name, "User");
+    }
+
+    assert_eq!(client.count_registered_between(&base, &(base + 5 * day)), 5);
+    assert_eq!(
+        client.count_registered_between(&(base + day), &(base + 3 * day)),
+        2
+    );
+    assert_eq!(
+        client.count_registered_between(&(base + day + 1), &(base + 3 * day + 1)),
+        2
+    );
+    assert_eq!(
+        client.count_registered_between(&(base + 10 * day), &(base + 20 * day)),
+        0
+    );
+    assert_eq!(
+        client.count_registered_between(&(base + 3 * day), &(base + day)),
+        0
+    );
+
+    // Deleted profiles still count as registrations
+    let dave = client
+        .get_by_username(&Bytes::from_slice(&env, b"dave001"))
+        .unwrap()
+        .owner;
+    client.delete_profile(&dave);
+    assert_eq!(client.count_registered_between(&base, &(base + 5 * day)), 5);
+
+    // So do purged ones, and their empty slot keeps the search ordered
+    let carol = client
+        .get_by_username(&Bytes::from_slice(&env, b"carol001"))
+        .unwrap()
+        .owner;
+    client.hard_delete_profile(&true, &carol);
+    assert_eq!(client.count_registered_between(&base, &(base + 5 * day)), 5);
+    assert_eq!(
+        client.count_registered_between(&(base + 3 * day), &(base + 5 * day)),
+        2
+    );
+    assert_eq!(client.count_registered_between(&base, &(base + 3 * day)), 3);
+}
+
+/// Sample validator with a different policy: no names starting with "adm".
+#[contract]
+pub struct NoAdminNamesValidator;
+
+#[contractimpl]
+impl NoAdminNamesValidator {
+    pub fn validate(_env: Env, username: Bytes) -> bool {
+        !(username.len() >= 3
+            && username.get(0) == Some(b'a')
+            && username.get(1) == Some(b'd')
+            && username.get(2) == Some(b'm'))
+    }
+}
+
+/// Sample validator accepting any lowercase name of 3-12 letters.
+#[contract]
+pub struct LettersOnlyValidator;
+
+#[contractimpl]
+impl LettersOnlyValidator {
+    pub fn validate(_env: Env, username: Bytes) -> bool {
+        (3..=12).contains(&username.len()) && username.iter().all(|b| b.is_ascii_lowercase())
+    }
+}
+
+/// Sample validator accepting lowercase letters and digits, in any order.
+#[contract]
+pub struct AlphanumericValidator;
+
+#[contractimpl]
+impl AlphanumericValidator {
+    pub fn validate(_env: Env, username: Bytes) -> bool {
+        (6..=17).contains(&username.len())
+            && username
+                .iter()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+    }
+}
+
+/// Stub registrar contract that registers names in its namespace.
+#[contract]
+pub struct StubRegistrar;
+
+#[contractimpl]
+impl StubRegistrar {
+    pub fn register_name(env: Env, profiles: Address, username: String, owner: Address) {
+        UserProfileContractClient::new(&env, &profiles).register_via_registrar(
+            &username,
+            &String::from_str(&env, "Acme Member"),
+            &owner,
+            &env.current_contract_address(),
+        );
+    }
+}
+
+/// Validator that always fails.
+#[contract]
+pub struct BrokenValidator;
+
+#[contractimpl]
+impl BrokenValidator {
+    pub fn validate(_env: Env, _username: Bytes) -> bool {
+        panic!("validator is broken")
+    }
+}
+
+#[test]
+fn test_external_username_validator() {
+    let (env, client, admin) = setup();
+    let user = Address::generate(&env);
+
+    // Combined with the built-in rules
+    let no_admin = env.register(NoAdminNamesValidator, ());
+    client.set_username_validator(&Some(no_admin.clone()), &false, &admin);
+    assert_eq!(client.username_validator(), Some((no_admin, false)));
+
+    assert!(!client.is_valid_username(&Bytes::from_slice(&env, b"admin001")));
+    assert!(client.is_valid_username(&Bytes::from_slice(&env, b"alice001")));
+    assert!(!client.is_valid_username(&Bytes::from_slice(&env, b"alice")));
+    let result = client.try_register(
+        &String::from_str(&env, "admin001"),
+        &String::from_str(&env, "Admin"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::InvalidUsername.into())));
+
+    // Replacing the built-in rules
+    let letters = env.register(LettersOnlyValidator, ());
+    client.set_username_validator(&Some(letters), &true, &admin);
+    assert!(client.is_valid_username(&Bytes::from_slice(&env, b"alice")));
+    assert!(!client.is_valid_username(&Bytes::from_slice(&env, b"alice001")));
+    client.register(
+        &String::from_str(&env, "alice"),
+        &String::from_str(&env, "Alice"),
+        &user,
+    );
+    assert!(client
+        .get_by_username(&Bytes::from_slice(&env, b"alice"))
+        .is_some());
+
+    // A broken validator falls back to the built-in rules
+    let broken = env.register(BrokenValidator, ());
+    client.set_username_validator(&Some(broken), &true, &admin);
+    assert!(client.is_valid_username(&Bytes::from_slice(&env, b"bob_smith123")));
+    assert!(!client.is_valid_username(&Bytes::from_slice(&env, b"bob")));
+    register_user(&env, &client, "bob_smith123", "Bob");
+
+    client.set_username_validator(&None, &false, &admin);
+    assert_eq!(client.username_validator(), None);
+}
+
+#[test]
+fn test_register_while_paused() {
+    let (env, client, admin) = setup();
+    let user = Address::generate(&env);
+
+    client.set_paused(&true, &admin);
+    assert!(client.is_paused());
+
+    let page = render_path_as(&env, &client, "/register", Some(user.clone()));
+    assert!(page.contains("Registrations temporarily closed"));
+    assert!(!page.contains("form:register"));
+
+    let result = client.try_register(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::RegistrationPaused.into())));
+
+    client.set_paused(&false, &admin);
+    let page = render_path_as(&env, &client, "/register", Some(user.clone()));
+    assert!(!page.contains("Registrations temporarily closed"));
+    assert!(page.contains("form:register"));
+    client.register(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &user,
+    );
+}
+
+#[test]
+fn test_render_embed() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, "Hello there"),
+        &user,
+    );
+    client.set_string_field(
+        &Symbol::new(&env, "avatar"),
+        &String::from_str(&env, "https://example.com/a.png"),
+        &user,
+    );
+
+    assert_eq!(
+        render_path(&env, &client, "/embed/u/alice001"),
+        "<div class=\"profile-embed\">\
+         <img class=\"profile-avatar\" src=\"https://example.com/a.png\" alt=\"Avatar\" />\
+         <div class=\"profile-embed-name\">Alice</div>\
+         <div class=\"profile-embed-username\">@alice001</div>\
+         <p class=\"profile-embed-bio\">Hello there</p>\
+         <a href=\"render:@profile:/u/alice001\">View full profile</a>\
+         </div>"
+    );
+
+    assert_eq!(
+        render_path(&env, &client, "/embed/u/nobody001"),
+        "<div class=\"profile-embed-missing\">Profile not found</div>"
+    );
+}
+
+#[test]
+fn test_profile_aliases() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "acme001", "Acme");
+    let alias = Bytes::from_slice(&env, b"acmecorp123");
+
+    client.add_alias(&alias, &user);
+    assert_eq!(
+        client.aliases(&user),
+        Vec::from_array(&env, [alias.clone()])
+    );
+    assert!(!client.is_username_available(&alias));
+
+    let primary = client
+        .get_by_username(&Bytes::from_slice(&env, b"acme001"))
+        .unwrap();
+    let via_alias = client.get_by_username(&alias).unwrap();
+    assert_eq!(via_alias.owner, user);
+    assert_eq!(via_alias.username, primary.username);
+
+    // Aliases must be free
+    let other = register_user(&env, &client, "bob001", "Bob");
+    let result = client.try_add_alias(&alias, &other);
+    assert_eq!(result, Err(Ok(ProfileError::UsernameTaken.into())));
+    let result = client.try_add_alias(&Bytes::from_slice(&env, b"acme001"), &other);
+    assert_eq!(result, Err(Ok(ProfileError::UsernameTaken.into())));
+
+    // Aliases follow the profile on transfer
+    let new_owner = Address::generate(&env);
+    client.transfer(&new_owner, &user);
+    assert_eq!(client.get_by_username(&alias).unwrap().owner, new_owner);
+    assert_eq!(client.aliases(&new_owner).len(), 1);
+    assert_eq!(client.aliases(&user).len(), 0);
+
+    // Deleting releases them
+    client.delete_profile(&new_owner);
+    assert!(client.get_by_username(&alias).is_none());
+    assert!(client.is_username_available(&alias));
+}
+
+#[test]
+fn test_alias_limit() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "acme001", "Acme");
+
+    for i in 0..5 {
+        let alias = std::format!("acme{}00", i + 1);
+        client.add_alias(&Bytes::from_slice(&env, alias.as_bytes()), &user);
+    }
+
+    let result = client.try_add_alias(&Bytes::from_slice(&env, b"acme600"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::TooManyAliases.into())));
+
+    let result = client.try_add_alias(&Bytes::from_slice(&env, b"ac-me600"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidUsername.into())));
+}
+
+#[test]
+fn test_created_at_ledger() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| {
+        li.timestamp = 1_700_000_000;
+        li.sequence_number = 4_242;
+    });
+
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let profile = client.get_by_address(&user).unwrap();
+    assert_eq!(profile.created_at, 1_700_000_000);
+    assert_eq!(client.created_at_ledger(&user), Some(4_242));
+
+    // The sequence moves with the profile
+    let new_owner = Address::generate(&env);
+    client.transfer(&new_owner, &user);
+    assert_eq!(client.created_at_ledger(&new_owner), Some(4_242));
+    assert_eq!(client.created_at_ledger(&user), None);
+}
+
+#[test]
+fn test_created_at_ledger_legacy() {
+    let (env, client, _admin) = setup();
+    let user = Address::generate(&env);
+
+    // A legacy profile stored its ledger sequence in created_at
+    let profile = Profile::new(
+        Bytes::from_slice(&env, b"legacy001"),
+        String::from_str(&env, "Legacy"),
+        user.clone(),
+        12_345,
+    );
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(user.clone()), &profile);
+    });
+
+    assert_eq!(client.created_at_ledger(&user), Some(12_345));
+}
+
+#[test]
+fn test_is_field_frozen() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+    let github = Symbol::new(&env, "github");
+
+    assert!(!client.is_field_frozen(&user, &bio));
+
+    client.set_string_field(&bio, &String::from_str(&env, "Original bio"), &user);
+    client.freeze_field(&bio, &user);
+
+    assert!(client.is_field_frozen(&user, &bio));
+    assert!(!client.is_field_frozen(&user, &github));
+}
+
+#[test]
+fn test_hard_delete_profile() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let username = Bytes::from_slice(&env, b"alice001");
+    let alias = Bytes::from_slice(&env, b"alicia001");
+    let bio = Symbol::new(&env, "bio");
+    let custom = Symbol::new(&env, "custom");
+
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+    client.set_int_field(&custom, &7, &user);
+    client.freeze_field(&bio, &user);
+    client.set_locale(&Symbol::new(&env, "en"), &user);
+    client.add_alias(&alias, &user);
+
+    client.hard_delete_profile(&false, &user);
+
+    let keys = [
+        ProfileKey::Profile(user.clone()),
+        ProfileKey::Field(user.clone(), bio.clone()),
+        ProfileKey::Field(user.clone(), custom),
+        ProfileKey::FrozenField(user.clone(), bio),
+        ProfileKey::FieldIndex(user.clone()),
+        ProfileKey::Locale(user.clone()),
+        ProfileKey::CreatedAtSeq(user.clone()),
+        ProfileKey::Aliases(user.clone()),
+        ProfileKey::Username(username.clone()),
+        ProfileKey::Username(alias.clone()),
+        ProfileKey::IndexSlot(username.clone()),
+        ProfileKey::ProfileIndex(0),
+        ProfileKey::DirectorySlot(username.clone()),
+    ];
+    env.as_contract(&client.address, || {
+        for key in keys.iter() {
+            assert!(
+                !env.storage().persistent().has(key),
+                "{:?} was not removed",
+                key
+            );
+        }
+    });
+
+    assert!(client.get_by_address(&user).is_none());
+    assert!(client.get_by_username(&username).is_none());
+    assert!(client.get_by_index(&0).is_none());
+
+    // The username stays reserved; the alias is released
+    assert!(!client.is_username_available(&username));
+    assert!(client.is_username_available(&alias));
+
+    // Irreversible: nothing left to delete or purge
+    let result = client.try_hard_delete_profile(&false, &user);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileNotFound.into())));
+
+    // The directory entry is blanked, so a new name is listed once
+    client.register(
+        &String::from_str(&env, "alex001"),
+        &String::from_str(&env, "Alex"),
+        &user,
+    );
+    let page = render_path(&env, &client, "/dir/a");
+    assert_eq!(page.matches("[@alex001]").count(), 1);
+}
+
+#[test]
+fn test_hard_delete_release_username() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let username = Bytes::from_slice(&env, b"alice001");
+
+    client.hard_delete_profile(&true, &user);
+    assert!(client.is_username_available(&username));
+
+    // Someone else can claim it, and the old index slot doesn't follow
+    let other = register_user(&env, &client, "alice001", "Other Alice");
+    assert_eq!(client.get_by_username(&username).unwrap().owner, other);
+    assert!(client.get_by_index(&0).is_none());
+    assert_eq!(client.get_by_index(&1).unwrap().owner, other);
+
+    // A banned profile cannot free its username
+    client.ban_profile(&other, &admin);
+    let result = client.try_hard_delete_profile(&true, &other);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+
+    client.hard_delete_profile(&false, &other);
+    assert!(client.is_banned(&other));
+    assert!(!client.is_username_available(&username));
+
+    // Nor can the banned address start over under a new name
+    let result = client.try_register(
+        &String::from_str(&env, "fresh001"),
+        &String::from_str(&env, "Fresh"),
+        &other,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+}
+
+#[test]
+fn test_address_truncation() {
+    let (env, client, admin) = setup();
+    let stranger = Address::generate(&env);
+    let full = stranger.to_string().to_string();
+
+    assert_eq!(client.address_truncation(), (4, 4));
+    let short = to_std_string(&client.render_username(&stranger));
+    assert_eq!(short, std::format!("{}...{}", &full[..4], &full[52..]));
+
+    client.set_address_truncation(&6, &6, &admin);
+    assert_eq!(client.address_truncation(), (6, 6));
+    let wide = to_std_string(&client.render_username(&stranger));
+    assert_eq!(wide.len(), 15);
+    assert_eq!(wide, std::format!("{}...{}", &full[..6], &full[50..]));
+
+    // Lengths covering the whole address render it in full
+    client.set_address_truncation(&40, &40, &admin);
+    assert_eq!(to_std_string(&client.render_username(&stranger)), full);
+}
+
+#[test]
+fn test_register_full() {
+    let (env, client, _admin) = setup();
+    let user = Address::generate(&env);
+    let bio = Symbol::new(&env, "bio");
+    let dweb = Symbol::new(&env, "dweb");
+    let hiring = Symbol::new(&env, "hiring");
+
+    let mut fields = soroban_sdk::Map::new(&env);
+    fields.set(
+        bio.clone(),
+        FieldValue::StringField(String::from_str(&env, "Hello")),
+    );
+    fields.set(
+        dweb.clone(),
+        FieldValue::StringField(String::from_str(&env, "ipfs://bafyalice")),
+    );
+    fields.set(hiring.clone(), FieldValue::BoolField(true));
+
+    client.register_full(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &fields,
+        &user,
+    );
+
+    let profile = client
+        .get_by_username(&Bytes::from_slice(&env, b"alice001"))
+        .unwrap();
+    assert_eq!(profile.owner, user);
+    assert_eq!(
+        client
+            .get_fields(&user, &Vec::from_array(&env, [bio, dweb, hiring]))
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn test_register_full_is_atomic() {
+    let (env, client, _admin) = setup();
+    let user = Address::generate(&env);
+
+    let mut fields = soroban_sdk::Map::new(&env);
+    fields.set(
+        Symbol::new(&env, "bio"),
+        FieldValue::StringField(String::from_str(&env, "Hello")),
+    );
+    fields.set(
+        Symbol::new(&env, "dweb"),
+        FieldValue::StringField(String::from_str(&env, "https://example.com")),
+    );
+
+    let result = client.try_register_full(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &fields,
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::InvalidFieldValue.into())));
+
+    // Nothing was registered
+    assert!(client.get_by_address(&user).is_none());
+    assert!(client.is_username_available(&Bytes::from_slice(&env, b"alice001")));
+    assert_eq!(client.profile_count(), 0);
+
+    // Too many fields are rejected up front
+    let mut fields = soroban_sdk::Map::new(&env);
+    for i in 0..11u32 {
+        fields.set(
+            Symbol::new(&env, &std::format!("field{}", i)),
+            FieldValue::IntField(i as i128),
+        );
+    }
+    let result = client.try_register_full(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &fields,
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::BatchTooLarge.into())));
+}
+
+#[test]
+fn test_export_my_profile() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+    let hiring = Symbol::new(&env, "hiring");
+
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+    client.set_bool_field(&hiring, &true, &user);
+
+    let names = Vec::from_array(
+        &env,
+        [bio.clone(), hiring.clone(), Symbol::new(&env, "github")],
+    );
+    let (profile, fields) = client.export_my_profile(&names, &user);
+
+    assert_eq!(profile.owner, user);
+    assert_eq!(profile.username, Bytes::from_slice(&env, b"alice001"));
+    assert_eq!(profile.display_name, String::from_str(&env, "Alice"));
+    assert_eq!(fields.len(), 2);
+    assert_eq!(
+        fields.get(bio).unwrap().as_string(),
+        Some(&String::from_str(&env, "Hello"))
+    );
+    assert_eq!(fields.get(hiring).unwrap().as_bool(), Some(true));
+
+    let stranger = Address::generate(&env);
+    let result = client.try_export_my_profile(&names, &stranger);
+    assert_eq!(result.err(), Some(Ok(ProfileError::ProfileNotFound.into())));
+}
+
+#[test]
+fn test_registration_throttle() {
+    let (env, client, admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+
+    // Disabled by default
+    assert_eq!(client.registration_throttle(), None);
+    register_user(&env, &client, "alice001", "Alice");
+    assert_eq!(client.registrations_in_window(), 0);
+
+    client.set_registration_throttle(&2, &3_600, &0, &admin);
+    register_user(&env, &client, "bob001", "Bob");
+    register_user(&env, &client, "carol001", "Carol");
+    assert_eq!(client.registrations_in_window(), 2);
+
+    let user = Address::generate(&env);
+    let result = client.try_register(
+        &String::from_str(&env, "dave001"),
+        &String::from_str(&env, "Dave"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::ThrottleExceeded.into())));
+    assert_eq!(client.registrations_in_window(), 2);
+
+    // The window rolls over
+    env.ledger().with_mut(|li| li.timestamp = 1_700_003_600);
+    assert_eq!(client.registrations_in_window(), 0);
+    client.register(
+        &String::from_str(&env, "dave001"),
+        &String::from_str(&env, "Dave"),
+        &user,
+    );
+    assert_eq!(client.registrations_in_window(), 1);
+}
+
+#[test]
+fn test_registration_throttle_surge() {
+    let (env, client, admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+
+    client.set_registration_fee(&100, &admin);
+    client.set_registration_throttle(&1, &60, &5, &admin);
+    assert_eq!(client.current_registration_fee(), 100);
+
+    register_user(&env, &client, "alice001", "Alice");
+    assert_eq!(client.current_registration_fee(), 500);
+
+    // The multiplier is only quoted; the window is still enforced
+    let result = client.try_register(
+        &String::from_str(&env, "bob001"),
+        &String::from_str(&env, "Bob"),
+        &Address::generate(&env),
+    );
+    assert_eq!(result, Err(Ok(ProfileError::ThrottleExceeded.into())));
+    assert_eq!(client.registrations_in_window(), 1);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_060);
+    assert_eq!(client.current_registration_fee(), 100);
+
+    let result = client.try_set_registration_throttle(&1, &0, &0, &admin);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidConfig.into())));
+
+    client.set_registration_throttle(&0, &0, &0, &admin);
+    assert_eq!(client.registration_throttle(), None);
+}
+
+#[test]
+fn test_render_profile_meta() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let long_bio = "Builder of things.\nLoves Soroban. ".repeat(10);
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, &long_bio),
+        &user,
+    );
+    client.set_string_field(
+        &Symbol::new(&env, "avatar"),
+        &String::from_str(&env, "https://example.com/a.png"),
+        &user,
+    );
+
+    let meta = to_std_string(&client.render_profile_meta(&Bytes::from_slice(&env, b"alice001")));
+    let lines: std::vec::Vec<&str> = meta.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "title: Alice");
+    assert_eq!(lines[2], "image: https://example.com/a.png");
+
+    let description = lines[1].strip_prefix("description: ").unwrap();
+    assert!(description.starts_with("Builder of things. Loves Soroban."));
+    assert!(description.ends_with("..."));
+    assert_eq!(description.len(), 160 + 3);
+
+    assert!(client
+        .render_profile_meta(&Bytes::from_slice(&env, b"nobody001"))
+        .is_empty());
+}
+
+#[test]
+fn test_date_style() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_709_164_800);
+    let user = register_user(&env, &client, "alice001", "Alice");
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_709_164_800 + 3 * 86_400);
+
+    assert_eq!(client.date_style(&user), DateStyle::Full);
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("Created: 2024-02-29 00:00:00 UTC"));
+
+    client.set_date_style(&DateStyle::Relative, &user);
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("Created: 3 days ago"));
+
+    client.set_date_style(&DateStyle::DateOnly, &user);
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("Created: 2024-02-29 ·"));
+
+    // Cards always use the short form
+    client.set_date_style(&DateStyle::Relative, &user);
+    let card = to_std_string(&client.render_profile_card(&user));
+    assert!(card.contains("Joined 2024-02-29</span>"));
+}
+
+#[test]
+fn test_username_of() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+
+    assert_eq!(
+        client.username_of(&user),
+        Some(Bytes::from_slice(&env, b"alice001"))
+    );
+    assert_eq!(client.username_of(&Address::generate(&env)), None);
+
+    client.delete_profile(&user);
+    assert_eq!(client.username_of(&user), None);
+}
+
+#[test]
+fn test_profile_path_variants() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, "Hello"),
+        &user,
+    );
+
+    let canonical = render_path(&env, &client, "/u/alice001");
+    assert!(canonical.contains("Alice"));
+
+    for path in [
+        "/u/Alice001",
+        "/u/alice001/",
+        "/u/ALICE001/",
+        "/@alice001",
+        "/@Alice001/",
+    ] {
+        assert_eq!(render_path(&env, &client, path), canonical, "{}", path);
+    }
+
+    assert!(render_path(&env, &client, "/@nobody001").contains("Profile Not Found"));
+}
+
+#[test]
+fn test_banned_profile_cannot_edit() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+
+    client.ban_profile(&user, &admin);
+
+    let result = client.try_set_string_field(&bio, &String::from_str(&env, "Back"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+    let result = client.try_set_display_name(&String::from_str(&env, "Alicia"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+    let result = client.try_remove_field(&bio, &user);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileBanned.into())));
+
+    // Self-deleted profiles still report ProfileDeleted
+    let other = register_user(&env, &client, "bob001", "Bob");
+    client.delete_profile(&other);
+    let result = client.try_set_string_field(&bio, &String::from_str(&env, "Back"), &other);
+    assert_eq!(result, Err(Ok(ProfileError::ProfileDeleted.into())));
+}
+
+#[test]
+fn test_claim_reserved_username() {
+    let (env, client, admin) = setup();
+    let brand = Address::generate(&env);
+    let username = Bytes::from_slice(&env, b"stellarorg001");
+
+    client.reserve_username_with_condition(&username, &brand, &Symbol::new(&env, "none"), &admin);
+    assert!(!client.is_username_available(&username));
+
+    // Only the beneficiary can claim, and regular registration stays blocked
+    let squatter = Address::generate(&env);
+    let result = client.try_claim_reserved(&username, &squatter);
+    assert_eq!(result, Err(Ok(ProfileError::NotAuthorized.into())));
+    let result = client.try_register(
+        &String::from_str(&env, "stellarorg001"),
+        &String::from_str(&env, "Stellar"),
+        &squatter,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::UsernameReserved.into())));
+
+    client.claim_reserved(&username, &brand);
+    let profile = client.get_by_username(&username).unwrap();
+    assert_eq!(profile.owner, brand);
+    assert_eq!(
+        profile.display_name,
+        String::from_str(&env, "stellarorg001")
+    );
+
+    // The assignment is used up
+    let result = client.try_claim_reserved(&username, &brand);
+    assert_eq!(result, Err(Ok(ProfileError::NotAuthorized.into())));
+}
+
+#[test]
+fn test_claim_reserved_condition_fails_closed() {
+    let (env, client, admin) = setup();
+    let brand = Address::generate(&env);
+    let username = Bytes::from_slice(&env, b"stellarorg001");
+
+    client.reserve_username_with_condition(
+        &username,
+        &brand,
+        &Symbol::new(&env, "verified"),
+        &admin,
+    );
+
+    let result = client.try_claim_reserved(&username, &brand);
+    assert_eq!(result, Err(Ok(ProfileError::ConditionNotMet.into())));
+    assert!(client.get_by_username(&username).is_none());
+    assert!(!client.is_username_available(&username));
+
+    // Unreserving drops the assignment
+    client.unreserve_username(&username, &admin);
+    let result = client.try_claim_reserved(&username, &brand);
+    assert_eq!(result, Err(Ok(ProfileError::NotAuthorized.into())));
+}
+
+#[test]
+fn test_render_stats() {
+    let (env, client, admin) = setup();
+    register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob002", "Bob");
+    let carol = register_user(&env, &client, "carol003", "Carol");
+    client.delete_profile(&bob);
+    client.ban_profile(&carol, &admin);
+    client.reserve_username(&Bytes::from_slice(&env, b"stellar123"), &admin);
+    client.reserve_username(&Bytes::from_slice(&env, b"soroban123"), &admin);
+    client.set_registration_fee(&500, &admin);
+
+    assert_eq!(client.active_profile_count(), 1);
+    assert_eq!(client.reserved_count(), 2);
+
+    let output = render_path(&env, &client, "/stats");
+    assert!(output.contains("**Total profiles:** 3"));
+    assert!(output.contains("**Active profiles:** 1"));
+    assert!(output.contains("**Reserved usernames:** 2"));
+    assert!(output.contains("**Registration fee:** 500 stroops"));
+
+    // Reserving twice counts once; unreserving uncounts
+    client.reserve_username(&Bytes::from_slice(&env, b"stellar123"), &admin);
+    client.unreserve_username(&Bytes::from_slice(&env, b"soroban123"), &admin);
+    assert!(render_path(&env, &client, "/stats").contains("**Reserved usernames:** 1"));
+}
+
+#[test]
+fn test_expect_profile() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob002", "Bob");
+    let carol = register_user(&env, &client, "carol003", "Carol");
+    let stranger = Address::generate(&env);
+
+    assert_eq!(
+        client.expect_by_address(&alice).username,
+        Bytes::from_slice(&env, b"alice001")
+    );
+    assert_eq!(
+        client
+            .expect_by_username(&Bytes::from_slice(&env, b"alice001"))
+            .owner,
+        alice
+    );
+
+    assert_eq!(
+        client.try_expect_by_address(&stranger).err(),
+        Some(Ok(ProfileError::ProfileNotFound.into()))
+    );
+    assert_eq!(
+        client
+            .try_expect_by_username(&Bytes::from_slice(&env, b"nobody001"))
+            .err(),
+        Some(Ok(ProfileError::ProfileNotFound.into()))
+    );
+
+    client.delete_profile(&bob);
+    assert_eq!(
+        client.try_expect_by_address(&bob).err(),
+        Some(Ok(ProfileError::ProfileDeleted.into()))
+    );
+
+    client.ban_profile(&carol, &admin);
+    assert_eq!(
+        client.try_expect_by_address(&carol).err(),
+        Some(Ok(ProfileError::ProfileBanned.into()))
+    );
+    assert_eq!(
+        client
+            .try_expect_by_username(&Bytes::from_slice(&env, b"carol003"))
+            .err(),
+        Some(Ok(ProfileError::ProfileBanned.into()))
+    );
+}
+
+#[test]
+fn test_edit_form_ttl_warning() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| li.sequence_number = 5_000_000);
+    let user = register_user(&env, &client, "alice001", "Alice");
+
+    assert_eq!(client.profile_ttl_remaining(&user), Some(2_592_000));
+    let output = render_path_as(&env, &client, "/edit", Some(user.clone()));
+    assert!(!output.contains("profile-ttl-warning"));
+
+    // Simulate a profile last extended long ago
+    env.as_contract(&client.address, || {
+        env.storage().persistent().set(
+            &ProfileKey::TtlExtendedAt(user.clone()),
+            &(5_000_000u32 - 2_592_000 + 17_280),
+        );
+    });
+    assert_eq!(client.profile_ttl_remaining(&user), Some(17_280));
+
+    let output = render_path_as(&env, &client, "/edit", Some(user.clone()));
+    assert!(output.contains("<div class=\"profile-ttl-warning\">"));
+    assert!(output.contains("Estimated time left: 1 day"));
+    assert!(output.contains("name=\"address\" value=\""));
+    assert!(output.contains("(form:bump_profile)"));
+
+    client.bump_profile(&user);
+    assert_eq!(client.profile_ttl_remaining(&user), Some(2_592_000));
+    let output = render_path_as(&env, &client, "/edit", Some(user.clone()));
+    assert!(!output.contains("profile-ttl-warning"));
+}
+
+#[test]
+fn test_app_card_prefs() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let user = register_user(&env, &client, "alice001", "Alice");
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, "Speedrunner"),
+        &user,
+    );
+    client.set_string_field(
+        &Symbol::new(&env, "avatar"),
+        &String::from_str(&env, "https://example.com/a.png"),
+        &user,
+    );
+
+    // Apps without prefs get the standard card
+    let game = Address::generate(&env);
+    assert_eq!(client.app_card_prefs(&game), None);
+    assert_eq!(
+        client.render_profile_card_for_app(&user, &game),
+        client.render_profile_card(&user)
+    );
+
+    let prefs = CardOptions {
+        compact: false,
+        show_avatar: false,
+        show_bio: true,
+        show_joined: false,
+        show_dweb: false,
+        text: false,
+    };
+    client.set_app_card_prefs(&game, &prefs, &game);
+    assert_eq!(client.app_card_prefs(&game), Some(prefs));
+
+    let card = to_std_string(&client.render_profile_card_for_app(&user, &game));
+    assert!(card.contains("<span class=\"profile-bio\">Speedrunner</span>"));
+    assert!(!card.contains("profile-avatar"));
+    assert!(!card.contains("profile-joined"));
+
+    let standard = to_std_string(&client.render_profile_card(&user));
+    assert!(standard.contains("profile-avatar"));
+    assert!(standard.contains("profile-joined"));
+    assert!(!standard.contains("profile-bio"));
+
+    let forum = Address::generate(&env);
+    client.set_app_card_prefs(
+        &forum,
+        &CardOptions {
+            compact: true,
+            ..CardOptions::default()
+        },
+        &forum,
+    );
+    assert_eq!(
+        client.render_profile_card_for_app(&user, &forum),
+        client.render_profile_card_compact(&user)
+    );
+
+    // The dweb link shows only where an app asks for it
+    client.set_string_field(
+        &Symbol::new(&env, "dweb"),
+        &String::from_str(
+            &env,
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+        ),
+        &user,
+    );
+    assert!(!to_std_string(&client.render_profile_card(&user)).contains("profile-dweb"));
+    let site = Address::generate(&env);
+    client.set_app_card_prefs(
+        &site,
+        &CardOptions {
+            show_dweb: true,
+            ..CardOptions::default()
+        },
+        &site,
+    );
+    let card = to_std_string(&client.render_profile_card_for_app(&user, &site));
+    assert!(card.contains(
+        "<a class=\"profile-dweb\" href=\"ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi\">Visit site <span class=\"profile-dweb-scheme\">ipfs</span></a>"
+    ));
+
+    // Only the app itself may set its prefs
+    let result = client.try_set_app_card_prefs(&game, &CardOptions::default(), &user);
+    assert_eq!(result, Err(Ok(ProfileError::NotAuthorized.into())));
+}
+
+#[test]
+fn test_verified_link() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let github = Symbol::new(&env, "github");
+    let handle = String::from_str(&env, "alice");
+    let proof = BytesN::from_array(&env, &[7u8; 32]);
+
+    assert_eq!(client.verified_link(&user, &github), None);
+    client.set_string_field(&github, &handle, &user);
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(!page.contains("profile-verified"));
+
+    client.set_verified_link(&github, &handle, &proof, &user);
+    assert_eq!(
+        client.verified_link(&user, &github),
+        Some((handle.clone(), proof.clone()))
+    );
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains(
+        "**GitHub:** alice <span class=\"profile-verified\" title=\"Verified\">✓</span>"
+    ));
+
+    // Changing the field to a different handle drops the checkmark
+    client.set_string_field(&github, &String::from_str(&env, "mallory"), &user);
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(!page.contains("profile-verified"));
+    assert_eq!(
+        client.verified_link(&user, &github),
+        Some((handle.clone(), proof.clone()))
+    );
+
+    // Links are capped at the field limit
+    let keybase = Symbol::new(&env, "keybase");
+    client.set_field_limits(&256, &2, &admin);
+    client.set_verified_link(&keybase, &handle, &proof, &user);
+    assert_eq!(
+        client.try_set_verified_link(&Symbol::new(&env, "gitlab"), &handle, &proof, &user),
+        Err(Ok(ProfileError::TooManyFields.into()))
+    );
+
+    // A purge removes links whose platform is not, or no longer, a field
+    client.remove_field(&github, &user);
+    client.hard_delete_profile(&true, &user);
+    assert_eq!(client.verified_link(&user, &github), None);
+    assert_eq!(client.verified_link(&user, &keybase), None);
+}
+
+#[test]
+fn test_former_usernames_redirect() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let names = ["alice002", "alice003", "alice004", "alice005"];
+
+    client.admin_rename(&user, &Bytes::from_slice(&env, names[0].as_bytes()), &admin);
+
+    // Old links resolve to the renamed profile
+    let old = Bytes::from_slice(&env, b"alice001");
+    assert_eq!(client.get_by_username(&old).unwrap().owner, user);
+    assert_eq!(
+        client.canonical_username(&old),
+        Some(Bytes::from_slice(&env, b"alice002"))
+    );
+    assert!(render_path(&env, &client, "/u/alice001").contains("**@alice002**"));
+    assert!(!client.is_username_available(&old));
+
+    for name in &names[1..] {
+        client.admin_rename(&user, &Bytes::from_slice(&env, name.as_bytes()), &admin);
+    }
+
+    // Only the last three former names still redirect, all to the current name
+    let current = Bytes::from_slice(&env, b"alice005");
+    assert_eq!(client.former_usernames(&user).len(), 3);
+    assert_eq!(client.canonical_username(&old), None);
+    assert!(client.get_by_username(&old).is_none());
+    for name in ["alice002", "alice003", "alice004"] {
+        let former = Bytes::from_slice(&env, name.as_bytes());
+        assert_eq!(client.canonical_username(&former), Some(current.clone()));
+    }
+    assert_eq!(client.canonical_username(&current), Some(current.clone()));
+    assert_eq!(
+        client.canonical_username(&Bytes::from_slice(&env, b"nobody001")),
+        None
+    );
+}
+
+#[test]
+fn test_soft_reserve() {
+    let (env, client, admin) = setup();
+    let user = Address::generate(&env);
+    let soft = Bytes::from_slice(&env, b"admin001");
+    let hard = Bytes::from_slice(&env, b"stellar123");
+
+    client.soft_reserve(&soft, &admin);
+    client.reserve_username(&hard, &admin);
+    assert_eq!(
+        client.reservation_status(&Bytes::from_slice(&env, b"alice001")),
+        RESERVATION_FREE
+    );
+    assert_eq!(client.reservation_status(&soft), RESERVATION_SOFT);
+    assert_eq!(client.reservation_status(&hard), RESERVATION_HARD);
+
+    // Soft-reserved names stay available, and the form warns about them
+    assert!(client.is_username_available(&soft));
+    let form = render_path_as(&env, &client, "/register/admin001", Some(user.clone()));
+    assert!(form.contains("discouraged by the registry"));
+    assert!(form.contains("name=\"username\" value=\"admin001\""));
+    let form = render_path_as(&env, &client, "/register/alice001", Some(user.clone()));
+    assert!(!form.contains("discouraged by the registry"));
+
+    client.register(
+        &String::from_str(&env, "admin001"),
+        &String::from_str(&env, "Admin"),
+        &user,
+    );
+    assert_eq!(client.reservation_status(&soft), RESERVATION_TAKEN);
+
+    client.unreserve_username(&hard, &admin);
+    assert_eq!(client.reservation_status(&hard), RESERVATION_FREE);
+}
+
+#[test]
+fn test_gravatar_fallback() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let avatar = Symbol::new(&env, "avatar");
+    let card =
+        |client: &UserProfileContractClient| to_std_string(&client.render_profile_card(&user));
+
+    assert!(!card(&client).contains("profile-avatar"));
+
+    let mut hash = [0u8; 16];
+    hash[0] = 0xab;
+    hash[15] = 0x01;
+    client.set_avatar_email_hash(&Bytes::from_slice(&env, &hash), &user);
+    assert!(card(&client).contains(
+        "src=\"https://gravatar.com/avatar/ab000000000000000000000000000001?d=identicon\""
+    ));
+
+    // An avatar URL takes precedence over the hash
+    client.set_string_field(
+        &avatar,
+        &String::from_str(&env, "https://example.com/a.png"),
+        &user,
+    );
+    let output = card(&client);
+    assert!(output.contains("src=\"https://example.com/a.png\""));
+    assert!(!output.contains("gravatar.com"));
+
+    // As does an on-chain image
+    client.set_bytes_field(&avatar, &Bytes::from_slice(&env, b"GIF89a"), &user);
+    assert!(card(&client).contains("src=\"data:image/gif;base64,"));
+
+    // Unrecognised bytes fall back to the hash
+    client.set_bytes_field(&avatar, &Bytes::from_slice(&env, b"nope"), &user);
+    assert!(card(&client).contains("gravatar.com/avatar/ab00"));
+
+    let result = client.try_set_avatar_email_hash(&Bytes::from_slice(&env, b"short"), &user);
+    assert_eq!(result, Err(Ok(ProfileError::InvalidFieldValue.into())));
+
+    client.set_avatar_email_hash(&Bytes::new(&env), &user);
+    assert!(!card(&client).contains("profile-avatar"));
+}
+
+#[test]
+fn test_profile_links() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let add = |label: &str, url: &str| {
+        client.add_link(
+            &String::from_str(&env, label),
+            &String::from_str(&env, url),
+            &user,
+        )
+    };
+
+    add("YouTube", "https://youtube.com/@alice");
+    add("Mastodon", "https://mastodon.social/@alice");
+    add("[Lens]", "https://hey.xyz/u/alice");
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains(
+        "- [YouTube](https://youtube.com/@alice)\n\
+         - [Mastodon](https://mastodon.social/@alice)\n\
+         - [\\[Lens\\]](https://hey.xyz/u/alice)\n"
+    ));
+
+    // Reorder must be a permutation
+    let vec = |items: &[u32]| Vec::from_slice(&env, items);
+    for bad in [vec(&[0, 1]), vec(&[0, 0, 1]), vec(&[0, 1, 3])] {
+        let result = client.try_reorder_links(&bad, &user);
+        assert_eq!(result, Err(Ok(ProfileError::InvalidFieldValue.into())));
+    }
+    client.reorder_links(&vec(&[2, 0, 1]), &user);
+    let links = client.links_of(&user);
+    assert_eq!(links.get(0).unwrap().0, String::from_str(&env, "[Lens]"));
+    assert_eq!(links.get(2).unwrap().0, String::from_str(&env, "Mastodon"));
+
+    client.remove_link(&0, &user);
+    assert_eq!(client.links_of(&user).len(), 2);
+    assert_eq!(
+        client.try_remove_link(&5, &user),
+        Err(Ok(ProfileError::InvalidFieldValue.into()))
+    );
+
+    // URLs are validated
+    for url in [
+        "javascript:alert(1)",
+        "https://x.com/a)[b](c",
+        "https://x.com/a b",
+    ] {
+        let result = client.try_add_link(
+            &String::from_str(&env, "Bad"),
+            &String::from_str(&env, url),
+            &user,
+        );
+        assert_eq!(result, Err(Ok(ProfileError::InvalidFieldValue.into())));
+    }
+
+    // Capped at ten links
+    for _ in 0..8 {
+        add("Site", "https://example.com");
+    }
+    let result = client.try_add_link(
+        &String::from_str(&env, "Site"),
+        &String::from_str(&env, "https://example.com"),
+        &user,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::TooManyLinks.into())));
+}
+
+#[test]
+fn test_admin_clear_field_for() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob002", "Bob");
+    let carol = register_user(&env, &client, "carol003", "Carol");
+    let homepage = Symbol::new(&env, "homepage");
+    let bio = Symbol::new(&env, "bio");
+    let bad = String::from_str(&env, "https://malware.example");
+
+    client.set_string_field(&homepage, &bad, &alice);
+    client.set_string_field(&homepage, &bad, &bob);
+    client.set_string_field(&bio, &String::from_str(&env, "Hi"), &bob);
+
+    let targets = Vec::from_array(&env, [alice.clone(), bob.clone(), carol.clone()]);
+    client.admin_clear_field_for(&targets, &homepage, &admin);
+
+    assert!(client.get_field(&alice, &homepage).is_none());
+    assert!(client.get_field(&bob, &homepage).is_none());
+    assert!(client.get_field(&bob, &bio).is_some());
+
+    // One moderation entry per profile actually cleared
+    assert_eq!(client.mod_log_count(), 2);
+    for (index, target) in [(0, &alice), (1, &bob)] {
+        let entry: ModLogEntry = client.mod_log_entry(&index).unwrap();
+        assert_eq!(entry.action, Symbol::new(&env, "clear_field"));
+        assert_eq!(entry.target, *target);
+        assert_eq!(entry.admin, admin);
+        assert_eq!(entry.detail, Bytes::from_slice(&env, b"homepage"));
+    }
+
+    // Only the admin may clear, and batches are capped
+    let result = client.try_admin_clear_field_for(&targets, &homepage, &alice);
+    assert_eq!(result, Err(Ok(ProfileError::NotAuthorized.into())));
+    let mut many = Vec::new(&env);
+    for _ in 0..51 {
+        many.push_back(Address::generate(&env));
+    }
+    let result = client.try_admin_clear_field_for(&many, &homepage, &admin);
+    assert_eq!(result, Err(Ok(ProfileError::BatchTooLarge.into())));
+}
+
+#[test]
+fn test_render_compare() {
+    let (env, client, admin) = setup();
+    let real = register_user(&env, &client, "alice001", "Alice");
+    let fake = register_user(&env, &client, "aliice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+    let homepage = Symbol::new(&env, "homepage");
+    client.set_string_field(&bio, &String::from_str(&env, "Builder on Stellar"), &real);
+    client.set_string_field(&bio, &String::from_str(&env, "Builder on Stellar"), &fake);
+    client.set_string_field(
+        &homepage,
+        &String::from_str(&env, "https://alice.dev"),
+        &real,
+    );
+    client.set_string_field(
+        &homepage,
+        &String::from_str(&env, "https://alice.scam"),
+        &fake,
+    );
+
+    let page = render_path_as(
+        &env,
+        &client,
+        "/compare/alice001/aliice001",
+        Some(admin.clone()),
+    );
+    assert_eq!(
+        page.matches("<div class=\"profile-compare-col\">").count(),
+        2
+    );
+    assert_eq!(
+        page.matches("<div class=\"compare-field compare-match\" data-field=\"bio\">Bio: Builder on Stellar</div>")
+            .count(),
+        2
+    );
+    assert_eq!(
+        page.matches("compare-match\" data-field=\"display_name\">Alice")
+            .count(),
+        2
+    );
+    assert!(page.contains(
+        "<div class=\"compare-field\" data-field=\"homepage\">Website: https://alice.dev</div>"
+    ));
+    assert!(page.contains("@aliice001"));
+
+    // Unknown usernames get an empty column
+    let page = render_path_as(
+        &env,
+        &client,
+        "/compare/alice001/nobody001",
+        Some(admin.clone()),
+    );
+    assert!(page.contains("Profile not found"));
+    assert!(!page.contains("compare-match"));
+
+    // Only the admin may compare
+    let page = render_path_as(&env, &client, "/compare/alice001/aliice001", Some(real));
+    assert!(page.contains("Not Authorized"));
+    assert!(!page.contains("profile-compare"));
+    assert!(render_path(&env, &client, "/compare/alice001/aliice001").contains("Not Authorized"));
+}
+
+#[test]
+fn test_typed_field_getters() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let hiring = Symbol::new(&env, "hiring");
+    let score = Symbol::new(&env, "score");
+    let bio = Symbol::new(&env, "bio");
+    let missing = Symbol::new(&env, "missing");
+
+    client.set_bool_field(&hiring, &true, &user);
+    client.set_int_field(&score, &-42, &user);
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+
+    assert_eq!(client.get_bool_field(&user, &hiring), Some(true));
+    assert_eq!(client.get_int_field(&user, &score), Some(-42));
+    assert_eq!(
+        client.get_string_field(&user, &bio),
+        Some(String::from_str(&env, "Hello"))
+    );
+
+    // Mismatched types and missing fields read as None
+    assert_eq!(client.get_bool_field(&user, &score), None);
+    assert_eq!(client.get_int_field(&user, &bio), None);
+    assert_eq!(client.get_string_field(&user, &hiring), None);
+    assert_eq!(client.get_bool_field(&user, &missing), None);
+}
+
+#[test]
+fn test_viewer_context() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    client.set_string_field(
+        &Symbol::new(&env, "avatar"),
+        &String::from_str(&env, "https://example.com/a.png"),
+        &user,
+    );
+    let stranger = Address::generate(&env);
+
+    assert_eq!(
+        client.viewer_context(&Some(user.clone())),
+        ViewerContext {
+            has_profile: true,
+            username: Some(Bytes::from_slice(&env, b"alice001")),
+            display_name: Some(String::from_str(&env, "Alice")),
+            avatar: Some(String::from_str(&env, "https://example.com/a.png")),
+            is_admin: false,
+        }
+    );
+    let empty = ViewerContext {
+        has_profile: false,
+        username: None,
+        display_name: None,
+        avatar: None,
+        is_admin: false,
+    };
+    assert_eq!(client.viewer_context(&Some(stranger.clone())), empty);
+    assert_eq!(client.viewer_context(&None), empty);
+    assert!(client.viewer_context(&Some(admin)).is_admin);
+
+    assert_eq!(
+        to_std_string(&client.render_viewer_chip(&Some(user))),
+        "<span class=\"profile-chip\">\
+         <img class=\"profile-avatar\" src=\"https://example.com/a.png\" alt=\"\" />\
+         <a href=\"render:@profile:/u/alice001\">Alice</a></span>"
+    );
+    let chip = to_std_string(&client.render_viewer_chip(&Some(stranger)));
+    assert!(chip.starts_with("<span class=\"profile-chip profile-chip-anonymous\">"));
+    assert!(chip.contains("<a href=\"render:@profile:/register\">Create Profile</a>"));
+    assert_eq!(
+        to_std_string(&client.render_viewer_chip(&None)),
+        "<span class=\"profile-chip profile-chip-guest\">Not connected</span>"
+    );
+}
+
+#[test]
+fn test_daily_registrations() {
+    let (env, client, _admin) = setup();
+    // 2024-03-01 00:00:00 UTC, day 19783
+    let day = 19_783u32;
+    env.ledger()
+        .with_mut(|li| li.timestamp = day as u64 * 86_400 + 3_600);
+    register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob002", "Bob");
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = (day as u64 + 2) * 86_400);
+    register_user(&env, &client, "carol003", "Carol");
+
+    // Deletions do not reduce the counts
+    client.delete_profile(&bob);
+
+    assert_eq!(client.registrations_on(&day), 2);
+    assert_eq!(client.registrations_on(&(day + 1)), 0);
+    assert_eq!(client.registrations_on(&(day + 2)), 1);
+    assert_eq!(
+        client.registrations_between(&day, &(day + 3)),
+        Vec::from_array(&env, [2u64, 0, 1])
+    );
+    assert_eq!(client.registrations_between(&day, &day).len(), 0);
+    assert!(client.try_registrations_between(&0, &91).is_err());
+
+    let stats = render_path(&env, &client, "/stats");
+    assert!(stats.contains("- 2024-03-03: 1\n- 2024-03-02: 0\n- 2024-03-01: 2\n- 2024-02-29: 0\n"));
+}
+
+#[test]
+fn test_unchanged_values_skip_write_and_event() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+    let name: Symbol = last_event_topics(&env).get(0).unwrap().into_val(&env);
+    assert_eq!(name, Symbol::new(&env, "profile_updated"));
+
+    // Resubmitting the same values is a no-op
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+    assert_eq!(env.events().all().len(), 0);
+    client.set_display_name(&String::from_str(&env, "Alice"), &user);
+    assert_eq!(env.events().all().len(), 0);
+
+    // Real changes still emit
+    client.set_string_field(&bio, &String::from_str(&env, "Hi"), &user);
+    assert_eq!(env.events().all().len(), 1);
+    client.set_display_name(&String::from_str(&env, "Alice B"), &user);
+    assert_eq!(env.events().all().len(), 1);
+}
+
+#[test]
+fn test_updated_shows_relative_time() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_709_164_800);
+    let user = register_user(&env, &client, "alice001", "Alice");
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("Created: 2024-02-29 00:00:00 UTC · Updated: just now"));
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_709_164_800 + 2 * 86_400);
+    client.set_display_name(&String::from_str(&env, "Alice B"), &user);
+    env.ledger().with_mut(|li| li.timestamp += 3 * 3_600);
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("Created: 2024-02-29 00:00:00 UTC · Updated: 3h ago"));
+}
+
+#[test]
+fn test_embed_policy() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let approved = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let full_card = to_std_string(&client.render_profile_card(&user));
+
+    // Open mode keeps the normal rendering for everyone
+    assert_eq!(client.embed_policy(), Symbol::new(&env, EMBED_OPEN));
+    assert_eq!(
+        to_std_string(&client.render_profile_card_for(&user, &Some(stranger.clone()))),
+        full_card
+    );
+    assert_eq!(
+        to_std_string(&client.render_username_for(&user, &None)),
+        "@alice001"
+    );
+
+    client.set_embed_policy(&Symbol::new(&env, EMBED_ALLOWLIST), &admin);
+    client.allow_embedder(&approved, &admin);
+    assert!(client.is_embedder_allowed(&Some(approved.clone())));
+    assert!(!client.is_embedder_allowed(&Some(stranger.clone())));
+    assert!(!client.is_embedder_allowed(&None));
+
+    assert_eq!(
+        to_std_string(&client.render_profile_card_for(&user, &Some(approved.clone()))),
+        full_card
+    );
+    assert_eq!(
+        to_std_string(&client.render_username_for(&user, &Some(approved.clone()))),
+        "@alice001"
+    );
+
+    // Unapproved embedders get the anonymous rendering
+    let card = to_std_string(&client.render_profile_card_for(&user, &Some(stranger.clone())));
+    assert!(card.contains("profile-card-anonymous"));
+    assert!(!card.contains("alice001"));
+    let name = to_std_string(&client.render_username_for(&user, &None));
+    assert!(!name.contains("alice001"));
+
+    client.deny_embedder(&approved, &admin);
+    assert!(!client.is_embedder_allowed(&Some(approved)));
+
+    assert_eq!(
+        client.try_set_embed_policy(&Symbol::new(&env, "closed"), &admin),
+        Err(Ok(ProfileError::InvalidConfig.into()))
+    );
+    assert!(client.try_allow_embedder(&stranger, &user).is_err());
+
+    client.set_embed_policy(&Symbol::new(&env, EMBED_OPEN), &admin);
+    assert!(client.is_embedder_allowed(&None));
+}
+
+#[test]
+fn test_require_distinct_display_name() {
+    let (env, client, admin) = setup();
+
+    // Off by default
+    assert!(!client.requires_distinct_name());
+    register_user(&env, &client, "alice001", "alice001");
+
+    client.set_require_distinct_name(&true, &admin);
+    let result = client.try_register(
+        &String::from_str(&env, "bob002"),
+        &String::from_str(&env, "bob002"),
+        &Address::generate(&env),
+    );
+    assert_eq!(result, Err(Ok(ProfileError::InvalidDisplayName.into())));
+
+    // Only an exact byte match is rejected
+    register_user(&env, &client, "carol003", "Carol003");
+    register_user(&env, &client, "dave004", "Dave");
+
+    // Claiming a reservation still names the profile after the username
+    let brand = Address::generate(&env);
+    let reserved = Bytes::from_slice(&env, b"stellar001");
+    client.reserve_username_with_condition(&reserved, &brand, &Symbol::new(&env, "none"), &admin);
+    client.claim_reserved(&reserved, &brand);
+    assert_eq!(
+        client.get_by_address(&brand).unwrap().display_name,
+        String::from_str(&env, "stellar001")
+    );
+
+    client.set_require_distinct_name(&false, &admin);
+    register_user(&env, &client, "bob002", "bob002");
+}
+
+/// Transfer hook that records the last transfer it was told about.
+#[contract]
+pub struct RecordingTransferHook;
+
+#[contractimpl]
+impl RecordingTransferHook {
+    pub fn on_transfer(env: Env, username: Bytes, from: Address, to: Address) {
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "last"), &(username, from, to));
+    }
+
+    pub fn last(env: Env) -> Option<(Bytes, Address, Address)> {
+        env.storage().instance().get(&Symbol::new(&env, "last"))
+    }
+}
+
+/// Transfer hook that always fails.
+#[contract]
+pub struct BrokenTransferHook;
+
+#[contractimpl]
+impl BrokenTransferHook {
+    pub fn on_transfer(_env: Env, _username: Bytes, _from: Address, _to: Address) {
+        panic!("hook is broken")
+    }
+}
+
+#[test]
+fn test_transfer_hook() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+
+    let hook_id = env.register(RecordingTransferHook, ());
+    let hook = RecordingTransferHookClient::new(&env, &hook_id);
+    client.set_transfer_hook(&Some(hook_id.clone()), &admin);
+    assert_eq!(client.transfer_hook(), Some(hook_id));
+
+    client.transfer(&bob, &alice);
+    assert_eq!(
+        hook.last(),
+        Some((Bytes::from_slice(&env, b"alice001"), alice, bob.clone()))
+    );
+
+    // A failing hook does not revert the transfer
+    let broken = env.register(BrokenTransferHook, ());
+    client.set_transfer_hook(&Some(broken), &admin);
+    client.transfer(&carol, &bob);
+    assert_eq!(
+        client.username_of(&carol),
+        Some(Bytes::from_slice(&env, b"alice001"))
+    );
+
+    client.set_transfer_hook(&None, &admin);
+    assert_eq!(client.transfer_hook(), None);
+}
+
+#[test]
+fn test_eligibility_check() {
+    let (env, client, _admin) = setup();
+    // Registered at timestamp 0, so created_at is treated as legacy
+    let legacy = register_user(&env, &client, "legacy001", "Legacy");
+
+    let start = 1_709_164_800;
+    env.ledger().with_mut(|li| li.timestamp = start);
+    let early = register_user(&env, &client, "alice001", "Alice");
+    let deleted = register_user(&env, &client, "bob002", "Bob");
+    client.delete_profile(&deleted);
+    env.ledger().with_mut(|li| li.timestamp = start + 86_400);
+    let late = register_user(&env, &client, "carol003", "Carol");
+    let stranger = Address::generate(&env);
+
+    let addresses = Vec::from_array(&env, [legacy, early, deleted, late, stranger]);
+    assert_eq!(
+        client.eligibility_check(&addresses, &(start + 3_600)),
+        Vec::from_array(&env, [false, true, false, false, false])
+    );
+    assert_eq!(client.count_eligible(&addresses, &(start + 3_600)), 1);
+    assert_eq!(client.count_eligible(&addresses, &(start + 2 * 86_400)), 2);
+    assert_eq!(client.count_eligible(&addresses, &start), 0);
+}
+
+#[test]
+fn test_eligibility_check_batch_budget() {
+    let (env, client, _admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_709_164_800);
+
+    let mut addresses = Vec::new(&env);
+    for i in 0..90u32 {
+        let address = if i % 5 == 0 {
+            let name = std::format!("user{:04}", i);
+            register_user(&env, &client, &name, "User")
+        } else {
+            Address::generate(&env)
+        };
+        addresses.push_back(address);
+    }
+
+    // A full batch stays within the footprint and CPU limits
+    assert_eq!(client.count_eligible(&addresses, &u64::MAX), 18);
+    let cpu = env.cost_estimate().budget().cpu_instruction_cost();
+    assert!(cpu < 50_000_000, "count_eligible used {cpu} instructions");
+    assert_eq!(client.eligibility_check(&addresses, &u64::MAX).len(), 90);
+
+    addresses.push_back(Address::generate(&env));
+    assert_eq!(
+        client.try_count_eligible(&addresses, &u64::MAX),
+        Err(Ok(ProfileError::BatchTooLarge.into()))
+    );
+    assert!(client.try_eligibility_check(&addresses, &u64::MAX).is_err());
+}
+
+#[test]
+fn test_orphaned_username_repair() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob002", "Bob");
+    client.delete_profile(&bob);
+    let name = Bytes::from_slice(&env, b"alice001");
+
+    // Live and soft-deleted profiles are not orphans
+    assert!(!client.check_orphan(&name, &admin));
+    assert!(!client.check_orphan(&Bytes::from_slice(&env, b"bob002"), &admin));
+    assert!(!client.check_orphan(&Bytes::from_slice(&env, b"nobody001"), &admin));
+
+    // Simulate a failure that lost the profile but kept the mapping
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::Profile(alice.clone()));
+    });
+    assert!(client.check_orphan(&name, &admin));
+    assert!(client.try_check_orphan(&name, &alice).is_err());
+
+    client.repair_orphan(&name, &admin);
+    assert!(!client.check_orphan(&name, &admin));
+    assert_eq!(
+        client
+            .mod_log_entry(&(client.mod_log_count() - 1))
+            .unwrap()
+            .detail,
+        name
+    );
+
+    // The name can be registered again
+    register_user(&env, &client, "alice001", "Alice Again");
+    assert!(!client.check_orphan(&name, &admin));
+}
+
+#[test]
+fn test_field_history() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let payto = Symbol::new(&env, "payto");
+    let bio = Symbol::new(&env, "bio");
+
+    client.add_audited_field(&payto, &admin);
+    assert!(client.is_audited_field(&payto));
+    assert!(!client.is_audited_field(&bio));
+
+    // Six writes: the first has nothing to record, then five replacements
+    // fill the history; a seventh evicts the oldest
+    for i in 0..7u64 {
+        env.ledger().with_mut(|li| li.timestamp = 1_709_164_800 + i);
+        let value = std::format!("G{}", i);
+        client.set_string_field(&payto, &String::from_str(&env, &value), &user);
+        client.set_string_field(&bio, &String::from_str(&env, &value), &user);
+    }
+
+    let history = client.field_history(&user, &payto);
+    assert_eq!(history.len(), 5);
+    for (i, (value, replaced_at)) in history.iter().enumerate() {
+        let expected = std::format!("G{}", i + 1);
+        assert_eq!(
+            value,
+            FieldValue::StringField(String::from_str(&env, &expected))
+        );
+        assert_eq!(replaced_at, 1_709_164_800 + i as u64 + 2);
+    }
+
+    // Non-audited fields keep no history
+    assert_eq!(client.field_history(&user, &bio).len(), 0);
+
+    client.remove_audited_field(&payto, &admin);
+    client.set_string_field(&payto, &String::from_str(&env, "G7"), &user);
+    assert_eq!(client.field_history(&user, &payto).len(), 5);
+    assert!(client.try_add_audited_field(&bio, &user).is_err());
+
+    // Removals are recorded too, by the owner or the admin
+    client.add_audited_field(&payto, &admin);
+    env.ledger().with_mut(|li| li.timestamp = 1_709_164_900);
+    client.remove_field(&payto, &user);
+    let history = client.field_history(&user, &payto);
+    assert_eq!(
+        history.last().unwrap(),
+        (
+            FieldValue::StringField(String::from_str(&env, "G7")),
+            1_709_164_900
+        )
+    );
+    client.set_string_field(&payto, &String::from_str(&env, "G8"), &user);
+    client.admin_clear_field_for(&Vec::from_array(&env, [user.clone()]), &payto, &admin);
+    let history = client.field_history(&user, &payto);
+    assert_eq!(
+        history.last().unwrap().0,
+        FieldValue::StringField(String::from_str(&env, "G8"))
+    );
+
+    // History outlives the field, but not a purge
+    client.hard_delete_profile(&false, &user);
+    assert_eq!(client.field_history(&user, &payto).len(), 0);
+}
+
+#[test]
+fn test_profile_schema() {
+    let (env, client, _admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    assert_eq!(client.profile_schema(&alice), Some(PROFILE_SCHEMA_VERSION));
+    assert_eq!(client.profile_schema(&Address::generate(&env)), None);
+
+    // The version follows the profile to its new owner
+    let bob = Address::generate(&env);
+    client.transfer(&bob, &alice);
+    assert_eq!(client.profile_schema(&bob), Some(PROFILE_SCHEMA_VERSION));
+    assert_eq!(client.profile_schema(&alice), None);
+
+    // Profiles stored before versions were recorded
+    let legacy = Address::generate(&env);
+    let profile = Profile::new(
+        Bytes::from_slice(&env, b"legacy001"),
+        String::from_str(&env, "Legacy"),
+        legacy.clone(),
+        12_345,
+    );
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::Profile(legacy.clone()), &profile);
+    });
+    assert_eq!(
+        client.profile_schema(&legacy),
+        Some(UNVERSIONED_PROFILE_SCHEMA)
+    );
+}
+
+#[test]
+fn test_delete_profile_keep_name() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let name = Bytes::from_slice(&env, b"alice001");
+
+    client.delete_profile_keep_name(&alice);
+    assert!(client.get_by_address(&alice).is_none());
+    assert_eq!(client.reservation_status(&name), RESERVATION_HARD);
+
+    // Nobody else can take the name
+    let stranger = Address::generate(&env);
+    let result = client.try_register(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Fake"),
+        &stranger,
+    );
+    assert_eq!(result, Err(Ok(ProfileError::UsernameReserved.into())));
+    assert_eq!(
+        client.try_claim_reserved(&name, &stranger),
+        Err(Ok(ProfileError::NotAuthorized.into()))
+    );
+
+    // The original owner comes back through a normal registration
+    client.register(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &alice,
+    );
+    assert_eq!(client.username_of(&alice), Some(name.clone()));
+    assert_eq!(client.reservation_status(&name), RESERVATION_TAKEN);
+
+    // Banned profiles cannot keep their name
+    let bob = register_user(&env, &client, "bob002", "Bob");
+    client.ban_profile(&bob, &admin);
+    assert_eq!(
+        client.try_delete_profile_keep_name(&bob),
+        Err(Ok(ProfileError::ProfileBanned.into()))
+    );
+}
+
+#[test]
+fn test_avatars_of() {
+    let (env, client, _admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    let bob = register_user(&env, &client, "bob002", "Bob");
+    let carol = register_user(&env, &client, "carol003", "Carol");
+    let avatar = Symbol::new(&env, "avatar");
+
+    let alice_url = String::from_str(&env, "https://example.com/alice.png");
+    let bob_url = String::from_str(&env, "https://example.com/bob.png");
+    client.set_string_field(&avatar, &alice_url, &alice);
+    client.set_string_field(&avatar, &bob_url, &bob);
+
+    let avatars = client.avatars_of(&Vec::from_array(&env, [alice.clone(), bob.clone(), carol]));
+    assert_eq!(avatars.len(), 2);
+    assert_eq!(avatars.get(alice), Some(alice_url));
+    assert_eq!(avatars.get(bob.clone()), Some(bob_url));
+
+    // Deleted profiles are skipped
+    client.delete_profile(&bob);
+    assert_eq!(client.avatars_of(&Vec::from_array(&env, [bob])).len(), 0);
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..51 {
+        too_many.push_back(Address::generate(&env));
+    }
+    assert_eq!(
+        client.try_avatars_of(&too_many),
+        Err(Ok(ProfileError::BatchTooLarge.into()))
+    );
+}
+
+#[test]
+fn test_export_page() {
+    let (env, client, admin) = setup();
+    let bio = Symbol::new(&env, "bio");
+    let website = Symbol::new(&env, "website");
+
+    let mut users = std::vec::Vec::new();
+    for i in 0..25u32 {
+        let name = std::format!("user{:04}", i);
+        let user = register_user(&env, &client, &name, &std::format!("User {}", i));
+        client.set_string_field(
+            &bio,
+            &String::from_str(&env, &std::format!("Bio {}", i)),
+            &user,
+        );
+        client.set_string_field(
+            &website,
+            &String::from_str(&env, "https://example.com"),
+            &user,
+        );
+        users.push(user);
+    }
+    client.delete_profile(&users[3]);
+    client.ban_profile(&users[7], &admin);
+    client.hard_delete_profile(&false, &users[11]);
+    client.deactivate(&users[13]);
+
+    // One profile with the per-address extras set
+    let rich = users[4].clone();
+    let github = Symbol::new(&env, "github");
+    let proof = BytesN::from_array(&env, &[1; 32]);
+    client.add_alias(&Bytes::from_slice(&env, b"userfour004"), &rich);
+    client.add_link(
+        &String::from_str(&env, "Site"),
+        &String::from_str(&env, "https://example.com"),
+        &rich,
+    );
+    client.set_verified_link(&github, &String::from_str(&env, "user4"), &proof, &rich);
+    client.set_locale(&Symbol::new(&env, "pt_BR"), &rich);
+    client.set_date_style(&DateStyle::DateOnly, &rich);
+
+    // Page through the whole directory
+    let mut exported: std::vec::Vec<ProfileExport> = std::vec::Vec::new();
+    let mut start = 0;
+    while start < client.profile_count() {
+        exported.extend(client.export_page(&start, &5).iter());
+        start += 5;
+    }
+
+    // Every slot but the purged one is present, in index order
+    assert_eq!(exported.len(), 24);
+    for record in exported.iter() {
+        let i = record.index as usize;
+        assert_ne!(i, 11);
+        assert_eq!(record.profile.owner, users[i]);
+        assert_eq!(
+            record.profile.username,
+            Bytes::from_slice(&env, std::format!("user{:04}", i).as_bytes())
+        );
+        assert_eq!(
+            record.profile.display_name,
+            String::from_str(&env, &std::format!("User {}", i))
+        );
+        assert_eq!(record.profile.deleted, i == 3 || i == 7);
+        assert_eq!(record.banned, i == 7);
+        assert!(!record.hidden);
+        assert_eq!(record.deactivated, i == 13);
+        assert_eq!(record.generation, client.generation(&users[i]));
+        assert_eq!(record.schema, Some(PROFILE_SCHEMA_VERSION));
+        assert!(record.created_at_ledger.is_some());
+        assert_eq!(record.fields.len(), 2);
+        assert_eq!(
+            record.fields.get(bio.clone()),
+            Some(FieldValue::StringField(String::from_str(
+                &env,
+                &std::format!("Bio {}", i)
+            )))
+        );
+    }
+
+    let record = &exported[4];
+    assert_eq!(record.aliases, client.aliases(&rich));
+    assert_eq!(record.links.len(), 1);
+    assert_eq!(
+        record.verified_links.get(github),
+        Some((String::from_str(&env, "user4"), proof))
+    );
+    assert_eq!(record.locale, Some(Symbol::new(&env, "pt_BR")));
+    assert_eq!(record.date_style, DateStyle::DateOnly);
+    assert_eq!(exported[5].date_style, DateStyle::Full);
+    assert_eq!(exported[5].aliases.len(), 0);
+
+    assert_eq!(
+        client.try_export_page(&0, &6).err(),
+        Some(Ok(ProfileError::BatchTooLarge.into()))
+    );
+    assert_eq!(client.export_page(&25, &5).len(), 0);
+}
+
+#[test]
+fn test_whitelist_mode() {
+    let (env, client, admin) = setup();
+    let custom = Bytes::from_slice(&env, b"custom");
+
+    // Off by default; "custom" fails the pattern rules
+    assert!(!client.is_whitelist_mode());
+    assert!(!client.is_valid_username(&custom));
+
+    client.set_whitelist_mode(&true, &admin);
+    client.whitelist_username(&custom, &admin);
+    assert!(client.is_whitelisted(&custom));
+
+    register_user(&env, &client, "custom", "Custom");
+    let result = client.try_register(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &Address::generate(&env),
+    );
+    assert_eq!(result, Err(Ok(ProfileError::InvalidUsername.into())));
+
+    client.unwhitelist_username(&custom, &admin);
+    assert!(!client.is_whitelisted(&custom));
+    assert!(client
+        .try_whitelist_username(&Bytes::new(&env), &admin)
+        .is_err());
+
+    client.set_whitelist_mode(&false, &admin);
+    register_user(&env, &client, "alice001", "Alice");
+}
+
+#[test]
+fn test_similar_display_names() {
+    let (env, client, admin) = setup();
+    let real = register_user(&env, &client, "stellar001", "Stellar Foundation");
+    let fake = register_user(&env, &client, "stellar002", "Stellar  Foundatlon");
+    let other = register_user(&env, &client, "bob002", "Bob");
+
+    // Each finds the other
+    let found = client.similar_display_names(&String::from_str(&env, "Stellar Foundation"), &10);
+    assert_eq!(found, Vec::from_array(&env, [real.clone(), fake.clone()]));
+    let found = client.similar_display_names(&String::from_str(&env, "stellar foundatlon"), &10);
+    assert_eq!(found, Vec::from_array(&env, [real.clone(), fake.clone()]));
+    assert_eq!(client.display_name_collisions(&real), 1);
+    assert_eq!(client.display_name_collisions(&fake), 1);
+    assert_eq!(client.display_name_collisions(&other), 0);
+    assert_eq!(
+        client
+            .similar_display_names(&String::from_str(&env, "Stellar"), &10)
+            .len(),
+        0
+    );
+
+    // The warning shows on the admin compare page
+    let page = render_path_as(&env, &client, "/compare/stellar001/stellar002", Some(admin));
+    assert_eq!(
+        page.matches("data-field=\"similar_names\">Similar display names: 1</div>")
+            .count(),
+        2
+    );
+
+    // Renaming moves the profile out of the collision
+    client.set_display_name(&String::from_str(&env, "Totally Different"), &fake);
+    assert_eq!(client.display_name_collisions(&real), 0);
+    assert_eq!(
+        client.similar_display_names(&String::from_str(&env, "Stellar Foundation"), &10),
+        Vec::from_array(&env, [real.clone()])
+    );
+
+    // Profiles from before the index can be indexed by anyone, once
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .remove(&ProfileKey::DisplayNameIndex(normalize_display_name(
+                &env,
+                &Bytes::from_slice(&env, b"Totally Different"),
+            )));
+    });
+    let different = String::from_str(&env, "Totally Different");
+    assert_eq!(client.similar_display_names(&different, &10).len(), 0);
+    client.reindex_display_name(&fake);
+    client.reindex_display_name(&fake);
+    assert_eq!(
+        client.similar_display_names(&different, &10),
+        Vec::from_array(&env, [fake.clone()])
+    );
+    assert_eq!(
+        client.try_reindex_display_name(&Address::generate(&env)),
+        Err(Ok(ProfileError::ProfileNotFound.into()))
+    );
+
+    // Deleted profiles are not reported
+    client.set_display_name(&String::from_str(&env, "STELLAR FOUNDATION"), &other);
+    client.delete_profile(&other);
+    assert_eq!(client.display_name_collisions(&real), 0);
+    assert!(client
+        .try_similar_display_names(&String::from_str(&env, "x"), &51)
+        .is_err());
+}
+
+#[cfg(feature = "allow-reinit")]
+#[test]
+fn test_reinit() {
+    let (env, client, admin) = setup();
+    register_user(&env, &client, "alice001", "Alice");
+
+    // Refused while an admin is set
+    assert_eq!(
+        client.try_reinit(&Address::generate(&env)),
+        Err(Ok(ProfileError::AlreadyInitialized.into()))
+    );
+    assert_eq!(client.admin(), admin);
+
+    // Without a recovery address nobody can claim the lost admin role
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&ProfileKey::Admin);
+    });
+    assert_eq!(
+        client.try_reinit(&Address::generate(&env)),
+        Err(Ok(ProfileError::NotAuthorized.into()))
+    );
+
+    // Recovers a contract that lost its admin, keeping its data
+    let contract_id = env.register(UserProfileContract, ());
+    let client = UserProfileContractClient::new(&env, &contract_id);
+    let recovery = Address::generate(&env);
+    let config = InitConfig {
+        registration_fee: None,
+        registration_open: true,
+        reserved_usernames: Vec::new(&env),
+        recovery_address: Some(recovery.clone()),
+    };
+    client.init_with_config(&admin, &config);
+    register_user(&env, &client, "alice001", "Alice");
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&ProfileKey::Admin);
+    });
+    let new_admin = Address::generate(&env);
+    client.reinit(&new_admin);
+    assert!(env.auths().iter().any(|(address, _)| *address == recovery));
+    assert_eq!(client.admin(), new_admin);
+    assert_eq!(client.profile_count(), 1);
+}
+
+#[test]
+fn test_profile_stats() {
+    let (env, client, _admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, "Hi"),
+        &alice,
+    );
+    client.add_link(
+        &String::from_str(&env, "Blog"),
+        &String::from_str(&env, "https://alice.dev"),
+        &alice,
+    );
+    client.add_link(
+        &String::from_str(&env, "Code"),
+        &String::from_str(&env, "https://github.com/alice"),
+        &alice,
+    );
+
+    assert_eq!(
+        client.profile_stats(&alice),
+        ProfileStats {
+            fields: 1,
+            links: 2,
+            aliases: 0
+        }
+    );
+    assert_eq!(
+        client.profile_stats(&Address::generate(&env)),
+        ProfileStats::default()
+    );
+
+    let page = render_path(&env, &client, "/u/alice001/stats");
+    assert!(page.contains("<dl class=\"profile-stats\">"));
+    assert!(page.contains("<dt>Fields</dt><dd>1</dd>"));
+    assert!(page.contains("<dt>Links</dt><dd>2</dd>"));
+    assert!(page.contains("<dt>Aliases</dt><dd>0</dd>"));
+
+    client.delete_profile(&alice);
+    assert_eq!(client.profile_stats(&alice), ProfileStats::default());
+    assert!(render_path(&env, &client, "/u/alice001/stats").contains("Profile Not Found"));
+}
+
+#[test]
+fn test_display_name_cooldown() {
+    let (env, client, admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_709_164_800);
+    let user = register_user(&env, &client, "alice001", "Alice");
+
+    // Disabled by default
+    assert_eq!(client.display_name_cooldown(), 0);
+    client.set_display_name(&String::from_str(&env, "Alice A"), &user);
+    client.set_display_name(&String::from_str(&env, "Alice B"), &user);
+
+    client.set_display_name_cooldown(&86_400, &admin);
+    assert_eq!(
+        client.try_set_display_name(&String::from_str(&env, "Alice C"), &user),
+        Err(Ok(ProfileError::ChangeTooSoon.into()))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.set_display_name(&String::from_str(&env, "Alice C"), &user);
+    assert_eq!(
+        client.get_by_address(&user).unwrap().display_name,
+        String::from_str(&env, "Alice C")
+    );
+    assert!(client
+        .try_set_display_name(&String::from_str(&env, "Alice D"), &user)
+        .is_err());
+
+    // The cooldown follows the profile to a new owner
+    let new_owner = Address::generate(&env);
+    client.transfer(&new_owner, &user);
+    assert_eq!(
+        client.try_set_display_name(&String::from_str(&env, "Alice D"), &new_owner),
+        Err(Ok(ProfileError::ChangeTooSoon.into()))
+    );
+
+    // And across a swap
+    let bob = register_user(&env, &client, "bob_smith123", "Bob");
+    client.admin_swap_owners(&new_owner, &bob, &admin);
+    assert_eq!(
+        client.try_set_display_name(&String::from_str(&env, "Alice D"), &bob),
+        Err(Ok(ProfileError::ChangeTooSoon.into()))
+    );
+    client.set_display_name(&String::from_str(&env, "Bobby"), &new_owner);
+}
+
+#[test]
+fn test_get_full_profile() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = String::from_str(&env, "Hello!");
+    client.set_string_field(&Symbol::new(&env, "bio"), &bio, &user);
+    client.set_string_field(
+        &Symbol::new(&env, "custom"),
+        &String::from_str(&env, "x"),
+        &user,
+    );
+
+    let full: FullProfile = client.get_full_profile(&user).unwrap();
+    assert_eq!(full.profile.display_name, String::from_str(&env, "Alice"));
+    assert_eq!(
+        full.fields.get(Symbol::new(&env, "bio")),
+        Some(FieldValue::StringField(bio))
+    );
+    // Only standard fields are bundled
+    assert_eq!(full.fields.len(), 1);
+
+    assert!(client.get_full_profile(&Address::generate(&env)).is_none());
+}
+
+#[test]
+fn test_contact_section() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let contact = Symbol::new(&env, "contact");
+    client.set_string_field(
+        &contact,
+        &String::from_str(&env, "DM me <script>alert(1)</script>"),
+        &user,
+    );
+    client.set_string_field(
+        &Symbol::new(&env, "email"),
+        &String::from_str(&env, "alice@example.com"),
+        &user,
+    );
+    client.add_link(
+        &String::from_str(&env, "Matrix"),
+        &String::from_str(&env, "https://matrix.to/#/@alice:example.com"),
+        &user,
+    );
+
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("### Contact"));
+    assert!(page.contains("DM me \\<script\\>alert\\(1\\)\\</script\\>"));
+    assert!(!page.contains("<script>"));
+    assert!(page.contains("- [Matrix](https://matrix.to/#/@alice:example.com)"));
+    // Email is not linked until the owner opts in
+    assert!(!client.shows_email(&user));
+    assert!(!page.contains("mailto:"));
+
+    client.set_show_email(&true, &user);
+    let page = render_path(&env, &client, "/u/alice001");
+    assert!(page.contains("(mailto:alice@example.com)"));
+
+    client.set_show_email(&false, &user);
+    assert!(!render_path(&env, &client, "/u/alice001").contains("mailto:"));
+
+    let long = "x".repeat(MAX_CONTACT_LENGTH as usize + 1);
+    assert_eq!(
+        client.try_set_string_field(&contact, &String::from_str(&env, &long), &user),
+        Err(Ok(ProfileError::ValueTooLong.into()))
+    );
+}
+
+#[test]
+fn test_block_confusable_usernames() {
+    let (env, client, admin) = setup();
+    let alice = register_user(&env, &client, "alice001", "Alice");
+
+    // Off by default
+    assert!(!client.blocks_confusables());
+    register_user(&env, &client, "bob_o_001", "Bob");
+    register_user(&env, &client, "bob_0_001", "Bob");
+
+    client.set_block_confusables(&true, &admin);
+    let impostor = Address::generate(&env);
+    let try_register = |name: &str| {
+        client.try_register(
+            &String::from_str(&env, name),
+            &String::from_str(&env, "Alice"),
+            &impostor,
+        )
+    };
+    assert_eq!(
+        try_register("allce001"),
+        Err(Ok(ProfileError::UsernameConfusable.into()))
+    );
+
+    // Aliases and admin renames are held to the same rule
+    let dave = register_user(&env, &client, "davi_001", "Dave");
+    let lookalike = Bytes::from_slice(&env, b"allce001");
+    assert_eq!(
+        client.try_add_alias(&lookalike, &dave),
+        Err(Ok(ProfileError::UsernameConfusable.into()))
+    );
+    assert_eq!(
+        client.try_admin_rename(&dave, &lookalike, &admin),
+        Err(Ok(ProfileError::UsernameConfusable.into()))
+    );
+
+    // A rename moves the protection to the new name
+    client.admin_rename(&dave, &Bytes::from_slice(&env, b"eri1_001"), &admin);
+    assert_eq!(
+        try_register("erii_001"),
+        Err(Ok(ProfileError::UsernameConfusable.into()))
+    );
+    register_user(&env, &client, "dav1_001", "Dave");
+
+    // Digit look-alikes in the leading letters need a looser validator
+    let validator = env.register(AlphanumericValidator, ());
+    client.set_username_validator(&Some(validator), &true, &admin);
+    assert_eq!(
+        try_register("a1ice001"),
+        Err(Ok(ProfileError::UsernameConfusable.into()))
+    );
+    register_user(&env, &client, "carol001", "Carol");
+
+    // The key follows the name on transfer and is freed on release
+    let new_owner = Address::generate(&env);
+    client.transfer(&new_owner, &alice);
+    assert!(try_register("a1ice001").is_err());
+    client.hard_delete_profile(&true, &new_owner);
+    assert!(try_register("a1ice001").is_ok());
+}
+
+#[test]
+fn test_init_with_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(UserProfileContract, ());
+    let client = UserProfileContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    let brand = Bytes::from_slice(&env, b"stellar001");
+    let config = InitConfig {
+        registration_fee: Some(5_000_000),
+        registration_open: false,
+        reserved_usernames: Vec::from_array(
+            &env,
+            [brand.clone(), Bytes::from_slice(&env, b"soroban001")],
+        ),
+        recovery_address: None,
+    };
+    client.init_with_config(&admin, &config);
+
+    assert_eq!(client.admin(), admin);
+    assert_eq!(client.registration_fee(), 5_000_000);
+    assert!(client.is_paused());
+    assert_eq!(client.reserved_count(), 2);
+    assert!(!client.is_username_available(&brand));
+    assert!(!client.is_username_available(&Bytes::from_slice(&env, b"soroban001")));
+    assert!(client.is_username_available(&Bytes::from_slice(&env, b"alice001")));
+
+    // Only one initialization, with or without config
+    assert_eq!(
+        client.try_init_with_config(&admin, &config),
+        Err(Ok(ProfileError::AlreadyInitialized.into()))
+    );
+
+    // An invalid reservation rolls back the whole init
+    let contract_id = env.register(UserProfileContract, ());
+    let client = UserProfileContractClient::new(&env, &contract_id);
+    let bad = InitConfig {
+        registration_fee: None,
+        registration_open: true,
+        reserved_usernames: Vec::from_array(&env, [Bytes::from_slice(&env, b"no")]),
+        recovery_address: None,
+    };
+    assert_eq!(
+        client.try_init_with_config(&admin, &bad),
+        Err(Ok(ProfileError::InvalidUsername.into()))
+    );
+    client.init(&admin);
+    assert!(!client.is_paused());
+
+    // The initial fee is reported like any later fee change
+    let contract_id = env.register(UserProfileContract, ());
+    let client = UserProfileContractClient::new(&env, &contract_id);
+    let config = InitConfig {
+        registration_fee: Some(7),
+        registration_open: true,
+        reserved_usernames: Vec::new(&env),
+        recovery_address: None,
+    };
+    client.init_with_config(&admin, &config);
+    let (setting, (old, new, caller, _)) = last_config_change(&env);
+    assert_eq!(setting, Symbol::new(&env, "registration_fee"));
+    assert!(old.is_none());
+    let new: i128 = new.unwrap().into_val(&env);
+    assert_eq!(new, 7);
+    assert_eq!(caller, admin);
+}
+
+#[test]
+fn test_owned_usernames() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let alias = Bytes::from_slice(&env, b"alicia001");
+    client.add_alias(&alias, &user);
+
+    assert_eq!(
+        client.owned_usernames(&user),
+        Vec::from_array(&env, [Bytes::from_slice(&env, b"alice001"), alias])
+    );
+
+    client.delete_profile(&user);
+    assert!(client.owned_usernames(&user).is_empty());
+    assert!(client.owned_usernames(&Address::generate(&env)).is_empty());
+}
+
+/// Setting name and data of the last `admin_config_changed` event.
+fn last_config_change(env: &Env) -> (Symbol, (Option<Val>, Option<Val>, Address, u64)) {
+    let topics = last_event_topics(env);
+    let name: Symbol = topics.get(0).unwrap().into_val(env);
+    assert_eq!(name, Symbol::new(env, "admin_config_changed"));
+    let setting: Symbol = topics.get(1).unwrap().into_val(env);
+    (setting, last_event_data(env).into_val(env))
+}
+
+#[test]
+fn test_admin_config_changed_events() {
+    let (env, client, admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    // First-time sets report no old value
+    client.set_registration_fee(&100, &admin);
+    let (setting, (old, new, caller, timestamp)) = last_config_change(&env);
+    assert_eq!(setting, Symbol::new(&env, "registration_fee"));
+    assert!(old.is_none());
+    let new: i128 = new.unwrap().into_val(&env);
+    assert_eq!(new, 100);
+    assert_eq!(caller, admin);
+    assert_eq!(timestamp, 1_000);
+
+    client.set_registration_fee(&250, &admin);
+    let (_, (old, new, _, _)) = last_config_change(&env);
+    let (old, new): (i128, i128) = (old.unwrap().into_val(&env), new.unwrap().into_val(&env));
+    assert_eq!((old, new), (100, 250));
+
+    client.set_min_transfer_age(&3_600, &admin);
+    let (setting, (old, new, _, _)) = last_config_change(&env);
+    assert_eq!(setting, Symbol::new(&env, "min_transfer_age"));
+    assert!(old.is_none());
+    let new: u64 = new.unwrap().into_val(&env);
+    assert_eq!(new, 3_600);
+
+    // Clearing a setting reports no new value
+    let hook = Address::generate(&env);
+    client.set_transfer_hook(&Some(hook.clone()), &admin);
+    client.set_transfer_hook(&None, &admin);
+    let (setting, (old, new, _, _)) = last_config_change(&env);
+    assert_eq!(setting, Symbol::new(&env, "transfer_hook"));
+    let old: Address = old.unwrap().into_val(&env);
+    assert_eq!(old, hook);
+    assert!(new.is_none());
+}
+
+#[test]
+fn test_invite_only_registration() {
+    let (env, client, admin) = setup();
+    assert!(!client.invite_only());
+    client.set_invite_only(&true, &admin);
+
+    let invited = Address::generate(&env);
+    client.invite(&invited, &admin);
+    assert!(client.is_invited(&invited));
+    client.register(
+        &String::from_str(&env, "alice001"),
+        &String::from_str(&env, "Alice"),
+        &invited,
+    );
+    // The invite is spent on success
+    assert!(!client.is_invited(&invited));
+
+    let uninvited = Address::generate(&env);
+    assert_eq!(
+        client.try_register(
+            &String::from_str(&env, "bobby001"),
+            &String::from_str(&env, "Bob"),
+            &uninvited
+        ),
+        Err(Ok(ProfileError::NotInvited.into()))
+    );
+
+    client.invite(&uninvited, &admin);
+    client.revoke_invite(&uninvited, &admin);
+    assert!(!client.is_invited(&uninvited));
+
+    // Turning it off opens registration again
+    client.set_invite_only(&false, &admin);
+    client.register(
+        &String::from_str(&env, "bobby001"),
+        &String::from_str(&env, "Bob"),
+        &uninvited,
+    );
+    assert!(client.get_by_address(&uninvited).is_some());
+}
+
+#[test]
+fn test_canonical_usernames() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice_001", "Alice");
+    let canonical = Bytes::from_slice(&env, b"alice_001");
+    let mixed = Bytes::from_slice(&env, b"AlIcE_001");
+    assert_eq!(canonicalize_username(&mixed), canonical);
+    assert_eq!(canonicalize_username(&canonical), canonical);
+
+    // Lookups agree whatever the case
+    for name in [&canonical, &mixed] {
+        assert!(client.is_valid_username(name));
+        assert!(!client.is_username_available(name));
+        assert_eq!(client.get_by_username(name).unwrap().owner, user);
+        assert_eq!(client.expect_by_username(name).owner, user);
+        assert_eq!(
+            client.reservation_status(name),
+            client.reservation_status(&canonical)
+        );
+        assert!(!client.is_whitelisted(name));
+    }
+
+    // A name differing only in case collides
+    let other = Address::generate(&env);
+    assert_eq!(
+        client.try_register(
+            &String::from_str(&env, "Alice_001"),
+            &String::from_str(&env, "A"),
+            &other
+        ),
+        Err(Ok(ProfileError::UsernameTaken.into()))
+    );
+    assert_eq!(
+        client.try_add_alias(&mixed, &user),
+        Err(Ok(ProfileError::UsernameTaken.into()))
+    );
+
+    // Writes store the canonical form
+    client.reserve_username(&Bytes::from_slice(&env, b"Carol_001"), &admin);
+    let carol = Bytes::from_slice(&env, b"carol_001");
+    assert!(!client.is_username_available(&carol));
+    client.unreserve_username(&Bytes::from_slice(&env, b"CAROL_001"), &admin);
+    assert!(client.is_username_available(&carol));
+
+    client.add_alias(&Bytes::from_slice(&env, b"Alicia001"), &user);
+    assert_eq!(
+        client.owned_usernames(&user),
+        Vec::from_array(&env, [canonical, Bytes::from_slice(&env, b"alicia001")])
+    );
+
+    client.whitelist_username(&Bytes::from_slice(&env, b"Dave_001"), &admin);
+    assert!(client.is_whitelisted(&Bytes::from_slice(&env, b"dave_001")));
+}
+
+#[test]
+fn test_generation() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+    let bio = Symbol::new(&env, "bio");
+    assert_eq!(client.generation(&user), 0);
+
+    client.set_display_name(&String::from_str(&env, "Alice B"), &user);
+    assert_eq!(client.generation(&user), 1);
+
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+    let (_, _, generation): (Address, Symbol, u64) = last_event_data(&env).into_val(&env);
+    assert_eq!(generation, 2);
+    assert_eq!(client.generation(&user), 2);
+
+    // Unchanged values are not a change
+    client.set_string_field(&bio, &String::from_str(&env, "Hello"), &user);
+    assert_eq!(client.generation(&user), 2);
+
+    client.remove_field(&bio, &user);
+    assert_eq!(client.generation(&user), 3);
+    client.add_link(
+        &String::from_str(&env, "Site"),
+        &String::from_str(&env, "https://example.com"),
+        &user,
+    );
+    assert_eq!(client.generation(&user), 4);
+
+    // Settings that change how the profile renders count too
+    client.set_date_style(&DateStyle::DateOnly, &user);
+    client.set_show_email(&true, &user);
+    client.set_avatar_email_hash(&Bytes::from_array(&env, &[7; 16]), &user);
+    client.freeze_field(&bio, &user);
+    client.add_alias(&Bytes::from_slice(&env, b"alicia001"), &user);
+    assert_eq!(client.generation(&user), 9);
+
+    // The generation follows a transferred profile and never goes back,
+    // neither for the old owner nor for a new owner that counted higher
+    let new_owner = register_user(&env, &client, "carol003", "Carol");
+    for i in 0..12 {
+        client.add_link(
+            &String::from_str(&env, "Site"),
+            &String::from_str(&env, "https://example.com"),
+            &new_owner,
+        );
+        if i % 2 == 1 {
+            client.remove_link(&0, &new_owner);
+        }
+    }
+    client.hard_delete_profile(&true, &new_owner);
+    assert_eq!(client.generation(&new_owner), 19);
+    client.transfer(&new_owner, &user);
+    assert_eq!(client.generation(&user), 20);
+    assert_eq!(client.generation(&new_owner), 20);
+
+    client.delete_profile(&new_owner);
+    assert_eq!(client.generation(&new_owner), 21);
+    client.hard_delete_profile(&true, &new_owner);
+    assert_eq!(client.generation(&new_owner), 22);
+
+    // A batch of fields is one change
+    let bob = Address::generate(&env);
+    let mut fields = soroban_sdk::Map::new(&env);
+    let location = Symbol::new(&env, "location");
+    fields.set(
+        bio.clone(),
+        FieldValue::StringField(String::from_str(&env, "Hi")),
+    );
+    fields.set(
+        location,
+        FieldValue::StringField(String::from_str(&env, "Oslo")),
+    );
+    client.register_full(
+        &String::from_str(&env, "bobby001"),
+        &String::from_str(&env, "Bob"),
+        &fields,
+        &bob,
+    );
+    assert_eq!(client.generation(&bob), 1);
+
+    client.admin_clear_field_for(&Vec::from_array(&env, [bob.clone()]), &bio, &admin);
+    assert_eq!(client.generation(&bob), 2);
+
+    // Verified links and the hidden flag show on the page
+    let proof = BytesN::from_array(&env, &[1; 32]);
+    client.set_verified_link(
+        &Symbol::new(&env, "github"),
+        &String::from_str(&env, "bob"),
+        &proof,
+        &bob,
+    );
+    assert_eq!(client.generation(&bob), 3);
+
+    let carol = register_user(&env, &client, "carol001", "Carol");
+    client.set_auto_hide_threshold(&1, &admin);
+    client.report_profile(&bob, &Symbol::new(&env, "spam"), &carol);
+    assert_eq!(client.generation(&bob), 4);
+    client.resolve_reports(&bob, &admin);
+    assert_eq!(client.generation(&bob), 5);
+    client.unhide_profile(&bob, &admin);
+    assert_eq!(client.generation(&bob), 5);
+
+    client.ban_profile(&bob, &admin);
+    assert_eq!(client.generation(&bob), 6);
+}
+
+#[test]
+fn test_render_if_changed() {
+    let (env, client, admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice");
+
+    let hash = client.profile_hash(&user);
+    assert!(client.render_if_changed(&user, &hash, &None).is_none());
+    assert_eq!(client.profile_hash(&user), hash);
+
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, "Hi"),
+        &user,
+    );
+    let card = client.render_if_changed(&user, &hash, &None).unwrap();
+    assert_eq!(card, client.render_profile_card(&user));
+
+    let hash = client.profile_hash(&user);
+    assert!(client.render_if_changed(&user, &hash, &None).is_none());
+
+    // Render settings count even when written without a generation bump
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::DateStyle(user.clone()), &DateStyle::DateOnly);
+    });
+    assert_ne!(client.profile_hash(&user), hash);
+
+    // The embed policy applies to the embedder asking
+    client.set_embed_policy(&Symbol::new(&env, EMBED_ALLOWLIST), &admin);
+    let embedder = Address::generate(&env);
+    let card = client
+        .render_if_changed(&user, &hash, &Some(embedder.clone()))
+        .unwrap();
+    assert_eq!(card, client.render_profile_card_for(&user, &Some(embedder)));
+    assert_ne!(card, client.render_profile_card(&user));
+}
+
+#[test]
+fn test_delegated_namespace() {
+    let (env, client, admin) = setup();
+    let registrar_id = env.register(StubRegistrar, ());
+    let registrar = StubRegistrarClient::new(&env, &registrar_id);
+    let prefix = Bytes::from_slice(&env, b"acme");
+    client.delegate_namespace(&prefix, &registrar_id, &admin);
+    let (setting, (old, new, _, _)) = last_config_change(&env);
+    assert_eq!(setting, Symbol::new(&env, "namespaces"));
+    assert!(old.is_none());
+    let new: Vec<(Bytes, Address)> = new.unwrap().into_val(&env);
+    assert_eq!(new, client.namespaces());
+    assert_eq!(
+        client.namespace_registrar(&Bytes::from_slice(&env, b"acme001")),
+        Some(registrar_id.clone())
+    );
+
+    // Direct registration of a prefixed name is rejected
+    let user = Address::generate(&env);
+    let name = |s: &str| String::from_str(&env, s);
+    assert_eq!(
+        client.try_register(&name("acme001"), &name("Acme"), &user),
+        Err(Ok(ProfileError::NamespaceDelegated.into()))
+    );
+
+    // The registrar can register it for the user, with the user's consent
+    // signed inside the registrar's call
+    env.mock_all_auths_allowing_non_root_auth();
+    registrar.register_name(&client.address, &name("acme001"), &user);
+    assert!(env.auths().iter().any(|(address, _)| *address == user));
+    let profile = client
+        .get_by_username(&Bytes::from_slice(&env, b"acme001"))
+        .unwrap();
+    assert_eq!(profile.owner, user);
+
+    // Only the delegated registrar may use the namespace
+    let other = Address::generate(&env);
+    assert_eq!(
+        client.try_register_via_registrar(&name("acme002"), &name("A"), &other, &other),
+        Err(Ok(ProfileError::NotAuthorized.into()))
+    );
+
+    // Reservations assigned to the registrar are taken over; others block
+    let assigned = Bytes::from_slice(&env, b"acmeceo001");
+    client.reserve_username_with_condition(
+        &assigned,
+        &registrar_id,
+        &Symbol::new(&env, "none"),
+        &admin,
+    );
+    registrar.register_name(
+        &client.address,
+        &name("acmeceo001"),
+        &Address::generate(&env),
+    );
+    assert!(client.get_by_username(&assigned).is_some());
+    client.reserve_username(&Bytes::from_slice(&env, b"acmevip001"), &admin);
+    assert!(registrar
+        .try_register_name(
+            &client.address,
+            &name("acmevip001"),
+            &Address::generate(&env)
+        )
+        .is_err());
+
+    // Other names and aliases
+    let alice = register_user(&env, &client, "alice001", "Alice");
+    assert_eq!(
+        client.try_add_alias(&Bytes::from_slice(&env, b"acme003"), &alice),
+        Err(Ok(ProfileError::NamespaceDelegated.into()))
+    );
+    assert!(client
+        .try_delegate_namespace(&Bytes::from_slice(&env, b"acmex"), &registrar_id, &admin)
+        .is_err());
+
+    client.undelegate_namespace(&prefix, &admin);
+    let (setting, (old, new, _, _)) = last_config_change(&env);
+    assert_eq!(setting, Symbol::new(&env, "namespaces"));
+    assert!(old.is_some() && new.is_none());
+    assert!(client.namespaces().is_empty());
+    register_user(&env, &client, "acme002", "Acme");
+}
+
+#[test]
+fn test_render_profile_card_text() {
+    let (env, client, _admin) = setup();
+    let user = register_user(&env, &client, "alice001", "Alice <b>");
+
+    assert_eq!(
+        to_std_string(&client.render_profile_card_text(&user)),
+        "Alice b (@alice001) — profile:/u/alice001"
+    );
+
+    // 30 three-byte characters: the cut at 80 bytes must not split one
+    let bio = format!("<i>{}</i>\nmore", "日".repeat(30));
+    client.set_string_field(
+        &Symbol::new(&env, "bio"),
+        &String::from_str(&env, &bio),
+        &user,
+    );
+    let card = client.render_profile_card_text(&user);
+    assert!(!card.iter().any(|b| b == b'<'));
+    let card = to_std_string(&card);
+    assert!(card.starts_with(&format!("Alice b (@alice001) — i{}...", "日".repeat(26))));
+    assert!(card.ends_with(" — profile:/u/alice001"));
+
+    // The options-aware renderer has a text mode
+    let options = CardOptions {
+        text: true,
+        show_bio: false,
+        ..CardOptions::default()
+    };
+    let game = Address::generate(&env);
+    client.set_app_card_prefs(&game, &options, &game);
+    assert_eq!(
+        to_std_string(&client.render_profile_card_for_app(&user, &game)),
+        "Alice b (@alice001) — profile:/u/alice001"
+    );
+
+    // Preferences stored before the text flag still decode
+    let legacy_app = Address::generate(&env);
+    let mut legacy = soroban_sdk::Map::<Symbol, bool>::new(&env);
+    let stored = [
+        ("compact", true),
+        ("show_avatar", true),
+        ("show_bio", false),
+        ("show_joined", true),
+    ];
+    for (key, value) in stored {
+        legacy.set(Symbol::new(&env, key), value);
+    }
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&ProfileKey::AppCardPrefs(legacy_app.clone()), &legacy);
+    });
+    assert_eq!(
+        client.app_card_prefs(&legacy_app),
+        Some(CardOptions {
+            compact: true,
+            ..CardOptions::default()
+        })
+    );
+
+    client.delete_profile(&user);
+    assert_eq!(
+        to_std_string(&client.render_profile_card_text(&user)),
+        "[deleted]"
+    );
+}